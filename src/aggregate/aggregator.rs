@@ -0,0 +1,313 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::condition::evaluate;
+use crate::error::{TableError, TableResult};
+use crate::types::{AttributeValue, Item, KeyValue, encode_ordered_key};
+use crate::utils::compare_values;
+
+use super::decimal::Total;
+use super::request::{Aggregate, Aggregation};
+use super::result::{AggregateGroup, AggregateResult, AggregateValue};
+
+#[derive(Debug)]
+enum Accumulator {
+    Count(usize),
+    Total {
+        total: Total,
+        seen: usize,
+    },
+    Extreme {
+        value: Option<AttributeValue>,
+        keep: Ordering,
+    },
+}
+
+impl Accumulator {
+    fn new(aggregate: &Aggregate) -> Self {
+        match aggregate {
+            Aggregate::Count | Aggregate::CountOf(_) => Self::Count(0),
+            Aggregate::Sum(_) | Aggregate::Avg(_) => Self::Total {
+                total: Total::new(),
+                seen: 0,
+            },
+            Aggregate::Min(_) => Self::Extreme {
+                value: None,
+                keep: Ordering::Less,
+            },
+            Aggregate::Max(_) => Self::Extreme {
+                value: None,
+                keep: Ordering::Greater,
+            },
+        }
+    }
+
+    fn add(&mut self, aggregate: &Aggregate, item: &Item) -> TableResult<()> {
+        let value = match aggregate {
+            Aggregate::Count => None,
+            Aggregate::CountOf(path)
+            | Aggregate::Sum(path)
+            | Aggregate::Avg(path)
+            | Aggregate::Min(path)
+            | Aggregate::Max(path) => path.resolve(item),
+        };
+
+        match self {
+            Self::Count(count) => {
+                if matches!(aggregate, Aggregate::Count) || value.is_some() {
+                    *count += 1;
+                }
+            }
+            Self::Total { total, seen } => {
+                if let Some(AttributeValue::N(n)) = value {
+                    total.add(n);
+                    *seen += 1;
+                }
+            }
+            Self::Extreme {
+                value: current,
+                keep,
+            } => {
+                let Some(
+                    value @ (AttributeValue::S(_) | AttributeValue::N(_) | AttributeValue::B(_)),
+                ) = value
+                else {
+                    return Ok(());
+                };
+                let replace = match current {
+                    None => true,
+                    Some(current) => {
+                        compare_values(value, current).map_err(|_| {
+                            TableError::validation_error(format!(
+                                "{} saw both {} and {} values",
+                                aggregate,
+                                current.type_name(),
+                                value.type_name()
+                            ))
+                        })? == *keep
+                    }
+                };
+                if replace {
+                    *current = Some(value.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, aggregate: &Aggregate) -> AggregateValue {
+        match self {
+            Self::Count(count) => AggregateValue::Count(count),
+            Self::Total { seen: 0, .. } => AggregateValue::Null,
+            Self::Total { total, seen } => match aggregate {
+                Aggregate::Avg(_) => AggregateValue::Number(total.average(seen)),
+                _ => AggregateValue::Number(total.to_number()),
+            },
+            Self::Extreme { value, .. } => {
+                value.map_or(AggregateValue::Null, AggregateValue::Value)
+            }
+        }
+    }
+}
+
+// groups sort missing values first, then numbers, strings, binaries and everything else
+type GroupKey = Vec<(u8, Vec<u8>)>;
+
+fn group_key_part(value: Option<&AttributeValue>) -> (u8, Vec<u8>) {
+    match value {
+        None => (0, Vec::new()),
+        Some(AttributeValue::N(n)) => (1, encode_ordered_key(&KeyValue::N(n.clone()))),
+        Some(AttributeValue::S(s)) => (2, s.as_bytes().to_vec()),
+        Some(AttributeValue::B(b)) => (3, b.clone()),
+        Some(other) => (4, format!("{:?}", other).into_bytes()),
+    }
+}
+
+struct Group {
+    key: Vec<Option<AttributeValue>>,
+    accumulators: Vec<Accumulator>,
+}
+
+// folds items one at a time, so nothing but the running totals is kept
+pub(crate) struct Aggregator<'a> {
+    aggregation: &'a Aggregation,
+    groups: BTreeMap<GroupKey, Group>,
+    count: usize,
+}
+
+impl<'a> Aggregator<'a> {
+    pub(crate) fn new(aggregation: &'a Aggregation) -> TableResult<Self> {
+        if aggregation.aggregates.is_empty() {
+            return Err(TableError::validation_error(
+                "an aggregation needs at least one aggregate",
+            ));
+        }
+        Ok(Self {
+            aggregation,
+            groups: BTreeMap::new(),
+            count: 0,
+        })
+    }
+
+    pub(crate) fn add(&mut self, item: &Item) -> TableResult<()> {
+        if let Some(ref filter) = self.aggregation.filter
+            && !evaluate(filter, item).unwrap_or(false)
+        {
+            return Ok(());
+        }
+        self.count += 1;
+
+        let values: Vec<Option<&AttributeValue>> = self
+            .aggregation
+            .group_by
+            .iter()
+            .map(|path| path.resolve(item))
+            .collect();
+        let aggregates = &self.aggregation.aggregates;
+        let group = self
+            .groups
+            .entry(values.iter().map(|v| group_key_part(*v)).collect())
+            .or_insert_with(|| Group {
+                key: values.iter().map(|v| v.cloned()).collect(),
+                accumulators: aggregates.iter().map(Accumulator::new).collect(),
+            });
+        for (accumulator, aggregate) in group.accumulators.iter_mut().zip(aggregates) {
+            accumulator.add(aggregate, item)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> AggregateResult {
+        let aggregates = &self.aggregation.aggregates;
+        if self.groups.is_empty() && self.aggregation.group_by.is_empty() {
+            self.groups.insert(
+                GroupKey::new(),
+                Group {
+                    key: Vec::new(),
+                    accumulators: aggregates.iter().map(Accumulator::new).collect(),
+                },
+            );
+        }
+
+        let groups = self
+            .groups
+            .into_values()
+            .map(|group| AggregateGroup {
+                key: group.key,
+                values: group
+                    .accumulators
+                    .into_iter()
+                    .zip(aggregates)
+                    .map(|(accumulator, aggregate)| {
+                        (aggregate.label(), accumulator.finish(aggregate))
+                    })
+                    .collect(),
+            })
+            .collect();
+        AggregateResult {
+            groups,
+            count: self.count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::attr;
+
+    fn order(customer: &str, total: &str) -> Item {
+        Item::new()
+            .with_s("customer", customer)
+            .with("total", AttributeValue::N(total.to_string()))
+    }
+
+    fn run(aggregation: &Aggregation, items: &[Item]) -> TableResult<AggregateResult> {
+        let mut aggregator = Aggregator::new(aggregation)?;
+        for item in items {
+            aggregator.add(item)?;
+        }
+        Ok(aggregator.finish())
+    }
+
+    #[test]
+    fn totals_without_groups() {
+        let items = [
+            order("a", "10.10"),
+            order("b", "0.2"),
+            order("a", "5"),
+            Item::new()
+                .with_s("customer", "c")
+                .with_bool("total", false),
+        ];
+        let aggregation = Aggregation::new()
+            .count()
+            .count_of("total")
+            .sum("total")
+            .avg("total")
+            .min("total")
+            .max("customer");
+        let result = run(&aggregation, &items).unwrap();
+
+        assert_eq!(result.groups.len(), 1);
+        assert_eq!(result.get("count"), Some(&AggregateValue::Count(4)));
+        assert_eq!(result.get("count(total)"), Some(&AggregateValue::Count(4)));
+        assert_eq!(result.get("sum(total)").unwrap().as_number(), Some("15.3"));
+        assert_eq!(result.get("avg(total)").unwrap().as_number(), Some("5.1"));
+        assert_eq!(result.get("min(total)").unwrap().as_number(), Some("0.2"));
+        assert_eq!(
+            result.get("max(customer)").unwrap().as_value(),
+            Some(&AttributeValue::S("c".into()))
+        );
+    }
+
+    #[test]
+    fn groups_normalize_numbers_and_keep_missing_values() {
+        let items = [
+            Item::new().with_n("size", "1.50").with_n("qty", 2),
+            Item::new().with_n("size", "1.5").with_n("qty", 3),
+            Item::new().with_n("size", 10).with_n("qty", 1),
+            Item::new().with_n("qty", 7),
+        ];
+        let aggregation = Aggregation::new().sum("qty").group_by("size");
+        let result = run(&aggregation, &items).unwrap();
+
+        let keys: Vec<_> = result.groups.iter().map(|g| g.key.clone()).collect();
+        assert_eq!(
+            keys,
+            [
+                vec![None],
+                vec![Some(AttributeValue::N("1.50".into()))],
+                vec![Some(AttributeValue::N("10".into()))],
+            ]
+        );
+        let group = result.group(&[Some(AttributeValue::N("1.50".into()))]);
+        assert_eq!(
+            group.unwrap().get("sum(qty)").unwrap().as_number(),
+            Some("5")
+        );
+    }
+
+    #[test]
+    fn empty_inputs_filters_and_errors() {
+        let aggregation = Aggregation::new().count().sum("total");
+        let result = run(&aggregation, &[]).unwrap();
+        assert_eq!(result.get("count"), Some(&AggregateValue::Count(0)));
+        assert!(result.get("sum(total)").unwrap().is_null());
+
+        let grouped = Aggregation::new().count().group_by("customer");
+        assert!(run(&grouped, &[]).unwrap().groups.is_empty());
+
+        let filtered = Aggregation::new()
+            .sum("total")
+            .filter(attr("customer").eq("a"));
+        let result = run(&filtered, &[order("a", "1"), order("b", "2")]).unwrap();
+        assert_eq!(result.count, 1);
+        assert_eq!(result.get("sum(total)").unwrap().as_f64(), Some(1.0));
+
+        let mixed = [order("a", "1"), Item::new().with_s("total", "x")];
+        let err = run(&Aggregation::new().max("total"), &mixed).unwrap_err();
+        assert!(err.is_validation_error());
+        assert!(Aggregator::new(&Aggregation::new()).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use std::fmt;
+
+// the most digits after the point a number can carry, as for `N`
+const MAX_SCALE: u32 = 38;
+// extra digits averages are computed to
+const AVERAGE_DIGITS: u32 = 16;
+
+// mantissa * 10^-scale, exact while it fits in an i128
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub(crate) const ZERO: Decimal = Decimal {
+        mantissa: 0,
+        scale: 0,
+    };
+
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let (negative, rest) = match s.as_bytes().first()? {
+            b'-' => (true, &s[1..]),
+            b'+' => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (digits, exp) = match rest.find(['e', 'E']) {
+            Some(i) => (&rest[..i], rest[i + 1..].parse::<i64>().ok()?),
+            None => (rest, 0),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if int.is_empty() && frac.is_empty() {
+            return None;
+        }
+
+        let mut mantissa: i128 = 0;
+        for d in int.bytes().chain(frac.bytes()) {
+            if !d.is_ascii_digit() {
+                return None;
+            }
+            mantissa = mantissa.checked_mul(10)?.checked_add((d - b'0') as i128)?;
+        }
+        let mut scale = frac.len() as i64 - exp;
+        if scale < 0 {
+            mantissa = mantissa.checked_mul(10i128.checked_pow(u32::try_from(-scale).ok()?)?)?;
+            scale = 0;
+        }
+        let scale = u32::try_from(scale).ok().filter(|&s| s <= MAX_SCALE)?;
+        if negative {
+            mantissa = -mantissa;
+        }
+        Some(Self { mantissa, scale }.normalized())
+    }
+
+    pub(crate) fn checked_add(self, other: Self) -> Option<Self> {
+        let scale = self.scale.max(other.scale);
+        let sum = self
+            .rescaled(scale)?
+            .mantissa
+            .checked_add(other.rescaled(scale)?.mantissa)?;
+        Some(
+            Self {
+                mantissa: sum,
+                scale,
+            }
+            .normalized(),
+        )
+    }
+
+    // rounded half away from zero, to as many extra digits as fit
+    pub(crate) fn checked_div(self, divisor: usize) -> Option<Self> {
+        let divisor = i128::try_from(divisor).ok().filter(|&d| d > 0)?;
+        (0..=AVERAGE_DIGITS).rev().find_map(|extra| {
+            let scale = self.scale + extra;
+            if scale > MAX_SCALE {
+                return None;
+            }
+            let scaled = self.mantissa.checked_mul(10i128.pow(extra))?;
+            let mut quotient = scaled / divisor;
+            if (scaled % divisor).unsigned_abs() * 2 >= divisor.unsigned_abs() {
+                quotient += scaled.signum();
+            }
+            Some(
+                Self {
+                    mantissa: quotient,
+                    scale,
+                }
+                .normalized(),
+            )
+        })
+    }
+
+    pub(crate) fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    fn rescaled(self, scale: u32) -> Option<Self> {
+        let mantissa = self
+            .mantissa
+            .checked_mul(10i128.checked_pow(scale - self.scale)?)?;
+        Some(Self { mantissa, scale })
+    }
+
+    fn normalized(mut self) -> Self {
+        while self.scale > 0 && self.mantissa % 10 == 0 {
+            self.mantissa /= 10;
+            self.scale -= 1;
+        }
+        self
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int, frac)
+    }
+}
+
+// falls back to floating point once the exact sum no longer fits
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Total {
+    Exact(Decimal),
+    Approximate(f64),
+}
+
+impl Total {
+    pub(crate) fn new() -> Self {
+        Self::Exact(Decimal::ZERO)
+    }
+
+    pub(crate) fn add(&mut self, n: &str) {
+        match self {
+            Self::Exact(total) => {
+                if let Some(sum) = Decimal::parse(n).and_then(|v| total.checked_add(v)) {
+                    *total = sum;
+                } else if let Ok(v) = n.parse::<f64>() {
+                    *self = Self::Approximate(total.to_f64() + v);
+                }
+            }
+            Self::Approximate(total) => {
+                if let Ok(v) = n.parse::<f64>() {
+                    *total += v;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn to_number(self) -> String {
+        match self {
+            Self::Exact(total) => total.to_string(),
+            Self::Approximate(total) => total.to_string(),
+        }
+    }
+
+    pub(crate) fn average(self, count: usize) -> String {
+        match self {
+            Self::Exact(total) => match total.checked_div(count) {
+                Some(average) => average.to_string(),
+                None => (total.to_f64() / count as f64).to_string(),
+            },
+            Self::Approximate(total) => (total / count as f64).to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        Decimal::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parses_and_formats() {
+        for (input, expected) in [
+            ("0", "0"),
+            ("-12.500", "-12.5"),
+            ("+.25", "0.25"),
+            ("1e3", "1000"),
+            ("12.5E-3", "0.0125"),
+            ("0.000", "0"),
+        ] {
+            assert_eq!(d(input).to_string(), expected, "{}", input);
+        }
+        assert!(Decimal::parse("abc").is_none());
+        assert!(Decimal::parse(".").is_none());
+        assert!(Decimal::parse("1e40").is_none());
+    }
+
+    #[test]
+    fn exact_sums() {
+        let sum = d("0.1").checked_add(d("0.2")).unwrap();
+        assert_eq!(sum.to_string(), "0.3");
+        let sum = d("-5").checked_add(d("2.75")).unwrap();
+        assert_eq!(sum.to_string(), "-2.25");
+    }
+
+    #[test]
+    fn averages_round_half_away_from_zero() {
+        assert_eq!(d("10").checked_div(4).unwrap().to_string(), "2.5");
+        assert_eq!(
+            d("2").checked_div(3).unwrap().to_string(),
+            "0.6666666666666667"
+        );
+        assert_eq!(
+            d("-2").checked_div(3).unwrap().to_string(),
+            "-0.6666666666666667"
+        );
+    }
+
+    #[test]
+    fn totals_fall_back_to_floats() {
+        let mut total = Total::new();
+        total.add("0.1");
+        total.add("0.2");
+        assert_eq!(total.to_number(), "0.3");
+
+        total.add("1e60");
+        assert!(matches!(total, Total::Approximate(_)));
+        assert_eq!(total.to_number().parse::<f64>().unwrap(), 1e60);
+    }
+}
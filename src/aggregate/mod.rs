@@ -0,0 +1,8 @@
+mod aggregator;
+mod decimal;
+mod request;
+mod result;
+
+pub(crate) use aggregator::Aggregator;
+pub use request::{Aggregate, Aggregation};
+pub use result::{AggregateGroup, AggregateResult, AggregateValue};
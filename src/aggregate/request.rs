@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::condition::{AttributePath, Condition};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aggregate {
+    // every matching item
+    Count,
+    // items that have the attribute
+    CountOf(AttributePath),
+    // numeric values only; anything else is skipped
+    Sum(AttributePath),
+    Avg(AttributePath),
+    // strings, numbers or binaries, which can't be mixed
+    Min(AttributePath),
+    Max(AttributePath),
+}
+
+impl Aggregate {
+    // how the aggregate is looked up in results, e.g. `sum(order.total)`
+    pub fn label(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count => write!(f, "count"),
+            Self::CountOf(path) => write!(f, "count({})", path),
+            Self::Sum(path) => write!(f, "sum({})", path),
+            Self::Avg(path) => write!(f, "avg({})", path),
+            Self::Min(path) => write!(f, "min({})", path),
+            Self::Max(path) => write!(f, "max({})", path),
+        }
+    }
+}
+
+// applied on top of the request's own filter
+#[derive(Debug, Clone, Default)]
+pub struct Aggregation {
+    pub(crate) aggregates: Vec<Aggregate>,
+    pub(crate) group_by: Vec<AttributePath>,
+    pub(crate) filter: Option<Condition>,
+}
+
+impl Aggregation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregates.push(aggregate);
+        self
+    }
+
+    pub fn count(self) -> Self {
+        self.aggregate(Aggregate::Count)
+    }
+
+    pub fn count_of(self, path: impl Into<AttributePath>) -> Self {
+        self.aggregate(Aggregate::CountOf(path.into()))
+    }
+
+    pub fn sum(self, path: impl Into<AttributePath>) -> Self {
+        self.aggregate(Aggregate::Sum(path.into()))
+    }
+
+    pub fn avg(self, path: impl Into<AttributePath>) -> Self {
+        self.aggregate(Aggregate::Avg(path.into()))
+    }
+
+    pub fn min(self, path: impl Into<AttributePath>) -> Self {
+        self.aggregate(Aggregate::Min(path.into()))
+    }
+
+    pub fn max(self, path: impl Into<AttributePath>) -> Self {
+        self.aggregate(Aggregate::Max(path.into()))
+    }
+
+    // items missing a group-by attribute form their own group
+    pub fn group_by(mut self, path: impl Into<AttributePath>) -> Self {
+        self.group_by.push(path.into());
+        self
+    }
+
+    pub fn filter(mut self, condition: Condition) -> Self {
+        self.filter = Some(condition);
+        self
+    }
+
+    pub fn aggregates(&self) -> &[Aggregate] {
+        &self.aggregates
+    }
+}
@@ -0,0 +1,83 @@
+use crate::types::AttributeValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AggregateValue {
+    Count(usize),
+    // sums and averages, formatted like an `N` value
+    Number(String),
+    // min and max keep the attribute's own value
+    Value(AttributeValue),
+    // no values to aggregate
+    Null,
+}
+
+impl AggregateValue {
+    pub fn as_count(&self) -> Option<usize> {
+        match self {
+            Self::Count(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&str> {
+        match self {
+            Self::Number(n) => Some(n),
+            Self::Value(AttributeValue::N(n)) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Count(n) => Some(*n as f64),
+            _ => self.as_number()?.parse().ok(),
+        }
+    }
+
+    pub fn as_value(&self) -> Option<&AttributeValue> {
+        match self {
+            Self::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateGroup {
+    // one value per group-by path, `None` where the item lacked it
+    pub key: Vec<Option<AttributeValue>>,
+    // in the order the aggregates were requested
+    pub values: Vec<(String, AggregateValue)>,
+}
+
+impl AggregateGroup {
+    pub fn get(&self, label: &str) -> Option<&AggregateValue> {
+        self.values
+            .iter()
+            .find(|(name, _)| name == label)
+            .map(|(_, value)| value)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AggregateResult {
+    // ordered by key; without group-by there is exactly one group, even when nothing matched
+    pub groups: Vec<AggregateGroup>,
+    // items that passed every filter
+    pub count: usize,
+}
+
+impl AggregateResult {
+    // from the first group, which is the only one without group-by
+    pub fn get(&self, label: &str) -> Option<&AggregateValue> {
+        self.groups.first()?.get(label)
+    }
+
+    pub fn group(&self, key: &[Option<AttributeValue>]) -> Option<&AggregateGroup> {
+        self.groups.iter().find(|group| group.key == key)
+    }
+}
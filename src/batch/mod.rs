@@ -4,4 +4,5 @@ mod types;
 
 pub use executor::BatchExecutor;
 pub use request::{BatchGetRequest, BatchWriteRequest};
+pub(crate) use types::MAX_BATCH_WRITE_ITEMS;
 pub use types::{BatchGetResult, BatchWriteItem, BatchWriteResult};
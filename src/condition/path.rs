@@ -1,3 +1,4 @@
+use std::fmt;
 use std::str;
 
 use crate::types::{AttributeValue, Item};
@@ -55,6 +56,12 @@ impl AttributePath {
         &self.segments
     }
 
+    // one path is the other or lies inside it
+    pub(crate) fn overlaps(&self, other: &AttributePath) -> bool {
+        let shared = self.segments.len().min(other.segments.len());
+        self.segments[..shared] == other.segments[..shared]
+    }
+
     pub fn resolve<'a>(&self, item: &'a Item) -> Option<&'a AttributeValue> {
         if self.segments.is_empty() {
             return None;
@@ -78,20 +85,68 @@ impl AttributePath {
     }
 }
 
-// TODO: do we need these?
-// currently using a cleaner API than legacy string format
+impl fmt::Display for AttributePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Key(k) if i == 0 => write!(f, "{}", k)?,
+                PathSegment::Key(k) => write!(f, ".{}", k)?,
+                PathSegment::Index(idx) => write!(f, "[{}]", idx)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// "a.b[2]" is `a`, `b`, then element 2: dots split names and a trailing `[n]` indexes a list,
+// with no other rules, so "a.b-c" is `a` then `b-c` and "in" is the name "in". a step whose
+// brackets aren't list indexes stays a name as written. use `AttributePath::new` for a name
+// holding dots, and `AttributePath::parse` to have malformed paths rejected
 impl From<&str> for AttributePath {
     fn from(s: &str) -> Self {
-        Self::new(s)
+        let mut path = Self::empty();
+        for step in s.split('.') {
+            match step_indexes(step) {
+                Some((name, indexes)) => {
+                    path = path.key(name);
+                    for i in indexes {
+                        path = path.index(i);
+                    }
+                }
+                None => path = path.key(step),
+            }
+        }
+        path
     }
 }
 
 impl From<String> for AttributePath {
     fn from(s: String) -> Self {
-        Self::new(s)
+        Self::from(s.as_str())
     }
 }
 
+// "tags[1][2]" is the name "tags" and indexes 1 and 2; None when the brackets don't hold them
+fn step_indexes(step: &str) -> Option<(&str, Vec<usize>)> {
+    let Some(open) = step.find('[') else {
+        return Some((step, Vec::new()));
+    };
+    let (name, mut rest) = step.split_at(open);
+    if name.is_empty() {
+        return None;
+    }
+    let mut indexes = Vec::new();
+    while !rest.is_empty() {
+        let (index, after) = rest.strip_prefix('[')?.split_once(']')?;
+        if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        indexes.push(index.parse().ok()?);
+        rest = after;
+    }
+    Some((name, indexes))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,4 +247,14 @@ mod tests {
             assert!(AttributePath::new("list").index(1).resolve(&item).is_none());
         }
     }
+
+    #[test]
+    fn display() {
+        let path = AttributePath::new("data")
+            .key("items")
+            .index(3)
+            .key("value");
+        assert_eq!(path.to_string(), "data.items[3].value");
+        assert_eq!(AttributePath::new("pk").to_string(), "pk");
+    }
 }
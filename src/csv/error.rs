@@ -0,0 +1,53 @@
+use std::fmt;
+
+use crate::error::TableError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    Syntax { line: usize, message: String },
+    Io(String),
+}
+
+impl CsvError {
+    pub fn syntax(line: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::Syntax { line, .. } => Some(*line),
+            Self::Io(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line, message } => {
+                write!(f, "invalid csv on line {}: {}", line, message)
+            }
+            Self::Io(msg) => write!(f, "io error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<std::io::Error> for CsvError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+impl From<CsvError> for TableError {
+    fn from(e: CsvError) -> Self {
+        match e {
+            CsvError::Io(msg) => Self::Io(msg),
+            e => Self::Encoding(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::BTreeSet;
+
+use crate::json::{JsonValue, attribute_from_plain_json, attribute_to_plain_json, parse};
+use crate::types::AttributeValue;
+use crate::utils::base64_encode;
+
+pub const DEFAULT_SET_DELIMITER: char = ';';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnType {
+    S,
+    N,
+    Bool,
+    Ss { delimiter: char },
+    Ns { delimiter: char },
+    // a json object or array, stored as M or L
+    Json,
+}
+
+impl ColumnType {
+    pub fn ss() -> Self {
+        Self::Ss {
+            delimiter: DEFAULT_SET_DELIMITER,
+        }
+    }
+
+    pub fn ns() -> Self {
+        Self::Ns {
+            delimiter: DEFAULT_SET_DELIMITER,
+        }
+    }
+
+    pub fn parse(&self, raw: &str) -> Result<AttributeValue, String> {
+        match self {
+            Self::S => Ok(AttributeValue::S(raw.to_string())),
+            Self::N => parse_number(raw).map(AttributeValue::N),
+            Self::Bool => match raw.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(AttributeValue::Bool(true)),
+                "false" | "0" | "no" => Ok(AttributeValue::Bool(false)),
+                _ => Err(format!("invalid bool: {}", raw)),
+            },
+            Self::Ss { delimiter } => {
+                let set: BTreeSet<_> = split_set(raw, *delimiter).map(str::to_string).collect();
+                non_empty(set, raw).map(AttributeValue::Ss)
+            }
+            Self::Ns { delimiter } => split_set(raw, *delimiter)
+                .map(parse_number)
+                .collect::<Result<BTreeSet<_>, _>>()
+                .and_then(|set| non_empty(set, raw))
+                .map(AttributeValue::Ns),
+            Self::Json => match parse(raw).map_err(|e| e.to_string())? {
+                value @ (JsonValue::Object(_) | JsonValue::Array(_)) => {
+                    Ok(attribute_from_plain_json(&value))
+                }
+                other => Err(format!(
+                    "expected json object or array, found {}",
+                    other.type_name()
+                )),
+            },
+        }
+    }
+}
+
+fn parse_number(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    match parse(raw) {
+        Ok(JsonValue::Number(n)) => Ok(n),
+        _ => Err(format!("invalid number: {}", raw)),
+    }
+}
+
+fn split_set(raw: &str, delimiter: char) -> impl Iterator<Item = &str> {
+    raw.split(delimiter)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+// a cell of only delimiters and spaces has no elements, and DynamoDB has no empty sets
+fn non_empty(set: BTreeSet<String>, raw: &str) -> Result<BTreeSet<String>, String> {
+    if set.is_empty() {
+        Err(format!("empty set: {:?}", raw))
+    } else {
+        Ok(set)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub column: String,
+    pub attribute: String,
+    pub column_type: ColumnType,
+    pub is_key: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvMapping {
+    columns: Vec<ColumnMapping>,
+}
+
+impl CsvMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn key(self, column: impl Into<String>, column_type: ColumnType) -> Self {
+        let column = column.into();
+        self.push(column.clone(), column, column_type, true)
+    }
+
+    pub fn key_as(
+        self,
+        column: impl Into<String>,
+        attribute: impl Into<String>,
+        column_type: ColumnType,
+    ) -> Self {
+        self.push(column.into(), attribute.into(), column_type, true)
+    }
+
+    pub fn column(self, column: impl Into<String>, column_type: ColumnType) -> Self {
+        let column = column.into();
+        self.push(column.clone(), column, column_type, false)
+    }
+
+    pub fn column_as(
+        self,
+        column: impl Into<String>,
+        attribute: impl Into<String>,
+        column_type: ColumnType,
+    ) -> Self {
+        self.push(column.into(), attribute.into(), column_type, false)
+    }
+
+    fn push(
+        mut self,
+        column: String,
+        attribute: String,
+        column_type: ColumnType,
+        is_key: bool,
+    ) -> Self {
+        self.columns.push(ColumnMapping {
+            column,
+            attribute,
+            column_type,
+            is_key,
+        });
+        self
+    }
+
+    pub fn columns(&self) -> &[ColumnMapping] {
+        &self.columns
+    }
+
+    pub fn key_columns(&self) -> impl Iterator<Item = &ColumnMapping> {
+        self.columns.iter().filter(|c| c.is_key)
+    }
+}
+
+// flatten a value into a single cell; inverse of the matching ColumnType where one exists
+pub fn format_cell(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::S(s) => s.clone(),
+        AttributeValue::N(n) => n.clone(),
+        AttributeValue::Bool(b) => b.to_string(),
+        AttributeValue::Null => String::new(),
+        AttributeValue::Ss(set) | AttributeValue::Ns(set) => join_set(set.iter().cloned()),
+        AttributeValue::B(b) => base64_encode(b),
+        AttributeValue::Bs(set) => join_set(set.iter().map(|b| base64_encode(b))),
+        AttributeValue::M(_) | AttributeValue::L(_) => attribute_to_plain_json(value).to_string(),
+    }
+}
+
+fn join_set(values: impl Iterator<Item = String>) -> String {
+    values
+        .collect::<Vec<_>>()
+        .join(&DEFAULT_SET_DELIMITER.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_scalars() {
+        assert_eq!(
+            ColumnType::N.parse(" 12.5 "),
+            Ok(AttributeValue::N("12.5".into()))
+        );
+        assert!(ColumnType::N.parse("twelve").is_err());
+        assert_eq!(
+            ColumnType::Bool.parse("Yes"),
+            Ok(AttributeValue::Bool(true))
+        );
+        assert!(ColumnType::Bool.parse("maybe").is_err());
+    }
+
+    #[test]
+    fn parses_sets() {
+        assert_eq!(
+            ColumnType::ss().parse("b; a;;b"),
+            Ok(AttributeValue::Ss(
+                ["a".to_string(), "b".to_string()].into()
+            ))
+        );
+        assert_eq!(
+            ColumnType::Ns { delimiter: '|' }.parse("1|2"),
+            Ok(AttributeValue::Ns(
+                ["1".to_string(), "2".to_string()].into()
+            ))
+        );
+        assert!(ColumnType::ns().parse("1;x").is_err());
+        assert_eq!(
+            ColumnType::ss().parse(" ; "),
+            Err("empty set: \" ; \"".to_string())
+        );
+        assert!(ColumnType::ns().parse(";;").is_err());
+    }
+
+    #[test]
+    fn parses_json_documents() {
+        let value = ColumnType::Json.parse(r#"{"a": [1, "x", null]}"#).unwrap();
+        let map = value.as_m().unwrap();
+        assert_eq!(
+            map.get("a"),
+            Some(&AttributeValue::L(vec![
+                AttributeValue::N("1".into()),
+                AttributeValue::S("x".into()),
+                AttributeValue::Null,
+            ]))
+        );
+        assert!(ColumnType::Json.parse("42").is_err());
+        assert!(ColumnType::Json.parse("{").is_err());
+    }
+
+    #[test]
+    fn cells_roundtrip_through_column_types() {
+        let set = AttributeValue::Ss(["x".to_string(), "y".to_string()].into());
+        assert_eq!(ColumnType::ss().parse(&format_cell(&set)), Ok(set));
+
+        let list = AttributeValue::L(vec![AttributeValue::Bool(false)]);
+        assert_eq!(ColumnType::Json.parse(&format_cell(&list)), Ok(list));
+    }
+}
@@ -0,0 +1,7 @@
+mod error;
+mod mapping;
+mod reader;
+
+pub use error::CsvError;
+pub use mapping::{ColumnMapping, ColumnType, CsvMapping, DEFAULT_SET_DELIMITER, format_cell};
+pub use reader::{CsvReader, CsvRecord, escape_field};
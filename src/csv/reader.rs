@@ -0,0 +1,172 @@
+use std::io::BufRead;
+
+use super::error::CsvError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvRecord {
+    // 1-based line the record starts on
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+// rfc 4180 style: quoted fields may contain commas, doubled quotes and newlines
+pub struct CsvReader<R> {
+    reader: R,
+    line: usize,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: 0 }
+    }
+
+    pub fn read_record(&mut self) -> Result<Option<CsvRecord>, CsvError> {
+        let mut buf = String::new();
+        // blank lines separate nothing, so they're skipped rather than read as one empty field
+        loop {
+            buf.clear();
+            if self.reader.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            // spreadsheet exports often start with a byte order mark
+            if self.line == 1
+                && let Some(rest) = buf.strip_prefix('\u{FEFF}')
+            {
+                buf = rest.to_string();
+            }
+            if !buf.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+        }
+        let start_line = self.line;
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut quoted = false;
+
+        loop {
+            let mut chars = buf.chars().peekable();
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    match c {
+                        '"' if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        '"' => in_quotes = false,
+                        c => field.push(c),
+                    }
+                    continue;
+                }
+
+                match c {
+                    '"' if field.is_empty() && !quoted => {
+                        in_quotes = true;
+                        quoted = true;
+                    }
+                    '"' => return Err(CsvError::syntax(self.line, "unexpected quote in field")),
+                    ',' => {
+                        fields.push(std::mem::take(&mut field));
+                        quoted = false;
+                    }
+                    '\r' if chars.peek() == Some(&'\n') => {}
+                    '\n' => {}
+                    c if quoted => {
+                        return Err(CsvError::syntax(
+                            self.line,
+                            format!("unexpected '{}' after closing quote", c),
+                        ));
+                    }
+                    c => field.push(c),
+                }
+            }
+
+            if !in_quotes {
+                break;
+            }
+
+            // quoted field spans lines
+            buf.clear();
+            if self.reader.read_line(&mut buf)? == 0 {
+                return Err(CsvError::syntax(start_line, "unterminated quoted field"));
+            }
+            self.line += 1;
+        }
+
+        fields.push(field);
+        Ok(Some(CsvRecord {
+            line: start_line,
+            fields,
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for CsvReader<R> {
+    type Item = Result<CsvRecord, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+pub fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(input: &str) -> Result<Vec<CsvRecord>, CsvError> {
+        CsvReader::new(input.as_bytes()).collect()
+    }
+
+    #[test]
+    fn plain_fields() {
+        let records = read_all("a,b,c\r\n1,,3\n").unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].fields, vec!["a", "b", "c"]);
+        assert_eq!(records[1].fields, vec!["1", "", "3"]);
+        assert_eq!(records[1].line, 2);
+    }
+
+    #[test]
+    fn quoted_fields() {
+        let records = read_all("\"x, y\",\"say \"\"hi\"\"\",\"multi\nline\"\nnext\n").unwrap();
+        assert_eq!(records[0].fields, vec!["x, y", "say \"hi\"", "multi\nline"]);
+        assert_eq!(records[1].line, 3);
+        assert_eq!(records[1].fields, vec!["next"]);
+    }
+
+    #[test]
+    fn blank_lines_and_byte_order_mark() {
+        let records = read_all("\u{FEFF}id,name\r\n\r\n1,a\n\n\n2,b\n\n").unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].fields, vec!["id", "name"]);
+        assert_eq!(records[1].line, 3);
+        assert_eq!(records[2].line, 6);
+        assert_eq!(records[2].fields, vec!["2", "b"]);
+        assert!(read_all("\n\r\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_quotes() {
+        assert!(read_all("ab\"c\n").is_err());
+        assert!(read_all("\"ab\"c\n").is_err());
+        assert!(read_all("\"open\n").is_err());
+    }
+
+    #[test]
+    fn escape_roundtrip() {
+        let fields = ["plain", "a,b", "q\"uote", "new\nline"];
+        let line: Vec<_> = fields.iter().map(|f| escape_field(f)).collect();
+        let records = read_all(&line.join(",")).unwrap();
+        assert_eq!(records[0].fields, fields);
+    }
+}
@@ -0,0 +1,5 @@
+mod repository;
+mod template;
+
+pub use repository::{DEFAULT_TYPE_ATTRIBUTE, Entity, EntityDecode, GsiKeyTemplate, Repository};
+pub use template::KeyTemplate;
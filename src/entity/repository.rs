@@ -0,0 +1,264 @@
+use std::marker::PhantomData;
+
+use super::template::KeyTemplate;
+use crate::error::{TableError, TableResult};
+use crate::query::KeyCondition;
+use crate::table::Table;
+use crate::types::{
+    AttributeValue, ConversionError, FromAttributeValue, Item, PrimaryKey, ToAttributeValue,
+};
+
+pub const DEFAULT_TYPE_ATTRIBUTE: &str = "entity_type";
+
+// extra key attributes written alongside the entity so a GSI can pick it up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GsiKeyTemplate {
+    pub pk_attribute: &'static str,
+    pub pk: &'static str,
+    pub sk_attribute: Option<&'static str>,
+    pub sk: Option<&'static str>,
+}
+
+impl GsiKeyTemplate {
+    pub const fn new(pk_attribute: &'static str, pk: &'static str) -> Self {
+        Self {
+            pk_attribute,
+            pk,
+            sk_attribute: None,
+            sk: None,
+        }
+    }
+
+    pub const fn sort_key(mut self, sk_attribute: &'static str, sk: &'static str) -> Self {
+        self.sk_attribute = Some(sk_attribute);
+        self.sk = Some(sk);
+        self
+    }
+}
+
+pub trait Entity: ToAttributeValue + FromAttributeValue {
+    // discriminator value stored in TYPE_ATTRIBUTE
+    const TYPE: &'static str;
+    const PK: &'static str;
+    const SK: Option<&'static str> = None;
+    const TYPE_ATTRIBUTE: &'static str = DEFAULT_TYPE_ATTRIBUTE;
+    const GSI_KEYS: &'static [GsiKeyTemplate] = &[];
+}
+
+// decodes items of one or more entity types; every Entity gets this, enums get it from entity_enum!
+pub trait EntityDecode: Sized {
+    fn type_attribute() -> &'static str;
+
+    // narrows query_children to a sort key prefix when there is a single entity type
+    fn sort_key_prefix() -> Option<&'static str> {
+        None
+    }
+
+    // None when the item's type isn't one this decoder handles
+    fn decode(entity_type: &str, item: &Item) -> Option<Result<Self, ConversionError>>;
+}
+
+impl<E: Entity> EntityDecode for E {
+    fn type_attribute() -> &'static str {
+        E::TYPE_ATTRIBUTE
+    }
+
+    fn sort_key_prefix() -> Option<&'static str> {
+        E::SK.map(|sk| KeyTemplate::new(sk).prefix())
+    }
+
+    fn decode(entity_type: &str, item: &Item) -> Option<Result<Self, ConversionError>> {
+        if entity_type != E::TYPE {
+            return None;
+        }
+        Some(E::from_attribute_value(&AttributeValue::M(
+            item.clone().into_inner(),
+        )))
+    }
+}
+
+pub struct Repository<'a, E> {
+    table: &'a mut Table,
+    _entity: PhantomData<E>,
+}
+
+impl<'a, E: Entity> Repository<'a, E> {
+    pub fn new(table: &'a mut Table) -> Self {
+        Self {
+            table,
+            _entity: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &Table {
+        self.table
+    }
+
+    pub fn key(&self, params: &[(&str, &str)]) -> TableResult<PrimaryKey> {
+        let pk = KeyTemplate::new(E::PK).render_params(params)?;
+        match E::SK {
+            Some(sk) => Ok(PrimaryKey::composite(
+                pk,
+                KeyTemplate::new(sk).render_params(params)?,
+            )),
+            None => Ok(PrimaryKey::simple(pk)),
+        }
+    }
+
+    pub fn to_item(&self, entity: &E) -> TableResult<Item> {
+        let mut item = Item::from_attribute_value(&entity.to_attribute_value()?)?;
+        let schema = self.table.schema();
+
+        let pk = KeyTemplate::new(E::PK).render_item(&item)?;
+        let sk = E::SK
+            .map(|sk| KeyTemplate::new(sk).render_item(&item))
+            .transpose()?;
+        match (schema.sk_name(), sk) {
+            (Some(sk_name), Some(sk)) => {
+                item.set(sk_name, AttributeValue::S(sk));
+            }
+            (None, None) => {}
+            (Some(sk_name), None) => {
+                return Err(TableError::validation_error(format!(
+                    "entity {} has no sort key template but the table requires {}",
+                    E::TYPE,
+                    sk_name
+                )));
+            }
+            (None, Some(_)) => {
+                return Err(TableError::validation_error(format!(
+                    "entity {} has a sort key template but the table has no sort key",
+                    E::TYPE
+                )));
+            }
+        }
+
+        let mut gsi_attributes = Vec::new();
+        for gsi in E::GSI_KEYS {
+            let mut keys = vec![(gsi.pk_attribute, KeyTemplate::new(gsi.pk))];
+            if let (Some(attribute), Some(template)) = (gsi.sk_attribute, gsi.sk) {
+                keys.push((attribute, KeyTemplate::new(template)));
+            }
+            // entities missing a field of either key simply stay out of that index
+            let mut complete = true;
+            for (_, template) in &keys {
+                complete &= has_fields(template, &item)?;
+            }
+            if !complete {
+                continue;
+            }
+            for (attribute, template) in keys {
+                gsi_attributes.push((attribute, template.render_item(&item)?));
+            }
+        }
+
+        item.set(schema.pk_name(), AttributeValue::S(pk));
+        item.set(E::TYPE_ATTRIBUTE, AttributeValue::S(E::TYPE.to_string()));
+        for (attribute, value) in gsi_attributes {
+            item.set(attribute, AttributeValue::S(value));
+        }
+
+        Ok(item)
+    }
+
+    pub fn put(&mut self, entity: &E) -> TableResult<()> {
+        let item = self.to_item(entity)?;
+        self.table.put_item(item)
+    }
+
+    pub fn get(&self, params: &[(&str, &str)]) -> TableResult<Option<E>> {
+        let key = self.key(params)?;
+        self.table
+            .get_item(&key)?
+            .map(|item| decode_as::<E>(&item))
+            .transpose()
+    }
+
+    pub fn delete(&mut self, params: &[(&str, &str)]) -> TableResult<Option<E>> {
+        let key = self.key(params)?;
+        self.table
+            .delete_item(&key)?
+            .map(|item| decode_as::<E>(&item))
+            .transpose()
+    }
+
+    // everything in this entity's partition that C knows how to decode
+    pub fn query_children<C: EntityDecode>(
+        &mut self,
+        params: &[(&str, &str)],
+    ) -> TableResult<Vec<C>> {
+        let pk = KeyTemplate::new(E::PK).render_params(params)?;
+        let mut condition = KeyCondition::pk(pk);
+        if let Some(prefix) = C::sort_key_prefix().filter(|p| !p.is_empty())
+            && self.table.schema().has_sort_key()
+        {
+            condition = condition.sk_begins_with(prefix);
+        }
+
+        let result = self.table.query(condition)?;
+        let mut children = Vec::new();
+        for item in &result.items {
+            let Some(entity_type) = item.get(C::type_attribute()).and_then(|v| v.as_s()) else {
+                continue;
+            };
+            if let Some(decoded) = C::decode(entity_type, item) {
+                children.push(decoded?);
+            }
+        }
+
+        Ok(children)
+    }
+}
+
+fn has_fields(template: &KeyTemplate, item: &Item) -> TableResult<bool> {
+    Ok(template.fields()?.iter().all(|name| item.contains(name)))
+}
+
+fn decode_as<E: Entity>(item: &Item) -> TableResult<E> {
+    let entity_type = item.get(E::TYPE_ATTRIBUTE).and_then(|v| v.as_s());
+    match entity_type {
+        Some(t) if t == E::TYPE => Ok(E::from_attribute_value(&AttributeValue::M(
+            item.clone().into_inner(),
+        ))?),
+        other => Err(TableError::validation_error(format!(
+            "expected entity type {}, found {}",
+            E::TYPE,
+            other.unwrap_or("none")
+        ))),
+    }
+}
+
+// an enum over several entity types sharing a table:
+//
+//     entity_enum!(UserData { User(User), Order(Order) });
+#[macro_export]
+macro_rules! entity_enum {
+    ($ty:ident { $first:ident($first_entity:ty) $(, $variant:ident($entity:ty))* $(,)? }) => {
+        impl $crate::entity::EntityDecode for $ty {
+            fn type_attribute() -> &'static str {
+                <$first_entity as $crate::entity::Entity>::TYPE_ATTRIBUTE
+            }
+
+            fn decode(
+                entity_type: &str,
+                item: &$crate::types::Item,
+            ) -> ::std::option::Option<
+                ::std::result::Result<Self, $crate::types::ConversionError>,
+            > {
+                if let ::std::option::Option::Some(decoded) =
+                    <$first_entity as $crate::entity::EntityDecode>::decode(entity_type, item)
+                {
+                    return ::std::option::Option::Some(decoded.map($ty::$first));
+                }
+                $(
+                    if let ::std::option::Option::Some(decoded) =
+                        <$entity as $crate::entity::EntityDecode>::decode(entity_type, item)
+                    {
+                        return ::std::option::Option::Some(decoded.map($ty::$variant));
+                    }
+                )*
+                ::std::option::Option::None
+            }
+        }
+    };
+}
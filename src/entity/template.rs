@@ -0,0 +1,171 @@
+use crate::error::{TableError, TableResult};
+use crate::types::{AttributeValue, Item};
+
+// a key pattern like `USER#{id}`; `{name}` is replaced by the attribute's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyTemplate<'a> {
+    raw: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part<'a> {
+    Literal(&'a str),
+    Field(&'a str),
+}
+
+impl<'a> KeyTemplate<'a> {
+    pub fn new(raw: &'a str) -> Self {
+        Self { raw }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    // literal text before the first placeholder, usable with begins_with
+    pub fn prefix(&self) -> &'a str {
+        match self.raw.find('{') {
+            Some(i) => &self.raw[..i],
+            None => self.raw,
+        }
+    }
+
+    pub fn fields(&self) -> TableResult<Vec<&'a str>> {
+        Ok(self
+            .parts()?
+            .into_iter()
+            .filter_map(|part| match part {
+                Part::Field(name) => Some(name),
+                Part::Literal(_) => None,
+            })
+            .collect())
+    }
+
+    pub fn render<F>(&self, mut lookup: F) -> TableResult<String>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut out = String::new();
+        for part in self.parts()? {
+            match part {
+                Part::Literal(s) => out.push_str(s),
+                Part::Field(name) => {
+                    let value = lookup(name).ok_or_else(|| {
+                        TableError::validation_error(format!(
+                            "key template {}: missing value for {}",
+                            self.raw, name
+                        ))
+                    })?;
+                    out.push_str(&value);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn render_item(&self, item: &Item) -> TableResult<String> {
+        let mut invalid = None;
+        let rendered = self.render(|name| match item.get(name)? {
+            AttributeValue::S(s) => Some(s.clone()),
+            AttributeValue::N(n) => Some(n.clone()),
+            other => {
+                invalid = Some((name.to_string(), other.type_name()));
+                None
+            }
+        });
+
+        match invalid {
+            Some((name, type_name)) => Err(TableError::validation_error(format!(
+                "key template {}: {} must be S or N, got {}",
+                self.raw, name, type_name
+            ))),
+            None => rendered,
+        }
+    }
+
+    pub fn render_params(&self, params: &[(&str, &str)]) -> TableResult<String> {
+        self.render(|name| {
+            params
+                .iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        })
+    }
+
+    fn parts(&self) -> TableResult<Vec<Part<'a>>> {
+        let mut parts = Vec::new();
+        let mut rest = self.raw;
+
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                parts.push(Part::Literal(&rest[..open]));
+            }
+            let close = rest[open..].find('}').ok_or_else(|| self.malformed())?;
+            let name = &rest[open + 1..open + close];
+            if name.is_empty() || name.contains('{') {
+                return Err(self.malformed());
+            }
+            parts.push(Part::Field(name));
+            rest = &rest[open + close + 1..];
+        }
+        if rest.contains('}') {
+            return Err(self.malformed());
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest));
+        }
+
+        Ok(parts)
+    }
+
+    fn malformed(&self) -> TableError {
+        TableError::validation_error(format!("malformed key template: {}", self.raw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_from_item() {
+        let item = Item::new().with_s("id", "42").with_n("ts", 1700);
+        let template = KeyTemplate::new("USER#{id}#ORDER#{ts}");
+        assert_eq!(template.render_item(&item).unwrap(), "USER#42#ORDER#1700");
+        assert_eq!(template.prefix(), "USER#");
+        assert_eq!(template.fields().unwrap(), vec!["id", "ts"]);
+    }
+
+    #[test]
+    fn constant_template() {
+        let template = KeyTemplate::new("PROFILE");
+        assert_eq!(template.render_item(&Item::new()).unwrap(), "PROFILE");
+        assert_eq!(template.prefix(), "PROFILE");
+    }
+
+    #[test]
+    fn renders_from_params() {
+        let template = KeyTemplate::new("USER#{id}");
+        assert_eq!(template.render_params(&[("id", "7")]).unwrap(), "USER#7");
+        assert!(
+            template
+                .render_params(&[])
+                .unwrap_err()
+                .to_string()
+                .contains("missing value for id")
+        );
+    }
+
+    #[test]
+    fn rejects_bad_templates_and_values() {
+        assert!(KeyTemplate::new("USER#{id").render_params(&[]).is_err());
+        assert!(KeyTemplate::new("USER#{}").render_params(&[]).is_err());
+        assert!(KeyTemplate::new("USER#id}").render_params(&[]).is_err());
+
+        let item = Item::new().with_bool("id", true);
+        let err = KeyTemplate::new("USER#{id}")
+            .render_item(&item)
+            .unwrap_err();
+        assert!(err.to_string().contains("id must be S or N, got BOOL"));
+    }
+}
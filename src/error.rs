@@ -1,7 +1,10 @@
 use std::error::Error;
 use std::fmt;
 
-use crate::types::{DecodeError, KeyValidationError};
+use crate::expression::ExpressionError;
+use crate::json::JsonError;
+use crate::partiql::PartiqlError;
+use crate::types::{ConversionError, DecodeError, KeyValidationError, PrimaryKey};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum StorageError {
@@ -90,6 +93,7 @@ pub enum TransactionCancelReason {
     ItemNotFound { index: usize },
     DuplicateItem { index: usize },
     ValidationError { index: usize, message: String },
+    UniqueViolation { index: usize, index_name: String },
 }
 
 impl TransactionCancelReason {
@@ -99,6 +103,7 @@ impl TransactionCancelReason {
             Self::ItemNotFound { index } => *index,
             Self::DuplicateItem { index } => *index,
             Self::ValidationError { index, .. } => *index,
+            Self::UniqueViolation { index, .. } => *index,
         }
     }
 }
@@ -118,6 +123,9 @@ impl fmt::Display for TransactionCancelReason {
             Self::ValidationError { index, message } => {
                 write!(f, "validation error at index {}: {}", index, message)
             }
+            Self::UniqueViolation { index, index_name } => {
+                write!(f, "unique index {} violated at index {}", index_name, index)
+            }
         }
     }
 }
@@ -130,14 +138,24 @@ pub enum TableError {
     IndexNotFound {
         name: String,
     },
+    IndexNotActive {
+        name: String,
+    },
     ConditionFailed,
     ConditionError(String),
     UpdateError(String),
+    ValidationError(String),
     TransactionCanceled {
         reasons: Vec<TransactionCancelReason>,
     },
+    // items that share (or would share) a key in a unique index
+    UniqueViolation {
+        index: String,
+        keys: Vec<PrimaryKey>,
+    },
     Storage(String),
     Encoding(String),
+    Io(String),
 }
 
 impl TableError {
@@ -156,15 +174,44 @@ impl TableError {
     pub fn is_index_not_found(&self) -> bool {
         matches!(self, Self::IndexNotFound { .. })
     }
+    pub fn is_index_not_active(&self) -> bool {
+        matches!(self, Self::IndexNotActive { .. })
+    }
     pub fn is_update_error(&self) -> bool {
         matches!(self, Self::UpdateError(_))
     }
     pub fn index_not_found(name: impl Into<String>) -> Self {
         Self::IndexNotFound { name: name.into() }
     }
+    pub fn index_not_active(name: impl Into<String>) -> Self {
+        Self::IndexNotActive { name: name.into() }
+    }
     pub fn update_error(msg: impl Into<String>) -> Self {
         Self::UpdateError(msg.into())
     }
+    pub fn is_validation_error(&self) -> bool {
+        matches!(self, Self::ValidationError(_))
+    }
+    pub fn validation_error(msg: impl Into<String>) -> Self {
+        Self::ValidationError(msg.into())
+    }
+
+    // also true for transactions canceled by a unique index
+    pub fn is_unique_violation(&self) -> bool {
+        match self {
+            Self::UniqueViolation { .. } => true,
+            Self::TransactionCanceled { reasons } => reasons
+                .iter()
+                .any(|r| matches!(r, TransactionCancelReason::UniqueViolation { .. })),
+            _ => false,
+        }
+    }
+    pub fn unique_violation(index: impl Into<String>, keys: Vec<PrimaryKey>) -> Self {
+        Self::UniqueViolation {
+            index: index.into(),
+            keys,
+        }
+    }
 
     pub fn is_transaction_canceled(&self) -> bool {
         matches!(self, Self::TransactionCanceled { .. })
@@ -187,11 +234,26 @@ impl fmt::Display for TableError {
             TableError::ItemNotFound => write!(f, "item not found"),
             TableError::ItemAlreadyExists => write!(f, "item already exists"),
             TableError::IndexNotFound { name } => write!(f, "index not found: {}", name),
+            TableError::IndexNotActive { name } => {
+                write!(f, "index is not active yet: {}", name)
+            }
             TableError::ConditionFailed => write!(f, "condition check failed"),
             TableError::ConditionError(msg) => write!(f, "condition error: {}", msg),
             TableError::UpdateError(msg) => write!(f, "update error: {}", msg),
+            TableError::ValidationError(msg) => write!(f, "validation error: {}", msg),
+            TableError::UniqueViolation { index, keys } => {
+                write!(f, "unique index {} has conflicting items: ", index)?;
+                for (i, key) in keys.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", key.to_storage_key())?;
+                }
+                Ok(())
+            }
             TableError::Storage(msg) => write!(f, "storage error: {}", msg),
             TableError::Encoding(msg) => write!(f, "encoding error: {}", msg),
+            TableError::Io(msg) => write!(f, "io error: {}", msg),
             TableError::TransactionCanceled { reasons } => {
                 write!(f, "transaction canceled: ")?;
                 for (i, reason) in reasons.iter().enumerate() {
@@ -233,6 +295,36 @@ impl From<DecodeError> for TableError {
     }
 }
 
+impl From<JsonError> for TableError {
+    fn from(e: JsonError) -> Self {
+        Self::Encoding(e.to_string())
+    }
+}
+
+impl From<ExpressionError> for TableError {
+    fn from(e: ExpressionError) -> Self {
+        Self::ValidationError(e.to_string())
+    }
+}
+
+impl From<PartiqlError> for TableError {
+    fn from(e: PartiqlError) -> Self {
+        Self::ValidationError(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for TableError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+impl From<ConversionError> for TableError {
+    fn from(e: ConversionError) -> Self {
+        Self::ValidationError(e.to_string())
+    }
+}
+
 impl From<EvalError> for TableError {
     fn from(e: EvalError) -> Self {
         Self::ConditionError(e.to_string())
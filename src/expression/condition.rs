@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+
+use crate::condition::{AttrType, AttributePath, CompareOp, Condition};
+use crate::syntax::Token;
+use crate::types::AttributeValue;
+
+use super::error::ExpressionError;
+use super::parser::Parser;
+
+// nesting limit for parentheses and NOT
+const MAX_DEPTH: usize = 64;
+
+const ATTR_TYPES: [AttrType; 10] = [
+    AttrType::String,
+    AttrType::Number,
+    AttrType::Binary,
+    AttrType::Boolean,
+    AttrType::Null,
+    AttrType::Map,
+    AttrType::List,
+    AttrType::StringSet,
+    AttrType::NumberSet,
+    AttrType::BinarySet,
+];
+
+impl Condition {
+    // parses a DynamoDB condition expression such as
+    // `attribute_exists(#pk) AND #s IN (:a, :b) OR size(tags) > :n`
+    pub fn parse(
+        expression: &str,
+        names: &HashMap<String, String>,
+        values: &HashMap<String, AttributeValue>,
+    ) -> Result<Self, ExpressionError> {
+        let mut parser = Parser::new(expression, names, values)?;
+        let condition = parser.condition(0)?;
+        parser.tokens.expect_end()?;
+        Ok(condition)
+    }
+}
+
+enum Operand {
+    Path(AttributePath),
+    Value(AttributeValue),
+    Size(AttributePath),
+}
+
+impl Parser<'_> {
+    // OR binds loosest, then AND, then NOT
+    pub(crate) fn condition(&mut self, depth: usize) -> Result<Condition, ExpressionError> {
+        let mut condition = self.conjunction(depth)?;
+        while self.tokens.accept_keyword("OR") {
+            condition = condition.or(self.conjunction(depth)?);
+        }
+        Ok(condition)
+    }
+
+    fn conjunction(&mut self, depth: usize) -> Result<Condition, ExpressionError> {
+        let mut condition = self.negation(depth)?;
+        while self.tokens.accept_keyword("AND") {
+            condition = condition.and(self.negation(depth)?);
+        }
+        Ok(condition)
+    }
+
+    fn negation(&mut self, depth: usize) -> Result<Condition, ExpressionError> {
+        if depth > MAX_DEPTH {
+            return Err(ExpressionError::syntax(
+                self.tokens.position(),
+                "expression is nested too deeply",
+            ));
+        }
+        if self.tokens.accept_keyword("NOT") {
+            return Ok(self.negation(depth + 1)?.not());
+        }
+        if self.tokens.accept(&Token::LParen) {
+            let condition = self.condition(depth + 1)?;
+            self.tokens.expect(Token::RParen, "')'")?;
+            return Ok(condition);
+        }
+        if let Token::Ident(name) = self.tokens.peek()
+            && self.tokens.peek_second() == &Token::LParen
+            && !name.eq_ignore_ascii_case("size")
+        {
+            return self.function();
+        }
+        self.predicate()
+    }
+
+    fn function(&mut self) -> Result<Condition, ExpressionError> {
+        let position = self.tokens.position();
+        let Token::Ident(name) = self.tokens.advance() else {
+            unreachable!("only called on an identifier");
+        };
+        self.tokens.expect(Token::LParen, "'('")?;
+        let path = self.path()?;
+        let condition = match name.to_ascii_lowercase().as_str() {
+            "attribute_exists" => Condition::AttributeExists(path),
+            "attribute_not_exists" => Condition::AttributeNotExists(path),
+            "attribute_type" => {
+                self.tokens.expect(Token::Comma, "','")?;
+                let position = self.tokens.position();
+                let attribute_type = match self.value()? {
+                    AttributeValue::S(s) => ATTR_TYPES.into_iter().find(|t| t.as_str() == s),
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    ExpressionError::syntax(
+                        position,
+                        "attribute_type needs one of S, N, B, BOOL, NULL, M, L, SS, NS or BS",
+                    )
+                })?;
+                Condition::AttributeType {
+                    path,
+                    attribute_type,
+                }
+            }
+            "begins_with" => {
+                self.tokens.expect(Token::Comma, "','")?;
+                Condition::BeginsWith {
+                    path,
+                    prefix: self.value()?,
+                }
+            }
+            "contains" => {
+                self.tokens.expect(Token::Comma, "','")?;
+                Condition::Contains {
+                    path,
+                    operand: self.value()?,
+                }
+            }
+            _ => {
+                return Err(ExpressionError::syntax(
+                    position,
+                    format!("unknown function '{}'", name),
+                ));
+            }
+        };
+        self.tokens.expect(Token::RParen, "')'")?;
+        Ok(condition)
+    }
+
+    fn operand(&mut self) -> Result<(Operand, usize), ExpressionError> {
+        let position = self.tokens.position();
+        let operand = match self.tokens.peek() {
+            Token::Value(_) => Operand::Value(self.value()?),
+            Token::Ident(name) if self.tokens.peek_second() == &Token::LParen => {
+                if !name.eq_ignore_ascii_case("size") {
+                    return Err(ExpressionError::syntax(
+                        position,
+                        format!("{}() can't be compared", name),
+                    ));
+                }
+                self.tokens.advance();
+                self.tokens.advance();
+                let path = self.path()?;
+                self.tokens.expect(Token::RParen, "')'")?;
+                Operand::Size(path)
+            }
+            _ => Operand::Path(self.path()?),
+        };
+        Ok((operand, position))
+    }
+
+    fn predicate(&mut self) -> Result<Condition, ExpressionError> {
+        let (left, position) = self.operand()?;
+
+        if let Token::Compare(op) = self.tokens.peek().clone() {
+            self.tokens.advance();
+            let (right, right_position) = self.operand()?;
+            return match (left, right) {
+                (Operand::Path(path), Operand::Value(value)) => {
+                    Ok(Condition::Compare { path, op, value })
+                }
+                (Operand::Value(value), Operand::Path(path)) => Ok(Condition::Compare {
+                    path,
+                    op: flip(op),
+                    value,
+                }),
+                (Operand::Size(path), Operand::Value(value)) => Ok(Condition::Size {
+                    path,
+                    op,
+                    value: size_operand(value, right_position)?,
+                }),
+                (Operand::Value(value), Operand::Size(path)) => Ok(Condition::Size {
+                    path,
+                    op: flip(op),
+                    value: size_operand(value, position)?,
+                }),
+                (Operand::Value(_), Operand::Value(_)) => Err(ExpressionError::syntax(
+                    position,
+                    "a comparison needs an attribute on one side",
+                )),
+                _ => Err(ExpressionError::syntax(
+                    position,
+                    "comparing two attributes is not supported",
+                )),
+            };
+        }
+
+        if self.tokens.accept_keyword("BETWEEN") {
+            let low_position = self.tokens.position();
+            let low = self.value()?;
+            self.tokens.expect_keyword("AND")?;
+            let high_position = self.tokens.position();
+            let high = self.value()?;
+            return match left {
+                Operand::Path(path) => Ok(Condition::Between { path, low, high }),
+                Operand::Size(path) => Ok(Condition::Size {
+                    path: path.clone(),
+                    op: CompareOp::Ge,
+                    value: size_operand(low, low_position)?,
+                }
+                .and(Condition::Size {
+                    path,
+                    op: CompareOp::Le,
+                    value: size_operand(high, high_position)?,
+                })),
+                Operand::Value(_) => Err(ExpressionError::syntax(
+                    position,
+                    "BETWEEN needs an attribute on the left",
+                )),
+            };
+        }
+
+        if self.tokens.accept_keyword("IN") {
+            self.tokens.expect(Token::LParen, "'('")?;
+            let mut values = vec![(self.tokens.position(), self.value()?)];
+            while self.tokens.accept(&Token::Comma) {
+                values.push((self.tokens.position(), self.value()?));
+            }
+            self.tokens.expect(Token::RParen, "')'")?;
+
+            let mut alternatives = Vec::with_capacity(values.len());
+            for (value_position, value) in values {
+                alternatives.push(match &left {
+                    Operand::Path(path) => Condition::eq(path.clone(), value),
+                    Operand::Size(path) => {
+                        Condition::size_eq(path.clone(), size_operand(value, value_position)?)
+                    }
+                    Operand::Value(_) => {
+                        return Err(ExpressionError::syntax(
+                            position,
+                            "IN needs an attribute on the left",
+                        ));
+                    }
+                });
+            }
+            return Ok(alternatives
+                .into_iter()
+                .reduce(Condition::or)
+                .expect("IN has at least one value"));
+        }
+
+        Err(self.tokens.unexpected("a comparison, BETWEEN or IN").into())
+    }
+}
+
+// `:v < a` is `a > :v`
+fn flip(op: CompareOp) -> CompareOp {
+    match op {
+        CompareOp::Lt => CompareOp::Gt,
+        CompareOp::Le => CompareOp::Ge,
+        CompareOp::Gt => CompareOp::Lt,
+        CompareOp::Ge => CompareOp::Le,
+        op => op,
+    }
+}
+
+fn size_operand(value: AttributeValue, position: usize) -> Result<usize, ExpressionError> {
+    match &value {
+        AttributeValue::N(n) => n.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        ExpressionError::syntax(position, "size() can only be compared with a whole number")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::attr;
+
+    fn parse(expression: &str) -> Result<Condition, ExpressionError> {
+        let names = HashMap::from([
+            ("#pk".to_string(), "pk".to_string()),
+            ("#s".to_string(), "status".to_string()),
+        ]);
+        let values = HashMap::from([
+            (":a".to_string(), AttributeValue::S("active".into())),
+            (":b".to_string(), AttributeValue::S("banned".into())),
+            (":n".to_string(), AttributeValue::N("3".into())),
+            (":m".to_string(), AttributeValue::N("10".into())),
+            (":t".to_string(), AttributeValue::S("SS".into())),
+            (":x".to_string(), AttributeValue::N("1.5".into())),
+        ]);
+        Condition::parse(expression, &names, &values)
+    }
+
+    // `Condition` has no `PartialEq`; its debug form is exact enough
+    fn assert_parses(expression: &str, expected: Condition) {
+        assert_eq!(
+            format!("{:?}", parse(expression).unwrap()),
+            format!("{:?}", expected),
+            "{}",
+            expression
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        assert_parses(
+            "attribute_exists(#pk) AND #s IN (:a, :b) OR size(tags) > :n",
+            attr("pk")
+                .exists()
+                .and(attr("status").eq("active").or(attr("status").eq("banned")))
+                .or(attr("tags").size_gt(3)),
+        );
+        assert_parses(
+            "NOT a = :a AND (b = :a OR c = :a)",
+            attr("a")
+                .eq("active")
+                .not()
+                .and(attr("b").eq("active").or(attr("c").eq("active"))),
+        );
+        assert_parses(
+            "not not a = :a or b = :a",
+            attr("a")
+                .eq("active")
+                .not()
+                .not()
+                .or(attr("b").eq("active")),
+        );
+    }
+
+    #[test]
+    fn paths_and_operands() {
+        assert_parses(
+            "#s.history[2].#pk <> :a",
+            attr(
+                AttributePath::new("status")
+                    .key("history")
+                    .index(2)
+                    .key("pk"),
+            )
+            .ne("active"),
+        );
+        assert_parses(":n <= n", attr("n").ge(3));
+        assert_parses(":m > size(#s)", attr("status").size_lt(10));
+        assert_parses("a BETWEEN :n AND :m", attr("a").between(3, 10));
+        assert_parses(
+            "size(a) between :n and :m",
+            Condition::Size {
+                path: "a".into(),
+                op: CompareOp::Ge,
+                value: 3,
+            }
+            .and(Condition::Size {
+                path: "a".into(),
+                op: CompareOp::Le,
+                value: 10,
+            }),
+        );
+    }
+
+    #[test]
+    fn functions() {
+        assert_parses(
+            "attribute_not_exists(a) OR attribute_type(a, :t)",
+            attr("a")
+                .not_exists()
+                .or(attr("a").is_type(AttrType::StringSet)),
+        );
+        assert_parses(
+            "begins_with(a, :a) AND contains(b[0], :b)",
+            attr("a")
+                .begins_with("active")
+                .and(attr(AttributePath::new("b").index(0)).contains("banned")),
+        );
+    }
+
+    #[test]
+    fn errors_carry_positions() {
+        for (expression, position, message) in [
+            (
+                "a = :a AND",
+                10,
+                "expected an attribute name, found the end of the expression",
+            ),
+            (
+                "a = :zz",
+                4,
+                "expression attribute value :zz is not defined",
+            ),
+            (
+                "#nope = :a",
+                0,
+                "expression attribute name #nope is not defined",
+            ),
+            (
+                "a = :a b",
+                7,
+                "expected the end of the expression, found 'b'",
+            ),
+            ("frob(a)", 0, "unknown function 'frob'"),
+            ("a = b", 0, "comparing two attributes is not supported"),
+            (
+                "size(a) > :x",
+                10,
+                "size() can only be compared with a whole number",
+            ),
+            (
+                "attribute_type(a, :a)",
+                18,
+                "attribute_type needs one of S, N, B, BOOL, NULL, M, L, SS, NS or BS",
+            ),
+            (
+                "(a = :a",
+                7,
+                "expected ')', found the end of the expression",
+            ),
+            (
+                "in = :a",
+                0,
+                "'in' is a reserved word; use an expression attribute name",
+            ),
+            ("a[x] = :a", 2, "expected a list index, found 'x'"),
+            (
+                "a :a",
+                2,
+                "expected a comparison, BETWEEN or IN, found ':a'",
+            ),
+        ] {
+            assert_eq!(
+                parse(expression).unwrap_err(),
+                ExpressionError::syntax(position, message),
+                "{}",
+                expression
+            );
+        }
+        let deep = format!("{}a = :a{}", "(".repeat(100), ")".repeat(100));
+        assert!(parse(&deep).is_err());
+    }
+}
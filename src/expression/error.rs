@@ -0,0 +1,54 @@
+use std::fmt;
+
+use crate::syntax::SyntaxError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+    // byte offset into the expression
+    Syntax { position: usize, message: String },
+    // well-formed expression that breaks a rule about the expression as a whole
+    Invalid(String),
+}
+
+impl ExpressionError {
+    pub fn syntax(position: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            position,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::Invalid(message.into())
+    }
+
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            Self::Syntax { position, .. } => Some(*position),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<SyntaxError> for ExpressionError {
+    fn from(error: SyntaxError) -> Self {
+        Self::syntax(error.position, error.message)
+    }
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { position, message } => {
+                write!(
+                    f,
+                    "invalid expression at position {}: {}",
+                    position, message
+                )
+            }
+            Self::Invalid(message) => write!(f, "invalid expression: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
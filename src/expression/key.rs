@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+
+use crate::condition::CompareOp;
+use crate::query::{KeyCondition, SortKeyOp};
+use crate::syntax::Token;
+use crate::types::{AttributeValue, KeySchema, KeyType, KeyValue};
+
+use super::error::ExpressionError;
+use super::parser::Parser;
+
+// a value with where it appeared
+type Placed = (AttributeValue, usize);
+
+enum Test {
+    Compare(CompareOp, Placed),
+    Between(Placed, Placed),
+    BeginsWith(Placed),
+}
+
+struct Term {
+    name: String,
+    position: usize,
+    test: Test,
+}
+
+impl KeyCondition {
+    // parses a DynamoDB key condition expression such as
+    // `#pk = :p AND begins_with(#sk, :prefix)` against the key attributes in `schema`; every
+    // placeholder handed in must appear in it
+    pub fn parse(
+        expression: &str,
+        names: &HashMap<String, String>,
+        values: &HashMap<String, AttributeValue>,
+        schema: &KeySchema,
+    ) -> Result<Self, ExpressionError> {
+        let mut parser = Parser::new(expression, names, values)?;
+        let mut terms = vec![parser.key_term()?];
+        if parser.tokens.accept_keyword("AND") {
+            terms.push(parser.key_term()?);
+        }
+        parser.tokens.expect_end()?;
+        parser.expect_all_used()?;
+
+        let mut partition = None;
+        let mut sort = None;
+        for term in terms {
+            if term.name == schema.pk_name() {
+                if partition.is_some() {
+                    return Err(ExpressionError::syntax(
+                        term.position,
+                        format!("partition key {} appears twice", term.name),
+                    ));
+                }
+                let Test::Compare(CompareOp::Eq, value) = term.test else {
+                    return Err(ExpressionError::syntax(
+                        term.position,
+                        format!("partition key {} can only be compared with =", term.name),
+                    ));
+                };
+                partition = Some(key_value(value, &term.name, schema.partition_key.key_type)?);
+            } else if let Some(sk) = &schema.sort_key
+                && term.name == sk.name
+            {
+                if sort.is_some() {
+                    return Err(ExpressionError::syntax(
+                        term.position,
+                        format!("sort key {} appears twice", term.name),
+                    ));
+                }
+                sort = Some(sort_key_op(term, sk.key_type)?);
+            } else {
+                let expected = match schema.sk_name() {
+                    Some(sk) => format!("{} or {}", schema.pk_name(), sk),
+                    None => schema.pk_name().to_string(),
+                };
+                return Err(ExpressionError::syntax(
+                    term.position,
+                    format!(
+                        "{} is not a key attribute; expected {}",
+                        term.name, expected
+                    ),
+                ));
+            }
+        }
+
+        let partition = partition.ok_or_else(|| {
+            ExpressionError::invalid(format!(
+                "key condition must compare partition key {} with =",
+                schema.pk_name()
+            ))
+        })?;
+        let condition = KeyCondition::pk(partition);
+        Ok(match sort {
+            Some(op) => condition.sk(op),
+            None => condition,
+        })
+    }
+}
+
+impl Parser<'_> {
+    fn key_term(&mut self) -> Result<Term, ExpressionError> {
+        let position = self.tokens.position();
+        if let Token::Ident(name) = self.tokens.peek().clone()
+            && self.tokens.peek_second() == &Token::LParen
+        {
+            if !name.eq_ignore_ascii_case("begins_with") {
+                return Err(ExpressionError::syntax(
+                    position,
+                    format!("{}() can't be used in a key condition", name),
+                ));
+            }
+            self.tokens.advance();
+            self.tokens.advance();
+            let name = self.key_name()?;
+            self.tokens.expect(Token::Comma, "','")?;
+            let prefix = self.placed_value()?;
+            self.tokens.expect(Token::RParen, "')'")?;
+            return Ok(Term {
+                name,
+                position,
+                test: Test::BeginsWith(prefix),
+            });
+        }
+
+        let name = self.key_name()?;
+        let test = if let Token::Compare(op) = self.tokens.peek().clone() {
+            if op == CompareOp::Ne {
+                return Err(ExpressionError::syntax(
+                    self.tokens.position(),
+                    "<> can't be used in a key condition",
+                ));
+            }
+            self.tokens.advance();
+            Test::Compare(op, self.placed_value()?)
+        } else if self.tokens.accept_keyword("BETWEEN") {
+            let low = self.placed_value()?;
+            self.tokens.expect_keyword("AND")?;
+            Test::Between(low, self.placed_value()?)
+        } else {
+            return Err(self.tokens.unexpected("a comparison or BETWEEN").into());
+        };
+        Ok(Term {
+            name,
+            position,
+            test,
+        })
+    }
+
+    fn key_name(&mut self) -> Result<String, ExpressionError> {
+        let position = self.tokens.position();
+        let path = self.path()?;
+        match path.root() {
+            Some(name) if path.is_simple() => Ok(name.to_string()),
+            _ => Err(ExpressionError::syntax(
+                position,
+                format!("key attribute {} must be a top-level attribute", path),
+            )),
+        }
+    }
+
+    fn placed_value(&mut self) -> Result<Placed, ExpressionError> {
+        let position = self.tokens.position();
+        Ok((self.value()?, position))
+    }
+}
+
+fn key_value(
+    (value, position): Placed,
+    name: &str,
+    key_type: KeyType,
+) -> Result<KeyValue, ExpressionError> {
+    KeyValue::from_attribute_with_type(&value, key_type).ok_or_else(|| {
+        ExpressionError::syntax(
+            position,
+            format!(
+                "key attribute {} is {:?}, got {}",
+                name,
+                key_type,
+                value.type_name()
+            ),
+        )
+    })
+}
+
+fn sort_key_op(term: Term, key_type: KeyType) -> Result<SortKeyOp, ExpressionError> {
+    let name = &term.name;
+    Ok(match term.test {
+        Test::Compare(op, value) => {
+            let value = key_value(value, name, key_type)?;
+            match op {
+                CompareOp::Eq => SortKeyOp::Eq(value),
+                CompareOp::Lt => SortKeyOp::Lt(value),
+                CompareOp::Le => SortKeyOp::Le(value),
+                CompareOp::Gt => SortKeyOp::Gt(value),
+                CompareOp::Ge => SortKeyOp::Ge(value),
+                CompareOp::Ne => unreachable!("rejected while parsing"),
+            }
+        }
+        Test::Between(low, high) => SortKeyOp::Between {
+            low: key_value(low, name, key_type)?,
+            high: key_value(high, name, key_type)?,
+        },
+        Test::BeginsWith(prefix) => {
+            if matches!(key_type, KeyType::N) {
+                return Err(ExpressionError::syntax(
+                    term.position,
+                    format!("begins_with needs a string or binary key, {} is N", name),
+                ));
+            }
+            SortKeyOp::BeginsWith(key_value(prefix, name, key_type)?)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> KeySchema {
+        KeySchema::composite("pk", KeyType::S, "sk", KeyType::S)
+    }
+
+    // the placeholders a test's expression uses, picked by name
+    fn names(used: &[&str]) -> HashMap<String, String> {
+        let mut names = HashMap::from([
+            ("#pk".to_string(), "pk".to_string()),
+            ("#sk".to_string(), "sk".to_string()),
+        ]);
+        names.retain(|name, _| used.contains(&name.as_str()));
+        names
+    }
+
+    fn values(used: &[&str]) -> HashMap<String, AttributeValue> {
+        let mut values = HashMap::from([
+            (":p".to_string(), AttributeValue::S("user#1".into())),
+            (":prefix".to_string(), AttributeValue::S("order#".into())),
+            (":a".to_string(), AttributeValue::S("a".into())),
+            (":n".to_string(), AttributeValue::N("5".into())),
+        ]);
+        values.retain(|value, _| used.contains(&value.as_str()));
+        values
+    }
+
+    #[test]
+    fn partition_and_sort_conditions() {
+        let condition = KeyCondition::parse(
+            "#pk = :p AND begins_with(#sk, :prefix)",
+            &names(&["#pk", "#sk"]),
+            &values(&[":p", ":prefix"]),
+            &schema(),
+        )
+        .unwrap();
+        assert_eq!(condition.partition_key, KeyValue::S("user#1".into()));
+        assert_eq!(
+            condition.sort_key,
+            Some(SortKeyOp::begins_with(KeyValue::S("order#".into())))
+        );
+
+        // either order, any case
+        let condition = KeyCondition::parse(
+            "sk between :a and :prefix and pk = :p",
+            &HashMap::new(),
+            &values(&[":a", ":prefix", ":p"]),
+            &schema(),
+        )
+        .unwrap();
+        assert_eq!(
+            condition.sort_key,
+            Some(SortKeyOp::between(
+                KeyValue::S("a".into()),
+                KeyValue::S("order#".into())
+            ))
+        );
+        assert!(
+            KeyCondition::parse(":p = :p", &HashMap::new(), &values(&[":p"]), &schema()).is_err()
+        );
+        let condition =
+            KeyCondition::parse("pk = :p", &HashMap::new(), &values(&[":p"]), &schema()).unwrap();
+        assert!(condition.sort_key.is_none());
+    }
+
+    #[test]
+    fn schema_and_operator_rules() {
+        let numeric = KeySchema::composite("pk", KeyType::S, "sk", KeyType::N);
+        for (expression, used, schema, position, message) in [
+            (
+                "pk < :p",
+                &[":p"][..],
+                schema(),
+                0,
+                "partition key pk can only be compared with =",
+            ),
+            (
+                "pk = :p AND pk = :a",
+                &[":p", ":a"],
+                schema(),
+                12,
+                "partition key pk appears twice",
+            ),
+            (
+                "pk = :p AND sk <> :a",
+                &[":p", ":a"],
+                schema(),
+                15,
+                "<> can't be used in a key condition",
+            ),
+            (
+                "pk = :p AND other = :a",
+                &[":p", ":a"],
+                schema(),
+                12,
+                "other is not a key attribute; expected pk or sk",
+            ),
+            (
+                "pk = :p AND sk = :a",
+                &[":p", ":a"],
+                KeySchema::simple("pk", KeyType::S),
+                12,
+                "sk is not a key attribute; expected pk",
+            ),
+            (
+                "pk = :n",
+                &[":n"],
+                schema(),
+                5,
+                "key attribute pk is S, got N",
+            ),
+            (
+                "pk = :p AND begins_with(sk, :n)",
+                &[":p", ":n"],
+                numeric.clone(),
+                12,
+                "begins_with needs a string or binary key, sk is N",
+            ),
+            (
+                "pk = :p AND contains(sk, :a)",
+                &[":p", ":a"],
+                schema(),
+                12,
+                "contains() can't be used in a key condition",
+            ),
+            (
+                "pk.x = :p",
+                &[":p"],
+                schema(),
+                0,
+                "key attribute pk.x must be a top-level attribute",
+            ),
+            (
+                "pk = :p OR sk = :a",
+                &[":p", ":a"],
+                schema(),
+                8,
+                "expected the end of the expression, found 'OR'",
+            ),
+            (
+                "pk = :p AND sk = :a AND sk = :a",
+                &[":p", ":a"],
+                schema(),
+                20,
+                "expected the end of the expression, found 'AND'",
+            ),
+        ] {
+            assert_eq!(
+                KeyCondition::parse(expression, &HashMap::new(), &values(used), &schema)
+                    .unwrap_err(),
+                ExpressionError::syntax(position, message),
+                "{}",
+                expression
+            );
+        }
+        assert_eq!(
+            KeyCondition::parse("sk = :a", &HashMap::new(), &values(&[":a"]), &schema())
+                .unwrap_err(),
+            ExpressionError::invalid("key condition must compare partition key pk with =")
+        );
+        assert!(
+            KeyCondition::parse(
+                "pk = :p AND sk > :n",
+                &HashMap::new(),
+                &values(&[":p", ":n"]),
+                &numeric
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn placeholders_must_be_used() {
+        assert_eq!(
+            KeyCondition::parse(
+                "#pk = :p",
+                &names(&["#pk", "#sk"]),
+                &values(&[":p", ":prefix", ":a", ":n"]),
+                &schema()
+            )
+            .unwrap_err(),
+            ExpressionError::invalid("unused placeholders: #sk, :a, :n, :prefix")
+        );
+    }
+}
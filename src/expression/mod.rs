@@ -0,0 +1,8 @@
+mod condition;
+mod error;
+mod key;
+mod parser;
+mod path;
+mod update;
+
+pub use error::ExpressionError;
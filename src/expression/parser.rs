@@ -0,0 +1,122 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::condition::AttributePath;
+use crate::syntax::{Dialect, Token, Tokens};
+use crate::types::AttributeValue;
+
+use super::error::ExpressionError;
+
+// words that can't name an attribute unless written through a `#name`
+const RESERVED: [&str; 5] = ["AND", "BETWEEN", "IN", "NOT", "OR"];
+
+// recursive descent over one expression, with its placeholders at hand
+pub(crate) struct Parser<'a> {
+    pub(crate) tokens: Tokens,
+    names: &'a HashMap<String, String>,
+    values: &'a HashMap<String, AttributeValue>,
+    used_names: BTreeSet<String>,
+    used_values: BTreeSet<String>,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(
+        input: &str,
+        names: &'a HashMap<String, String>,
+        values: &'a HashMap<String, AttributeValue>,
+    ) -> Result<Self, ExpressionError> {
+        Ok(Self {
+            tokens: Tokens::new(input, Dialect::Expression)?,
+            names,
+            values,
+            used_names: BTreeSet::new(),
+            used_values: BTreeSet::new(),
+        })
+    }
+
+    // `name`, `#name`, then any run of `.name`, `.#name` and `[n]`
+    pub(crate) fn path(&mut self) -> Result<AttributePath, ExpressionError> {
+        let (names, used_names) = (self.names, &mut self.used_names);
+        self.tokens
+            .path(|tokens| path_name(tokens, names, used_names))
+    }
+
+    // a `:value` placeholder, resolved
+    pub(crate) fn value(&mut self) -> Result<AttributeValue, ExpressionError> {
+        let position = self.tokens.position();
+        let Token::Value(placeholder) = self.tokens.peek().clone() else {
+            return Err(self
+                .tokens
+                .unexpected("an expression attribute value")
+                .into());
+        };
+        let value = self.values.get(&placeholder).ok_or_else(|| {
+            ExpressionError::syntax(
+                position,
+                format!("expression attribute value {} is not defined", placeholder),
+            )
+        })?;
+        self.tokens.advance();
+        self.used_values.insert(placeholder);
+        Ok(value.clone())
+    }
+
+    // every placeholder handed in must appear in the expression
+    pub(crate) fn expect_all_used(&self) -> Result<(), ExpressionError> {
+        let unused: Vec<&str> = self
+            .names
+            .keys()
+            .filter(|name| !self.used_names.contains(*name))
+            .chain(
+                self.values
+                    .keys()
+                    .filter(|value| !self.used_values.contains(*value)),
+            )
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if unused.is_empty() {
+            Ok(())
+        } else {
+            Err(ExpressionError::invalid(format!(
+                "unused placeholders: {}",
+                unused.join(", ")
+            )))
+        }
+    }
+}
+
+fn path_name(
+    tokens: &mut Tokens,
+    names: &HashMap<String, String>,
+    used_names: &mut BTreeSet<String>,
+) -> Result<String, ExpressionError> {
+    let position = tokens.position();
+    match tokens.peek().clone() {
+        Token::Ident(word) => {
+            if RESERVED.iter().any(|r| word.eq_ignore_ascii_case(r)) {
+                return Err(ExpressionError::syntax(
+                    position,
+                    format!(
+                        "'{}' is a reserved word; use an expression attribute name",
+                        word
+                    ),
+                ));
+            }
+            tokens.advance();
+            Ok(word)
+        }
+        Token::Name(placeholder) => {
+            let name = names.get(&placeholder).ok_or_else(|| {
+                ExpressionError::syntax(
+                    position,
+                    format!("expression attribute name {} is not defined", placeholder),
+                )
+            })?;
+            tokens.advance();
+            used_names.insert(placeholder);
+            Ok(name.clone())
+        }
+        _ => Err(tokens.unexpected("an attribute name").into()),
+    }
+}
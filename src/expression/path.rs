@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use crate::condition::AttributePath;
+use crate::syntax::Token;
+
+use super::error::ExpressionError;
+use super::parser::Parser;
+
+impl AttributePath {
+    // parses a document path such as `a.b[2].c`
+    pub fn parse(path: &str) -> Result<Self, ExpressionError> {
+        let (names, values) = (HashMap::new(), HashMap::new());
+        let mut parser = Parser::new(path, &names, &values)?;
+        let path = parser.path()?;
+        parser.tokens.expect_end()?;
+        Ok(path)
+    }
+
+    // parses a DynamoDB projection expression such as `#n, address.city, tags[0]`
+    pub fn parse_projection(
+        expression: &str,
+        names: &HashMap<String, String>,
+    ) -> Result<Vec<Self>, ExpressionError> {
+        let values = HashMap::new();
+        let mut parser = Parser::new(expression, names, &values)?;
+        let mut paths: Vec<Self> = Vec::new();
+        loop {
+            let position = parser.tokens.position();
+            let path = parser.path()?;
+            if let Some(other) = paths.iter().find(|other| other.overlaps(&path)) {
+                return Err(ExpressionError::syntax(
+                    position,
+                    format!("path {} overlaps path {}", path, other),
+                ));
+            }
+            paths.push(path);
+            if !parser.tokens.accept(&Token::Comma) {
+                break;
+            }
+        }
+        parser.tokens.expect_end()?;
+        parser.expect_all_used()?;
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            AttributePath::parse("a.b[2].c").unwrap(),
+            AttributePath::new("a").key("b").index(2).key("c")
+        );
+        assert_eq!(
+            AttributePath::parse(" matrix[0][1] ").unwrap(),
+            AttributePath::new("matrix").index(0).index(1)
+        );
+        for (path, position) in [("a.", 2), ("a..b", 2), ("[0]", 0), ("a[", 2), ("a b", 2)] {
+            assert_eq!(
+                AttributePath::parse(path).unwrap_err().position(),
+                Some(position),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn from_str_splits_on_dots_and_indexes() {
+        assert_eq!(
+            AttributePath::from("address.city"),
+            AttributePath::new("address").key("city")
+        );
+        assert_eq!(
+            AttributePath::from("user-id"),
+            AttributePath::new("user-id")
+        );
+        assert_eq!(AttributePath::from("in"), AttributePath::new("in"));
+        assert_eq!(AttributePath::from("a.b"), AttributePath::new("a").key("b"));
+        // one malformed step doesn't keep the rest of the path from splitting
+        assert_eq!(
+            AttributePath::from("a.b-c"),
+            AttributePath::new("a").key("b-c")
+        );
+        assert_eq!(
+            AttributePath::from("a[x].b[2][3]"),
+            AttributePath::new("a[x]").key("b").index(2).index(3)
+        );
+        assert_eq!(
+            AttributePath::from(String::from("tags[1]")),
+            AttributePath::new("tags").index(1)
+        );
+    }
+
+    #[test]
+    fn projections() {
+        let names = HashMap::from([("#n".to_string(), "name".to_string())]);
+        assert_eq!(
+            AttributePath::parse_projection("#n, address.city, tags[0]", &names).unwrap(),
+            [
+                AttributePath::new("name"),
+                AttributePath::new("address").key("city"),
+                AttributePath::new("tags").index(0),
+            ]
+        );
+        assert_eq!(
+            AttributePath::parse_projection("#n, a, a.b", &names).unwrap_err(),
+            ExpressionError::syntax(7, "path a.b overlaps path a")
+        );
+        assert_eq!(
+            AttributePath::parse_projection("a", &names).unwrap_err(),
+            ExpressionError::invalid("unused placeholders: #n")
+        );
+        assert!(AttributePath::parse_projection("", &HashMap::new()).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+
+use crate::condition::{AttributePath, CompareOp};
+use crate::syntax::Token;
+use crate::types::AttributeValue;
+use crate::update::{UpdateAction, UpdateExpression};
+
+use super::error::ExpressionError;
+use super::parser::Parser;
+
+const CLAUSES: [&str; 4] = ["SET", "REMOVE", "ADD", "DELETE"];
+
+impl UpdateExpression {
+    // parses a DynamoDB update expression such as
+    // `SET a = :v, b.c[1] = if_not_exists(b.c[1], :d) REMOVE x ADD n :inc DELETE s :rm`;
+    // SET only writes values, so `SET a = a + :v` (use ADD), `SET a = list_append(a, :v)` and
+    // `SET a = b` are rejected
+    pub fn parse(
+        expression: &str,
+        names: &HashMap<String, String>,
+        values: &HashMap<String, AttributeValue>,
+    ) -> Result<Self, ExpressionError> {
+        let mut parser = Parser::new(expression, names, values)?;
+        let mut seen: Vec<&str> = Vec::new();
+        // every path written so far
+        let mut targets: Vec<AttributePath> = Vec::new();
+        let mut update = UpdateExpression::new();
+
+        loop {
+            let position = parser.tokens.position();
+            let Some(clause) = CLAUSES
+                .into_iter()
+                .find(|clause| parser.tokens.peek().is_keyword(clause))
+            else {
+                if seen.is_empty() {
+                    return Err(parser
+                        .tokens
+                        .unexpected("SET, REMOVE, ADD or DELETE")
+                        .into());
+                }
+                parser.tokens.expect_end()?;
+                break;
+            };
+            if seen.contains(&clause) {
+                return Err(ExpressionError::syntax(
+                    position,
+                    format!("{} appears more than once", clause),
+                ));
+            }
+            seen.push(clause);
+            parser.tokens.advance();
+
+            loop {
+                let position = parser.tokens.position();
+                let path = parser.path()?;
+                if let Some(other) = targets.iter().find(|other| other.overlaps(&path)) {
+                    return Err(ExpressionError::syntax(
+                        position,
+                        format!("path {} overlaps path {}", path, other),
+                    ));
+                }
+                targets.push(path.clone());
+
+                let action = match clause {
+                    "SET" => {
+                        parser.tokens.expect(Token::Compare(CompareOp::Eq), "'='")?;
+                        parser.set_value(path)?
+                    }
+                    "REMOVE" => UpdateAction::Remove { path },
+                    "ADD" => {
+                        let position = parser.tokens.position();
+                        let value = parser.value()?;
+                        if !matches!(
+                            value,
+                            AttributeValue::N(_)
+                                | AttributeValue::Ss(_)
+                                | AttributeValue::Ns(_)
+                                | AttributeValue::Bs(_)
+                        ) {
+                            return Err(ExpressionError::syntax(
+                                position,
+                                format!("ADD needs a number or a set, got {}", value.type_name()),
+                            ));
+                        }
+                        UpdateAction::Add { path, value }
+                    }
+                    _ => {
+                        let position = parser.tokens.position();
+                        let value = parser.value()?;
+                        if !matches!(
+                            value,
+                            AttributeValue::Ss(_) | AttributeValue::Ns(_) | AttributeValue::Bs(_)
+                        ) {
+                            return Err(ExpressionError::syntax(
+                                position,
+                                format!("DELETE needs a set, got {}", value.type_name()),
+                            ));
+                        }
+                        UpdateAction::Delete { path, value }
+                    }
+                };
+                update = update.with_action(action);
+
+                if !parser.tokens.accept(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+
+        parser.expect_all_used()?;
+        Ok(update)
+    }
+}
+
+impl Parser<'_> {
+    // the right-hand side of `path = ...`; only values and if_not_exists on the same path
+    // have an `UpdateAction` to map to
+    fn set_value(&mut self, path: AttributePath) -> Result<UpdateAction, ExpressionError> {
+        let position = self.tokens.position();
+        let action = match self.tokens.peek().clone() {
+            Token::Value(_) => UpdateAction::Set {
+                path,
+                value: self.value()?,
+            },
+            Token::Ident(name) if self.tokens.peek_second() == &Token::LParen => {
+                if !name.eq_ignore_ascii_case("if_not_exists") {
+                    let message = if name.eq_ignore_ascii_case("list_append") {
+                        "list_append() is not supported".to_string()
+                    } else {
+                        format!("unknown function '{}'", name)
+                    };
+                    return Err(ExpressionError::syntax(position, message));
+                }
+                self.tokens.advance();
+                self.tokens.advance();
+                let checked_position = self.tokens.position();
+                let checked = self.path()?;
+                if checked != path {
+                    return Err(ExpressionError::syntax(
+                        checked_position,
+                        format!("if_not_exists must check the path it sets, {}", path),
+                    ));
+                }
+                self.tokens.expect(Token::Comma, "','")?;
+                let value = self.value()?;
+                self.tokens.expect(Token::RParen, "')'")?;
+                UpdateAction::SetIfNotExists { path, value }
+            }
+            Token::Ident(_) | Token::Name(_) => {
+                return Err(ExpressionError::syntax(
+                    position,
+                    "SET can't copy from another attribute",
+                ));
+            }
+            _ => {
+                return Err(self
+                    .tokens
+                    .unexpected("a value or if_not_exists(...)")
+                    .into());
+            }
+        };
+        if matches!(self.tokens.peek(), Token::Plus | Token::Minus) {
+            return Err(ExpressionError::syntax(
+                self.tokens.position(),
+                "arithmetic in SET is not supported; use ADD",
+            ));
+        }
+        Ok(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn names() -> HashMap<String, String> {
+        HashMap::from([("#n".to_string(), "name".to_string())])
+    }
+
+    fn values() -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            (":v".to_string(), AttributeValue::S("v".into())),
+            (":d".to_string(), AttributeValue::N("0".into())),
+            (":inc".to_string(), AttributeValue::N("1".into())),
+            (
+                ":rm".to_string(),
+                AttributeValue::Ss(BTreeSet::from(["x".to_string()])),
+            ),
+        ])
+    }
+
+    fn parse(expression: &str) -> Result<UpdateExpression, ExpressionError> {
+        UpdateExpression::parse(expression, &names(), &values())
+    }
+
+    #[test]
+    fn every_clause() {
+        let update = parse(
+            "SET #n = :v, b.c[1] = if_not_exists(b.c[1], :d) REMOVE x ADD n :inc DELETE s :rm",
+        )
+        .unwrap();
+        let expected = UpdateExpression::new()
+            .set("name", AttributeValue::S("v".into()))
+            .set_if_not_exists(
+                AttributePath::new("b").key("c").index(1),
+                AttributeValue::N("0".into()),
+            )
+            .remove("x")
+            .add("n", AttributeValue::N("1".into()))
+            .delete("s", AttributeValue::Ss(BTreeSet::from(["x".to_string()])));
+        assert_eq!(format!("{:?}", update), format!("{:?}", expected));
+
+        // clauses come in any order and keywords in any case
+        let update = UpdateExpression::parse(
+            "remove a, b[0] set c = :v",
+            &HashMap::new(),
+            &HashMap::from([(":v".to_string(), AttributeValue::Null)]),
+        )
+        .unwrap();
+        assert_eq!(update.len(), 3);
+    }
+
+    #[test]
+    fn dynamodb_rules() {
+        let all = "REMOVE #n ADD n :inc SET a = :v, b = if_not_exists(b, :d) DELETE s :rm";
+        assert!(parse(all).is_ok());
+        for (expression, error) in [
+            (
+                "SET a = :v, b = :d SET c = :inc REMOVE #n DELETE s :rm",
+                ExpressionError::syntax(19, "SET appears more than once"),
+            ),
+            (
+                "SET a.b = :v, a = :d REMOVE #n ADD n :inc DELETE s :rm",
+                ExpressionError::syntax(14, "path a overlaps path a.b"),
+            ),
+            (
+                "SET a[1] = :v REMOVE a[1] ADD #n :inc DELETE s :rm, n :d",
+                ExpressionError::syntax(21, "path a[1] overlaps path a[1]"),
+            ),
+            (
+                "SET a = :v REMOVE #n ADD n :inc DELETE s :rm",
+                ExpressionError::invalid("unused placeholders: :d"),
+            ),
+            (
+                "SET a = :v, b = :d, c = :inc, d = :rm",
+                ExpressionError::invalid("unused placeholders: #n"),
+            ),
+        ] {
+            assert_eq!(parse(expression).unwrap_err(), error, "{}", expression);
+        }
+        // a[0] and a[1] are different elements
+        assert!(parse("SET a[0] = :v, a[1] = :d, #n = :inc, s = :rm").is_ok());
+    }
+
+    #[test]
+    fn descriptive_errors() {
+        for (expression, position, message) in [
+            (
+                "",
+                0,
+                "expected SET, REMOVE, ADD or DELETE, found the end of the expression",
+            ),
+            ("SET a :v", 6, "expected '=', found ':v'"),
+            ("SET a = b", 8, "SET can't copy from another attribute"),
+            ("SET a = a + :v", 8, "SET can't copy from another attribute"),
+            (
+                "SET a = :d + :inc",
+                11,
+                "arithmetic in SET is not supported; use ADD",
+            ),
+            (
+                "SET a = list_append(a, :v)",
+                8,
+                "list_append() is not supported",
+            ),
+            (
+                "SET a = if_not_exists(b, :v)",
+                22,
+                "if_not_exists must check the path it sets, a",
+            ),
+            ("ADD a :v", 6, "ADD needs a number or a set, got S"),
+            ("DELETE a :inc", 9, "DELETE needs a set, got N"),
+            (
+                "REMOVE a,",
+                9,
+                "expected an attribute name, found the end of the expression",
+            ),
+            (
+                "REMOVE a WHERE",
+                9,
+                "expected the end of the expression, found 'WHERE'",
+            ),
+        ] {
+            assert_eq!(
+                parse(expression).unwrap_err(),
+                ExpressionError::syntax(position, message),
+                "{}",
+                expression
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeSet;
+
+use super::point::{BoundingBox, GeoPoint};
+
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+pub const MAX_PRECISION: usize = 12;
+
+// queries cover their area with at most this many cells, coarsening until they fit
+const MAX_COVERING_CELLS: usize = 32;
+
+// bits alternate longitude first, so each character halves both ranges in turn
+pub fn encode(point: GeoPoint, precision: usize) -> String {
+    let mut lat = (-90.0, 90.0);
+    let mut lng = (-180.0, 180.0);
+    let mut hash = String::with_capacity(precision);
+    let mut even = true;
+
+    for _ in 0..precision {
+        let mut index = 0;
+        for _ in 0..5 {
+            let (range, value) = if even {
+                (&mut lng, point.lng)
+            } else {
+                (&mut lat, point.lat)
+            };
+            let mid = (range.0 + range.1) / 2.0;
+            index <<= 1;
+            if value >= mid {
+                index |= 1;
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            even = !even;
+        }
+        hash.push(BASE32[index] as char);
+    }
+    hash
+}
+
+// (width in degrees of longitude, height in degrees of latitude) of a cell
+pub fn cell_size(precision: usize) -> (f64, f64) {
+    let bits = 5 * precision as i32;
+    let lng_bits = (bits + 1) / 2;
+    let lat_bits = bits / 2;
+    (360.0 / 2f64.powi(lng_bits), 180.0 / 2f64.powi(lat_bits))
+}
+
+// cells that together contain the whole box, as coarse as needed to stay under the cap
+pub fn covering_cells(area: &BoundingBox, max_precision: usize) -> BTreeSet<String> {
+    let spans = area.longitude_spans();
+    let height = area.north_east.lat - area.south_west.lat;
+
+    let mut precision = max_precision.max(1);
+    while precision > 1 {
+        let (w, h) = cell_size(precision);
+        let rows = (height / h).ceil() as usize + 1;
+        let columns: usize = spans
+            .iter()
+            .map(|(west, east)| ((east - west) / w).ceil() as usize + 1)
+            .sum();
+        if rows * columns <= MAX_COVERING_CELLS {
+            break;
+        }
+        precision -= 1;
+    }
+
+    let (w, h) = cell_size(precision);
+    let mut cells = BTreeSet::new();
+    for (west, east) in spans {
+        for lat in steps(area.south_west.lat, area.north_east.lat, h) {
+            for lng in steps(west, east, w) {
+                cells.insert(encode(GeoPoint::new(lat, lng), precision));
+            }
+        }
+    }
+    cells
+}
+
+// from `start` to `end` in `step`s, always including `end` so the last cell is touched
+fn steps(start: f64, end: f64, step: f64) -> impl Iterator<Item = f64> {
+    let count = ((end - start) / step).floor().max(0.0) as usize;
+    (0..=count)
+        .map(move |i| start + i as f64 * step)
+        .chain(std::iter::once(end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_known_points() {
+        assert_eq!(encode(GeoPoint::new(57.64911, 10.40744), 11), "u4pruydqqvj");
+        assert_eq!(encode(GeoPoint::new(48.8583, 2.2945), 6), "u09tun");
+        assert_eq!(encode(GeoPoint::new(-33.8568, 151.2153), 5), "r3gx2");
+    }
+
+    #[test]
+    fn cell_sizes_shrink() {
+        let (w, h) = cell_size(1);
+        assert_eq!((w, h), (45.0, 45.0));
+        let (w, h) = cell_size(6);
+        assert!((w - 0.010986).abs() < 1e-5);
+        assert!((h - 0.005493).abs() < 1e-5);
+    }
+
+    #[test]
+    fn covering_contains_corners_and_is_capped() {
+        let area = BoundingBox::new(GeoPoint::new(48.85, 2.29), GeoPoint::new(48.87, 2.36));
+        let cells = covering_cells(&area, 9);
+        assert!(cells.len() <= MAX_COVERING_CELLS);
+        for corner in [
+            GeoPoint::new(48.85, 2.29),
+            GeoPoint::new(48.87, 2.36),
+            GeoPoint::new(48.85, 2.36),
+            GeoPoint::new(48.86, 2.33),
+        ] {
+            let hash = encode(corner, 9);
+            assert!(cells.iter().any(|cell| hash.starts_with(cell.as_str())));
+        }
+
+        // the antimeridian splits the box in two
+        let area = BoundingBox::new(GeoPoint::new(-1.0, 179.5), GeoPoint::new(1.0, -179.5));
+        let cells = covering_cells(&area, 9);
+        assert!(
+            cells
+                .iter()
+                .any(|c| c.starts_with('x') || c.starts_with('r'))
+        );
+        assert!(
+            cells
+                .iter()
+                .any(|c| c.starts_with('8') || c.starts_with('2'))
+        );
+    }
+}
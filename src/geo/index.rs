@@ -0,0 +1,302 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::condition::AttributePath;
+use crate::error::{TableError, TableResult};
+use crate::index::KeyIndex;
+use crate::types::{AttributeValue, Item, PrimaryKey};
+
+use super::geohash::{self, MAX_PRECISION};
+use super::point::{BoundingBox, GeoPoint};
+
+// ~5m cells; queries use shorter prefixes of the stored hash as needed
+const DEFAULT_PRECISION: usize = 9;
+
+#[derive(Debug, Clone)]
+struct GeoEntry {
+    key: PrimaryKey,
+    point: GeoPoint,
+}
+
+// items by the geohash of their coordinates
+#[derive(Debug)]
+pub struct GeoIndex {
+    name: String,
+    lat: AttributePath,
+    lng: AttributePath,
+    precision: usize,
+    // (geohash, table storage key) -> entry
+    cells: BTreeMap<(String, String), GeoEntry>,
+    // table storage key -> geohash
+    reverse_index: HashMap<String, String>,
+}
+
+impl GeoIndex {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn precision(&self) -> usize {
+        self.precision
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // items without valid numeric coordinates aren't indexed
+    pub fn put(&mut self, table_key: &PrimaryKey, item: &Item) {
+        let storage_key = table_key.to_storage_key();
+        self.remove(&storage_key);
+
+        let Some(point) = self.point(item) else {
+            return;
+        };
+        let hash = geohash::encode(point, self.precision);
+        self.reverse_index.insert(storage_key.clone(), hash.clone());
+        self.cells.insert(
+            (hash, storage_key),
+            GeoEntry {
+                key: table_key.clone(),
+                point,
+            },
+        );
+    }
+
+    pub fn delete(&mut self, table_key: &PrimaryKey) {
+        self.remove(&table_key.to_storage_key());
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.reverse_index.clear();
+    }
+
+    // keys and distances in meters, nearest first
+    pub fn within_radius(&self, center: GeoPoint, meters: f64) -> Vec<(PrimaryKey, f64)> {
+        let area = BoundingBox::around(center, meters);
+        self.nearest_first(&area, center, |entry| {
+            center.distance_to(&entry.point) <= meters
+        })
+    }
+
+    // keys and distances from the box's center in meters, nearest first
+    pub fn within_box(&self, area: &BoundingBox) -> Vec<(PrimaryKey, f64)> {
+        self.nearest_first(area, area.center(), |entry| area.contains(&entry.point))
+    }
+
+    fn nearest_first(
+        &self,
+        area: &BoundingBox,
+        origin: GeoPoint,
+        keep: impl Fn(&GeoEntry) -> bool,
+    ) -> Vec<(PrimaryKey, f64)> {
+        let mut hits: Vec<(&GeoEntry, f64)> = geohash::covering_cells(area, self.precision)
+            .iter()
+            .flat_map(|cell| self.in_cell(cell))
+            .filter(|entry| keep(entry))
+            .map(|entry| (entry, origin.distance_to(&entry.point)))
+            .collect();
+        hits.sort_by(|a, b| {
+            a.1.total_cmp(&b.1)
+                .then_with(|| a.0.key.to_storage_key().cmp(&b.0.key.to_storage_key()))
+        });
+        hits.into_iter()
+            .map(|(entry, distance)| (entry.key.clone(), distance))
+            .collect()
+    }
+
+    // entries whose hash starts with `cell`
+    fn in_cell(&self, cell: &str) -> impl Iterator<Item = &GeoEntry> {
+        // '~' sorts after every geohash character
+        let start = (cell.to_string(), String::new());
+        let end = (format!("{}~", cell), String::new());
+        self.cells.range(start..end).map(|(_, entry)| entry)
+    }
+
+    fn remove(&mut self, storage_key: &str) {
+        if let Some(hash) = self.reverse_index.remove(storage_key) {
+            self.cells.remove(&(hash, storage_key.to_string()));
+        }
+    }
+
+    fn point(&self, item: &Item) -> Option<GeoPoint> {
+        let coordinate = |path: &AttributePath| match path.resolve(item)? {
+            AttributeValue::N(n) => n.parse::<f64>().ok().filter(|v| v.is_finite()),
+            _ => None,
+        };
+        let point = GeoPoint::new(coordinate(&self.lat)?, coordinate(&self.lng)?);
+        point.is_valid().then_some(point)
+    }
+}
+
+impl KeyIndex for GeoIndex {
+    fn name(&self) -> &str {
+        GeoIndex::name(self)
+    }
+
+    fn put(&mut self, table_key: &PrimaryKey, item: &Item) {
+        GeoIndex::put(self, table_key, item)
+    }
+
+    fn delete(&mut self, table_key: &PrimaryKey) {
+        GeoIndex::delete(self, table_key)
+    }
+
+    fn clear(&mut self) {
+        GeoIndex::clear(self)
+    }
+}
+
+pub struct GeoIndexBuilder {
+    name: String,
+    lat: AttributePath,
+    lng: AttributePath,
+    precision: usize,
+}
+
+impl GeoIndexBuilder {
+    // reads `lat` and `lng` unless told otherwise
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            lat: AttributePath::new("lat"),
+            lng: AttributePath::new("lng"),
+            precision: DEFAULT_PRECISION,
+        }
+    }
+
+    pub fn coordinates(
+        mut self,
+        lat: impl Into<AttributePath>,
+        lng: impl Into<AttributePath>,
+    ) -> Self {
+        self.lat = lat.into();
+        self.lng = lng.into();
+        self
+    }
+
+    // geohash length stored per item, from 1 to 12
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn build(self) -> TableResult<GeoIndex> {
+        if !(1..=MAX_PRECISION).contains(&self.precision) {
+            return Err(TableError::validation_error(format!(
+                "geo index {}: geohash precision must be between 1 and {}",
+                self.name, MAX_PRECISION
+            )));
+        }
+        Ok(GeoIndex {
+            name: self.name,
+            lat: self.lat,
+            lng: self.lng,
+            precision: self.precision,
+            cells: BTreeMap::new(),
+            reverse_index: HashMap::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(id: &str, lat: f64, lng: f64) -> (PrimaryKey, Item) {
+        let item = Item::new()
+            .with_s("id", id)
+            .with_n("lat", lat)
+            .with_n("lng", lng);
+        (PrimaryKey::simple(id), item)
+    }
+
+    fn ids(hits: &[(PrimaryKey, f64)]) -> Vec<&str> {
+        hits.iter().map(|(key, _)| key.pk.as_s().unwrap()).collect()
+    }
+
+    fn paris() -> GeoIndex {
+        let mut index = GeoIndexBuilder::new("places").build().unwrap();
+        for (key, item) in [
+            place("louvre", 48.8606, 2.3376),
+            place("notre-dame", 48.8530, 2.3499),
+            place("eiffel", 48.8584, 2.2945),
+            place("versailles", 48.8049, 2.1204),
+        ] {
+            index.put(&key, &item);
+        }
+        index
+    }
+
+    #[test]
+    fn radius_is_exact_and_sorted() {
+        let index = paris();
+        let center = GeoPoint::new(48.8566, 2.3522);
+
+        let hits = index.within_radius(center, 2_000.0);
+        assert_eq!(ids(&hits), ["notre-dame", "louvre"]);
+        assert!(hits.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        let hits = index.within_radius(center, 5_000.0);
+        assert_eq!(ids(&hits), ["notre-dame", "louvre", "eiffel"]);
+        assert_eq!(index.within_radius(center, 50_000.0).len(), 4);
+    }
+
+    #[test]
+    fn bounding_box() {
+        let index = paris();
+        let area = BoundingBox::new(GeoPoint::new(48.85, 2.29), GeoPoint::new(48.87, 2.34));
+        let hits = index.within_box(&area);
+        let mut found = ids(&hits);
+        found.sort();
+        assert_eq!(found, ["eiffel", "louvre"]);
+    }
+
+    #[test]
+    fn moves_and_invalid_coordinates() {
+        let mut index = paris();
+        let (key, item) = place("eiffel", 48.8049, 2.1204);
+        index.put(&key, &item);
+        assert_eq!(index.len(), 4);
+        assert_eq!(
+            ids(&index.within_radius(GeoPoint::new(48.8049, 2.1204), 10.0)),
+            ["eiffel", "versailles"]
+        );
+
+        let (key, item) = place("louvre", 91.0, 2.0);
+        index.put(&key, &item);
+        index.put(
+            &PrimaryKey::simple("nowhere"),
+            &Item::new().with_s("id", "nowhere").with_s("lat", "48.8"),
+        );
+        assert_eq!(index.len(), 3);
+
+        index.delete(&PrimaryKey::simple("eiffel"));
+        assert_eq!(index.len(), 2);
+    }
+
+    #[test]
+    fn precision_is_checked_by_build() {
+        for precision in [0, MAX_PRECISION + 1] {
+            let err = GeoIndexBuilder::new("places")
+                .precision(precision)
+                .build()
+                .unwrap_err();
+            assert!(err.is_validation_error());
+        }
+        assert!(
+            GeoIndexBuilder::new("places")
+                .precision(MAX_PRECISION)
+                .build()
+                .is_ok()
+        );
+    }
+}
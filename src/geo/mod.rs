@@ -0,0 +1,9 @@
+mod geohash;
+mod index;
+mod point;
+mod query;
+
+pub use geohash::{MAX_PRECISION, cell_size, encode as encode_geohash};
+pub use index::{GeoIndex, GeoIndexBuilder};
+pub use point::{BoundingBox, EARTH_RADIUS_METERS, GeoPoint};
+pub use query::GeoHit;
@@ -0,0 +1,160 @@
+// mean earth radius used for haversine distances
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+impl GeoPoint {
+    pub fn new(lat: f64, lng: f64) -> Self {
+        Self { lat, lng }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lng)
+    }
+
+    // great-circle distance in meters
+    pub fn distance_to(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lng = (other.lng - self.lng).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+    }
+}
+
+// a box whose west edge is east of its east edge crosses the antimeridian
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub south_west: GeoPoint,
+    pub north_east: GeoPoint,
+}
+
+impl BoundingBox {
+    pub fn new(south_west: GeoPoint, north_east: GeoPoint) -> Self {
+        Self {
+            south_west,
+            north_east,
+        }
+    }
+
+    // the smallest box holding every point within `meters` of `center`
+    pub fn around(center: GeoPoint, meters: f64) -> Self {
+        let d_lat = (meters / EARTH_RADIUS_METERS).to_degrees();
+        let south = center.lat - d_lat;
+        let north = center.lat + d_lat;
+        // a circle reaching a pole covers every longitude
+        if south <= -90.0 || north >= 90.0 {
+            return Self::new(
+                GeoPoint::new(south.max(-90.0), -180.0),
+                GeoPoint::new(north.min(90.0), 180.0),
+            );
+        }
+
+        // half the span between the meridians tangent to the circle; with no tangent meridian
+        // the circle takes in every longitude
+        let ratio = (meters / EARTH_RADIUS_METERS).sin() / center.lat.to_radians().cos();
+        if ratio >= 1.0 {
+            return Self::new(GeoPoint::new(south, -180.0), GeoPoint::new(north, 180.0));
+        }
+        let d_lng = ratio.asin().to_degrees();
+        Self::new(
+            GeoPoint::new(south, wrap_longitude(center.lng - d_lng)),
+            GeoPoint::new(north, wrap_longitude(center.lng + d_lng)),
+        )
+    }
+
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.south_west.lng > self.north_east.lng
+    }
+
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        if point.lat < self.south_west.lat || point.lat > self.north_east.lat {
+            return false;
+        }
+        if self.crosses_antimeridian() {
+            point.lng >= self.south_west.lng || point.lng <= self.north_east.lng
+        } else {
+            point.lng >= self.south_west.lng && point.lng <= self.north_east.lng
+        }
+    }
+
+    pub fn center(&self) -> GeoPoint {
+        let lat = (self.south_west.lat + self.north_east.lat) / 2.0;
+        let mut east = self.north_east.lng;
+        if self.crosses_antimeridian() {
+            east += 360.0;
+        }
+        GeoPoint::new(lat, wrap_longitude((self.south_west.lng + east) / 2.0))
+    }
+
+    // (west, east) ranges that don't wrap
+    pub(crate) fn longitude_spans(&self) -> Vec<(f64, f64)> {
+        if self.crosses_antimeridian() {
+            vec![(self.south_west.lng, 180.0), (-180.0, self.north_east.lng)]
+        } else {
+            vec![(self.south_west.lng, self.north_east.lng)]
+        }
+    }
+}
+
+fn wrap_longitude(lng: f64) -> f64 {
+    if lng > 180.0 {
+        lng - 360.0
+    } else if lng < -180.0 {
+        lng + 360.0
+    } else {
+        lng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn haversine_distance() {
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let london = GeoPoint::new(51.5074, -0.1278);
+        let km = paris.distance_to(&london) / 1000.0;
+        assert!((km - 343.5).abs() < 1.0, "{}", km);
+        assert_eq!(paris.distance_to(&paris), 0.0);
+    }
+
+    #[test]
+    fn box_around_a_point() {
+        let center = GeoPoint::new(48.8566, 2.3522);
+        let area = BoundingBox::around(center, 1000.0);
+        assert!(area.contains(&center));
+        assert!(!area.crosses_antimeridian());
+        let north = GeoPoint::new(area.north_east.lat, center.lng);
+        assert!((center.distance_to(&north) - 1000.0).abs() < 1.0);
+
+        let area = BoundingBox::around(GeoPoint::new(0.0, 179.99), 5000.0);
+        assert!(area.crosses_antimeridian());
+        assert!(area.contains(&GeoPoint::new(0.0, -179.99)));
+        assert!(!area.contains(&GeoPoint::new(0.0, 0.0)));
+
+        let area = BoundingBox::around(GeoPoint::new(89.99, 0.0), 5000.0);
+        assert_eq!(area.north_east.lat, 90.0);
+        assert_eq!(area.longitude_spans(), [(-180.0, 180.0)]);
+    }
+
+    #[test]
+    fn box_reaches_the_tangent_meridians() {
+        let center = GeoPoint::new(70.0, -62.1);
+        let edge = GeoPoint::new(70.13, -67.95);
+        assert!(center.distance_to(&edge) < 222_390.0);
+        assert!(BoundingBox::around(center, 222_390.0).contains(&edge));
+    }
+
+    #[test]
+    fn center_handles_wrapping() {
+        let area = BoundingBox::new(GeoPoint::new(-1.0, 170.0), GeoPoint::new(1.0, -170.0));
+        assert_eq!(area.center(), GeoPoint::new(0.0, 180.0));
+    }
+}
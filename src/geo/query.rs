@@ -0,0 +1,9 @@
+use crate::types::{Item, PrimaryKey};
+
+#[derive(Debug, Clone)]
+pub struct GeoHit {
+    pub key: PrimaryKey,
+    pub item: Item,
+    // meters from the query's center
+    pub distance: f64,
+}
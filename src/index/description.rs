@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+
+use crate::condition::{AttributePath, Condition};
+use crate::types::{KeyAttribute, KeySchema};
+
+use super::projection::Projection;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexStatus {
+    Creating,
+    #[default]
+    Active,
+}
+
+impl IndexStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Creating => "CREATING",
+            Self::Active => "ACTIVE",
+        }
+    }
+}
+
+impl std::fmt::Display for IndexStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexDescription {
+    pub name: String,
+    pub key_schema: KeySchema,
+    // multi-attribute GSI keys: attributes after the first on each side
+    pub additional_partition_keys: Vec<KeyAttribute>,
+    pub additional_sort_keys: Vec<KeyAttribute>,
+    // key attributes read from nested paths
+    pub key_paths: BTreeMap<String, AttributePath>,
+    // key attribute whose set or list elements are indexed one by one
+    pub multi_valued: Option<String>,
+    pub filter: Option<Condition>,
+    pub unique: bool,
+    pub projection: Projection,
+    pub item_count: usize,
+    pub size_bytes: usize,
+    pub status: IndexStatus,
+}
@@ -1,17 +1,39 @@
-use crate::error::TableResult;
-use crate::query::{KeyCondition, QueryExecutor, QueryOptions, QueryResult};
-use crate::types::{Item, KeySchema, KeyValue, PrimaryKey};
+use std::collections::BTreeMap;
 
-use super::projection::Projection;
-use super::storage::IndexStorage;
+use crate::condition::{AttributePath, Condition, evaluate};
+use crate::error::{TableError, TableResult};
+use crate::query::{KeyCondition, QueryOptions, QueryResult, SortKeyOp};
+use crate::types::{
+    AttributeValue, Item, KeyAttribute, KeySchema, KeyType, KeyValidationError, KeyValue,
+    PrimaryKey,
+};
+
+use super::description::{IndexDescription, IndexStatus};
+use super::projection::{Projection, project_path};
+use super::storage::{IndexKey, IndexStorage, Overlay};
+
+// per side of a multi-attribute GSI key
+pub const MAX_KEY_ATTRIBUTES: usize = 4;
 
 #[derive(Debug)]
 pub struct GlobalSecondaryIndex {
     name: String,
+    // first partition and sort attributes; any further ones are in the additional lists
     schema: KeySchema,
+    additional_partition_keys: Vec<KeyAttribute>,
+    additional_sort_keys: Vec<KeyAttribute>,
+    // key attributes read from inside a document rather than from the top level
+    key_paths: BTreeMap<String, AttributePath>,
+    // key attribute holding a set or list; each element gets its own entry
+    multi_valued: Option<String>,
+    // partial index: only items matching this are indexed
+    filter: Option<Condition>,
+    // no two items may share an index key
+    unique: bool,
     projection: Projection,
     table_schema: KeySchema,
     storage: IndexStorage<(PrimaryKey, Item)>,
+    status: IndexStatus,
 }
 
 impl GlobalSecondaryIndex {
@@ -24,9 +46,16 @@ impl GlobalSecondaryIndex {
         Self {
             name: name.into(),
             schema,
+            additional_partition_keys: Vec::new(),
+            additional_sort_keys: Vec::new(),
+            key_paths: BTreeMap::new(),
+            multi_valued: None,
+            filter: None,
+            unique: false,
             projection,
             table_schema,
             storage: IndexStorage::new(),
+            status: IndexStatus::Active,
         }
     }
 
@@ -40,11 +69,51 @@ impl GlobalSecondaryIndex {
         &self.schema
     }
 
+    pub fn partition_key_attributes(&self) -> impl Iterator<Item = &KeyAttribute> {
+        std::iter::once(&self.schema.partition_key).chain(&self.additional_partition_keys)
+    }
+
+    pub fn sort_key_attributes(&self) -> impl Iterator<Item = &KeyAttribute> {
+        self.schema
+            .sort_key
+            .iter()
+            .chain(&self.additional_sort_keys)
+    }
+
+    pub fn key_path(&self, name: &str) -> Option<&AttributePath> {
+        self.key_paths.get(name)
+    }
+
+    #[inline]
+    pub fn multi_valued(&self) -> Option<&str> {
+        self.multi_valued.as_deref()
+    }
+
+    #[inline]
+    pub fn filter(&self) -> Option<&Condition> {
+        self.filter.as_ref()
+    }
+
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
     #[inline]
     pub fn projection(&self) -> &Projection {
         &self.projection
     }
 
+    #[inline]
+    pub fn status(&self) -> IndexStatus {
+        self.status
+    }
+
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.status == IndexStatus::Active
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.storage.len()
@@ -55,27 +124,90 @@ impl GlobalSecondaryIndex {
         self.storage.is_empty()
     }
 
+    pub fn size_bytes(&self) -> usize {
+        self.storage
+            .values()
+            .map(|(_, item)| item.encoded_size())
+            .sum()
+    }
+
+    pub fn describe(&self) -> IndexDescription {
+        IndexDescription {
+            name: self.name.clone(),
+            key_schema: self.schema.clone(),
+            additional_partition_keys: self.additional_partition_keys.clone(),
+            additional_sort_keys: self.additional_sort_keys.clone(),
+            key_paths: self.key_paths.clone(),
+            multi_valued: self.multi_valued.clone(),
+            filter: self.filter.clone(),
+            unique: self.unique,
+            projection: self.projection.clone(),
+            item_count: self.storage.item_count(),
+            size_bytes: self.size_bytes(),
+            status: self.status,
+        }
+    }
+
+    pub(crate) fn set_status(&mut self, status: IndexStatus) {
+        self.status = status;
+    }
+
     pub fn put(&mut self, table_key: PrimaryKey, item: &Item) -> Option<Item> {
-        // if an item doesn't have index keys, it's a sparse index - item just isn't indexed
-        let index_key = match self.extract_index_key(item) {
-            Some(k) => k,
-            None => {
-                return self
-                    .storage
-                    .remove_by_table_key(&table_key.to_storage_key())
-                    .map(|(_, item)| item);
-            }
+        let entry = self.entry(item);
+        let multi_valued = self.multi_valued.is_some();
+        store(&mut self.storage, multi_valued, table_key, entry)
+    }
+
+    // the index keys and projected item `item` is stored under, or nothing to remove it.
+    // if an item doesn't have index keys, it's a sparse index - item just isn't indexed.
+    // the same goes for items outside a partial index's filter
+    fn entry(&self, item: &Item) -> Option<(Vec<IndexKey>, Item)> {
+        let index_keys = if self.includes(item) {
+            self.extract_index_keys(item)
+        } else {
+            Vec::new()
         };
+        if index_keys.is_empty() {
+            return None;
+        }
 
-        let storage_key = self.make_storage_key(&index_key, &table_key);
-        let table_storage_key = table_key.to_storage_key();
-        let projected = self
+        let mut projected = self
             .projection
             .project_item(item, &self.table_schema, &self.schema);
+        // the projection only knows the first attribute of each side, by top-level name
+        for attribute in self
+            .partition_key_attributes()
+            .chain(self.sort_key_attributes())
+        {
+            match self.key_paths.get(&attribute.name) {
+                Some(path) => project_path(item, path, &mut projected),
+                None => {
+                    if let Some(value) = item.get(&attribute.name) {
+                        projected.set(attribute.name.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        Some((index_keys, projected))
+    }
 
-        self.storage
-            .put(table_storage_key, storage_key, (table_key, projected))
-            .map(|(_, item)| item)
+    // the entries `writes` will leave once applied, without applying them; later writes to
+    // the same item win
+    pub(crate) fn overlay<'a>(
+        &self,
+        writes: impl IntoIterator<Item = (&'a PrimaryKey, Option<&'a Item>)>,
+    ) -> Overlay<(PrimaryKey, Item)> {
+        let mut overlay = Overlay::new();
+        for (table_key, item) in writes {
+            let entry = item.and_then(|item| self.entry(item));
+            store(
+                overlay.replace(&table_key.to_storage_key()),
+                self.multi_valued.is_some(),
+                table_key.clone(),
+                entry,
+            );
+        }
+        overlay
     }
 
     pub fn delete(&mut self, table_key: &PrimaryKey) -> Option<Item> {
@@ -93,55 +225,342 @@ impl GlobalSecondaryIndex {
         condition: KeyCondition,
         options: QueryOptions,
     ) -> TableResult<QueryResult> {
-        let executor = QueryExecutor::new(&self.schema);
-        executor.validate_condition(&condition)?;
-
-        let items = self.storage.values().filter_map(|(_, item)| {
-            self.extract_index_key(item)
-                .map(|index_key| (index_key, item.clone()))
-        });
+        self.query_overlaid(condition, options, None)
+    }
 
-        executor.execute(items, &condition, &options)
+    pub fn count(
+        &self,
+        condition: &KeyCondition,
+        filter: Option<&Condition>,
+    ) -> TableResult<QueryResult> {
+        self.count_overlaid(condition, filter, None)
     }
 
     pub fn scan(&self) -> Vec<&Item> {
-        self.storage.values().map(|(_, item)| item).collect()
+        self.scan_overlaid(None)
+    }
+
+    fn query_overlaid(
+        &self,
+        condition: KeyCondition,
+        options: QueryOptions,
+        overlay: Option<&Overlay<(PrimaryKey, Item)>>,
+    ) -> TableResult<QueryResult> {
+        let items: Vec<Item> = self
+            .matching_overlaid(&condition, &options, overlay)?
+            .into_iter()
+            .cloned()
+            .collect();
+
+        Ok(QueryResult {
+            count: items.len(),
+            scanned_count: items.len(),
+            items,
+        })
+    }
+
+    // projected items in query order, without copying them
+    fn matching_overlaid<'a>(
+        &'a self,
+        condition: &KeyCondition,
+        options: &QueryOptions,
+        overlay: Option<&'a Overlay<(PrimaryKey, Item)>>,
+    ) -> TableResult<Vec<&'a Item>> {
+        self.validate_condition(condition)?;
+
+        let entries = match self.multi_valued {
+            Some(_) => self
+                .storage
+                .query_distinct_overlaid(condition, options, overlay),
+            None => self.storage.query_overlaid(condition, options, overlay),
+        };
+        Ok(entries.into_iter().map(|(_, item)| item).collect())
+    }
+
+    fn count_overlaid(
+        &self,
+        condition: &KeyCondition,
+        filter: Option<&Condition>,
+        overlay: Option<&Overlay<(PrimaryKey, Item)>>,
+    ) -> TableResult<QueryResult> {
+        self.validate_condition(condition)?;
+
+        let entries: Box<dyn Iterator<Item = &(PrimaryKey, Item)>> = match self.multi_valued {
+            Some(_) => Box::new(self.storage.range_distinct_overlaid(condition, overlay)),
+            None => Box::new(self.storage.range_overlaid(condition, overlay)),
+        };
+        let mut result = QueryResult::empty();
+        for (_, item) in entries {
+            result.scanned_count += 1;
+            if filter.is_none_or(|filter| evaluate(filter, item).unwrap_or(false)) {
+                result.count += 1;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn scan_overlaid<'a>(
+        &'a self,
+        overlay: Option<&'a Overlay<(PrimaryKey, Item)>>,
+    ) -> Vec<&'a Item> {
+        self.storage
+            .values_overlaid(overlay)
+            .map(|(_, item)| item)
+            .collect()
     }
 
     pub fn clear(&mut self) {
         self.storage.clear();
     }
 
-    fn extract_index_key(&self, item: &Item) -> Option<PrimaryKey> {
-        let pk_attr = item.get(self.schema.pk_name())?;
-        let pk = KeyValue::from_attribute_with_type(pk_attr, self.schema.partition_key.key_type)?;
+    // existing entries were projected with the old projection, so the caller
+    // must re-put every base item afterwards
+    pub(crate) fn reset_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+        self.storage.clear();
+    }
+
+    fn validate_condition(&self, condition: &KeyCondition) -> TableResult<()> {
+        let partition: Vec<_> = self.partition_key_attributes().collect();
+        let values: Vec<_> = condition.partition_key_values().collect();
+        if values.len() != partition.len() {
+            return Err(TableError::validation_error(format!(
+                "index {} needs {} partition key value(s), got {}",
+                self.name,
+                partition.len(),
+                values.len()
+            )));
+        }
+        for (attribute, value) in partition.into_iter().zip(values) {
+            check_key_type(attribute, value)?;
+        }
 
-        let sk = if let Some(sk_def) = &self.schema.sort_key {
-            let sk_attr = item.get(&sk_def.name)?;
-            Some(KeyValue::from_attribute_with_type(
-                sk_attr,
-                sk_def.key_type,
-            )?)
-        } else {
-            None
+        let sort: Vec<_> = self.sort_key_attributes().collect();
+        let equalities = &condition.sort_key_equalities;
+        let used = equalities.len() + usize::from(condition.sort_key.is_some());
+        if sort.is_empty() && used > 0 {
+            return Err(TableError::InvalidKey(
+                KeyValidationError::MissingAttribute {
+                    name: "sort_key".to_string(),
+                },
+            ));
+        }
+        if used > sort.len() {
+            return Err(TableError::validation_error(format!(
+                "index {} has {} sort key attribute(s), condition uses {}",
+                self.name,
+                sort.len(),
+                used
+            )));
+        }
+        for (attribute, value) in sort.iter().zip(equalities) {
+            check_key_type(attribute, value)?;
+        }
+        if let Some(op) = &condition.sort_key {
+            let attribute = sort[equalities.len()];
+            check_key_type(attribute, op.value())?;
+            if let SortKeyOp::Between { high, .. } = op {
+                check_key_type(attribute, high)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // the key this item would be indexed under, if it's indexed at all
+    pub(crate) fn index_key_for(&self, item: &Item) -> Option<IndexKey> {
+        self.extract_index_key(item).filter(|_| self.includes(item))
+    }
+
+    // base table keys of the items indexed under `index_key`
+    pub(crate) fn holders(&self, index_key: &IndexKey) -> impl Iterator<Item = &PrimaryKey> {
+        self.storage.with_key(index_key).map(|(key, _)| key)
+    }
+
+    pub(crate) fn duplicate_keys(&self) -> Vec<Vec<PrimaryKey>> {
+        self.storage
+            .duplicates()
+            .into_iter()
+            .map(|group| group.into_iter().map(|(key, _)| key.clone()).collect())
+            .collect()
+    }
+
+    fn attribute_value<'a>(&self, item: &'a Item, name: &str) -> Option<&'a AttributeValue> {
+        match self.key_paths.get(name) {
+            Some(path) => path.resolve(item),
+            None => item.get(name),
+        }
+    }
+
+    fn includes(&self, item: &Item) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| evaluate(filter, item).unwrap_or(false))
+    }
+
+    // one key per element of the multi-valued attribute, otherwise at most one
+    fn extract_index_keys(&self, item: &Item) -> Vec<IndexKey> {
+        let Some(name) = &self.multi_valued else {
+            return self.extract_index_key(item).into_iter().collect();
+        };
+        let Some(attribute) = self
+            .partition_key_attributes()
+            .chain(self.sort_key_attributes())
+            .find(|attribute| &attribute.name == name)
+        else {
+            return Vec::new();
+        };
+        let Some(value) = self.attribute_value(item, name) else {
+            return Vec::new();
         };
 
-        Some(PrimaryKey { pk, sk })
+        elements(value, attribute.key_type)
+            .iter()
+            .filter_map(|element| self.extract_index_key_with(item, Some(element)))
+            .collect()
     }
 
-    #[inline]
-    fn make_storage_key(&self, index_key: &PrimaryKey, table_key: &PrimaryKey) -> String {
-        format!(
-            "{}|{}",
-            index_key.to_storage_key(),
-            table_key.to_storage_key()
-        )
+    fn extract_index_key(&self, item: &Item) -> Option<IndexKey> {
+        self.extract_index_key_with(item, None)
     }
+
+    // `element` stands in for the multi-valued attribute
+    fn extract_index_key_with(&self, item: &Item, element: Option<&KeyValue>) -> Option<IndexKey> {
+        let extract = |attribute: &KeyAttribute| {
+            if let Some(element) = element
+                && self.multi_valued.as_ref() == Some(&attribute.name)
+            {
+                return Some(element.clone());
+            }
+            let value = self.attribute_value(item, &attribute.name)?;
+            KeyValue::from_attribute_with_type(value, attribute.key_type)
+        };
+
+        Some(IndexKey {
+            partition: self
+                .partition_key_attributes()
+                .map(extract)
+                .collect::<Option<_>>()?,
+            sort: self
+                .sort_key_attributes()
+                .map(extract)
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+fn store(
+    storage: &mut IndexStorage<(PrimaryKey, Item)>,
+    multi_valued: bool,
+    table_key: PrimaryKey,
+    entry: Option<(Vec<IndexKey>, Item)>,
+) -> Option<Item> {
+    let table_storage_key = table_key.to_storage_key();
+    let Some((index_keys, projected)) = entry else {
+        return storage
+            .remove_by_table_key(&table_storage_key)
+            .map(|(_, item)| item);
+    };
+
+    if multi_valued {
+        let old = storage
+            .get(&table_storage_key)
+            .map(|(_, item)| item.clone());
+        storage.put_many(table_storage_key, &index_keys, (table_key, projected));
+        return old;
+    }
+    storage
+        .put(table_storage_key, &index_keys[0], (table_key, projected))
+        .map(|(_, item)| item)
+}
+
+// the index as readers see it, with writes it hasn't been given yet laid over it
+pub(crate) struct GsiView<'a> {
+    gsi: &'a GlobalSecondaryIndex,
+    overlay: Option<Overlay<(PrimaryKey, Item)>>,
+}
+
+impl<'a> GsiView<'a> {
+    pub(crate) fn new(
+        gsi: &'a GlobalSecondaryIndex,
+        overlay: Option<Overlay<(PrimaryKey, Item)>>,
+    ) -> Self {
+        Self { gsi, overlay }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.gsi.is_active()
+    }
+
+    pub(crate) fn query_with_options(
+        &self,
+        condition: KeyCondition,
+        options: QueryOptions,
+    ) -> TableResult<QueryResult> {
+        self.gsi
+            .query_overlaid(condition, options, self.overlay.as_ref())
+    }
+
+    pub(crate) fn matching(
+        &self,
+        condition: &KeyCondition,
+        options: &QueryOptions,
+    ) -> TableResult<Vec<&Item>> {
+        self.gsi
+            .matching_overlaid(condition, options, self.overlay.as_ref())
+    }
+
+    pub(crate) fn count(
+        &self,
+        condition: &KeyCondition,
+        filter: Option<&Condition>,
+    ) -> TableResult<QueryResult> {
+        self.gsi
+            .count_overlaid(condition, filter, self.overlay.as_ref())
+    }
+
+    pub(crate) fn scan(&self) -> Vec<&Item> {
+        self.gsi.scan_overlaid(self.overlay.as_ref())
+    }
+}
+
+// a set or list yields its elements; anything else is a single value
+fn elements(value: &AttributeValue, key_type: KeyType) -> Vec<KeyValue> {
+    match (value, key_type) {
+        (AttributeValue::Ss(set), KeyType::S) => set.iter().cloned().map(KeyValue::S).collect(),
+        (AttributeValue::Ns(set), KeyType::N) => set.iter().cloned().map(KeyValue::N).collect(),
+        (AttributeValue::Bs(set), KeyType::B) => set.iter().cloned().map(KeyValue::B).collect(),
+        (AttributeValue::L(list), _) => list
+            .iter()
+            .filter_map(|v| KeyValue::from_attribute_with_type(v, key_type))
+            .collect(),
+        _ => KeyValue::from_attribute_with_type(value, key_type)
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn check_key_type(attribute: &KeyAttribute, value: &KeyValue) -> TableResult<()> {
+    if attribute.key_type.matches(value) {
+        return Ok(());
+    }
+    Err(TableError::InvalidKey(KeyValidationError::TypeMismatch {
+        name: attribute.name.clone(),
+        expected: attribute.key_type.as_str(),
+        actual: value.type_name(),
+    }))
 }
 
 pub struct GsiBuilder {
     name: String,
     schema: KeySchema,
+    additional_partition_keys: Vec<KeyAttribute>,
+    additional_sort_keys: Vec<KeyAttribute>,
+    key_paths: BTreeMap<String, AttributePath>,
+    multi_valued: Option<String>,
+    filter: Option<Condition>,
+    unique: bool,
     projection: Projection,
 }
 
@@ -150,10 +569,65 @@ impl GsiBuilder {
         Self {
             name: name.into(),
             schema,
+            additional_partition_keys: Vec::new(),
+            additional_sort_keys: Vec::new(),
+            key_paths: BTreeMap::new(),
+            multi_valued: None,
+            filter: None,
+            unique: false,
             projection: Projection::All,
         }
     }
 
+    // writes that would give two items the same index key fail with UniqueViolation
+    pub fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    // makes a partial index; checked against the full item on every write
+    pub fn filter(mut self, filter: Condition) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    // appends a partition key attribute after the schema's; queries must give every one
+    pub fn partition_key_attribute(mut self, name: impl Into<String>, key_type: KeyType) -> Self {
+        self.additional_partition_keys
+            .push(KeyAttribute::new(name, key_type));
+        self
+    }
+
+    // appends a sort key attribute; conditions apply to these left to right
+    pub fn sort_key_attribute(mut self, name: impl Into<String>, key_type: KeyType) -> Self {
+        let attribute = KeyAttribute::new(name, key_type);
+        if self.schema.sort_key.is_none() {
+            self.schema.sort_key = Some(attribute);
+            return self;
+        }
+        self.additional_sort_keys.push(attribute);
+        self
+    }
+
+    // reads the named key attribute from `path`, e.g. `customer.id`, instead of the top level;
+    // queries still refer to the attribute by name
+    pub fn key_path(mut self, name: impl Into<String>, path: impl Into<AttributePath>) -> Self {
+        self.key_paths.insert(name.into(), path.into());
+        self
+    }
+
+    // indexes every element of the named key attribute's set or list, so a query for one
+    // element finds each item holding it; results list an item once however many elements match
+    pub fn multi_valued(mut self, name: impl Into<String>) -> Self {
+        self.multi_valued = Some(name.into());
+        self
+    }
+
     pub fn projection(mut self, projection: Projection) -> Self {
         self.projection = projection;
         self
@@ -173,14 +647,63 @@ impl GsiBuilder {
         self
     }
 
-    pub fn build(self, table_schema: KeySchema) -> GlobalSecondaryIndex {
-        GlobalSecondaryIndex::new(self.name, self.schema, self.projection, table_schema)
+    pub fn build(self, table_schema: KeySchema) -> TableResult<GlobalSecondaryIndex> {
+        for (part, additional) in [
+            ("partition", &self.additional_partition_keys),
+            ("sort", &self.additional_sort_keys),
+        ] {
+            if additional.len() + 1 > MAX_KEY_ATTRIBUTES {
+                return Err(TableError::validation_error(format!(
+                    "GSI {}: a {} key has at most {} attributes",
+                    self.name, part, MAX_KEY_ATTRIBUTES
+                )));
+            }
+        }
+
+        let is_key = |name: &String| {
+            std::iter::once(&self.schema.partition_key)
+                .chain(&self.schema.sort_key)
+                .chain(&self.additional_partition_keys)
+                .chain(&self.additional_sort_keys)
+                .any(|attribute| &attribute.name == name)
+        };
+        if let Some(name) = self.key_paths.keys().find(|name| !is_key(name)) {
+            return Err(TableError::validation_error(format!(
+                "GSI {}: key path given for '{}', which is not a key attribute",
+                self.name, name
+            )));
+        }
+        if let Some(name) = &self.multi_valued {
+            if !is_key(name) {
+                return Err(TableError::validation_error(format!(
+                    "GSI {}: multi-valued attribute '{}' is not a key attribute",
+                    self.name, name
+                )));
+            }
+            if self.unique {
+                return Err(TableError::validation_error(format!(
+                    "GSI {}: a multi-valued GSI can't be unique",
+                    self.name
+                )));
+            }
+        }
+
+        let mut gsi =
+            GlobalSecondaryIndex::new(self.name, self.schema, self.projection, table_schema);
+        gsi.additional_partition_keys = self.additional_partition_keys;
+        gsi.additional_sort_keys = self.additional_sort_keys;
+        gsi.key_paths = self.key_paths;
+        gsi.multi_valued = self.multi_valued;
+        gsi.filter = self.filter;
+        gsi.unique = self.unique;
+        Ok(gsi)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::condition::attr;
     use crate::query::KeyCondition;
     use crate::types::KeyType;
 
@@ -246,4 +769,335 @@ mod tests {
         // should not have non-key attributes
         assert!(!item.contains("amount"));
     }
+
+    #[test]
+    fn filter_limits_entries() {
+        let schema = KeySchema::simple("order_date", KeyType::S);
+        let mut gsi = GsiBuilder::new("big-orders", schema)
+            .filter(attr("amount").ge(100))
+            .build(table_schema())
+            .unwrap();
+
+        let key = PrimaryKey::composite("user1", "order001");
+        gsi.put(
+            key.clone(),
+            &sample_order("user1", "order001", "2026-01-07", 50),
+        );
+        assert!(gsi.is_empty());
+
+        gsi.put(
+            key.clone(),
+            &sample_order("user1", "order001", "2026-01-07", 150),
+        );
+        assert_eq!(gsi.len(), 1);
+
+        // no longer matches, so the entry goes away
+        let removed = gsi.put(key, &sample_order("user1", "order001", "2026-01-07", 10));
+        assert!(removed.is_some());
+        assert!(gsi.is_empty());
+    }
+
+    mod multi_attribute {
+        use super::*;
+
+        fn create_gsi() -> GlobalSecondaryIndex {
+            GsiBuilder::new("by-tenant", KeySchema::simple("tenant", KeyType::S))
+                .partition_key_attribute("region", KeyType::S)
+                .sort_key_attribute("status", KeyType::S)
+                .sort_key_attribute("amount", KeyType::N)
+                .keys_only()
+                .build(table_schema())
+                .unwrap()
+        }
+
+        fn order(order: &str, region: &str, status: &str, amount: i32) -> Item {
+            sample_order("user1", order, "2026-01-07", amount)
+                .with_s("tenant", "acme")
+                .with_s("region", region)
+                .with_s("status", status)
+        }
+
+        #[test]
+        fn queries_left_to_right() {
+            let mut gsi = create_gsi();
+            for (i, (region, status, amount)) in [
+                ("eu", "open", 30),
+                ("eu", "open", 5),
+                ("eu", "closed", 10),
+                ("us", "open", 1),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                let id = format!("order{}", i);
+                gsi.put(
+                    PrimaryKey::composite("user1", id.as_str()),
+                    &order(&id, region, status, amount),
+                );
+            }
+            // missing region, so not indexed
+            gsi.put(
+                PrimaryKey::composite("user1", "order9"),
+                &sample_order("user1", "order9", "2026-01-07", 1).with_s("tenant", "acme"),
+            );
+            assert_eq!(gsi.len(), 4);
+
+            let eu = || KeyCondition::pk("acme").and_pk("eu");
+            let ids = |condition: KeyCondition| -> Vec<String> {
+                gsi.query(condition)
+                    .unwrap()
+                    .items
+                    .iter()
+                    .map(|item| item.get("order_id").unwrap().as_s().unwrap().to_string())
+                    .collect()
+            };
+
+            assert_eq!(ids(eu()), ["order2", "order1", "order0"]);
+            assert_eq!(ids(eu().sk_eq("open")), ["order1", "order0"]);
+            assert_eq!(
+                ids(eu().and_sk_eq("open").sk_gt(KeyValue::N("10".into()))),
+                ["order0"]
+            );
+
+            // keys-only projection keeps every key attribute
+            let item = &gsi.query(eu().sk_eq("closed")).unwrap().items[0];
+            assert!(item.contains("region") && item.contains("amount"));
+            assert!(!item.contains("order_date"));
+        }
+
+        #[test]
+        fn validates_conditions() {
+            let gsi = create_gsi();
+
+            let err = gsi.query(KeyCondition::pk("acme")).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("needs 2 partition key value(s), got 1")
+            );
+
+            let err = gsi
+                .query(
+                    KeyCondition::pk("acme")
+                        .and_pk("eu")
+                        .and_sk_eq("open")
+                        .and_sk_eq("x")
+                        .sk_eq("y"),
+                )
+                .unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("has 2 sort key attribute(s), condition uses 3")
+            );
+
+            let err = gsi
+                .query(
+                    KeyCondition::pk("acme")
+                        .and_pk("eu")
+                        .and_sk_eq("open")
+                        .sk_eq("high"),
+                )
+                .unwrap_err();
+            assert!(matches!(err, TableError::InvalidKey(_)));
+        }
+
+        #[test]
+        fn limits_attribute_count() {
+            let partition_keys = |names: &[&str]| {
+                names.iter().fold(
+                    GsiBuilder::new("gsi", KeySchema::simple("a", KeyType::S)),
+                    |builder, name| builder.partition_key_attribute(*name, KeyType::S),
+                )
+            };
+            assert!(
+                partition_keys(&["b", "c", "d"])
+                    .build(table_schema())
+                    .is_ok()
+            );
+
+            let err = partition_keys(&["b", "c", "d", "e"])
+                .build(table_schema())
+                .unwrap_err();
+            assert!(err.is_validation_error());
+            assert!(
+                err.to_string()
+                    .contains("a partition key has at most 4 attributes")
+            );
+
+            let mut builder = GsiBuilder::new("gsi", KeySchema::simple("a", KeyType::S));
+            for name in ["s1", "s2", "s3", "s4", "s5"] {
+                builder = builder.sort_key_attribute(name, KeyType::S);
+            }
+            assert!(builder.build(table_schema()).is_err());
+        }
+    }
+
+    mod key_paths {
+        use super::*;
+        use crate::types::AttributeValue;
+        use std::collections::BTreeMap;
+
+        fn order_with_customer(order: &str, customer: &str) -> Item {
+            let mut details = BTreeMap::new();
+            details.insert("id".to_string(), AttributeValue::S(customer.into()));
+            details.insert("name".to_string(), AttributeValue::S("Ann".into()));
+            Item::new()
+                .with_s("user_id", "user1")
+                .with_s("order_id", order)
+                .with_n("amount", 100)
+                .with("customer", AttributeValue::M(details))
+        }
+
+        #[test]
+        fn indexes_nested_values() {
+            let mut gsi =
+                GsiBuilder::new("by-customer", KeySchema::simple("customer_id", KeyType::S))
+                    .key_path("customer_id", AttributePath::new("customer").key("id"))
+                    .keys_only()
+                    .build(table_schema())
+                    .unwrap();
+
+            gsi.put(
+                PrimaryKey::composite("user1", "order001"),
+                &order_with_customer("order001", "c1"),
+            );
+            gsi.put(
+                PrimaryKey::composite("user1", "order002"),
+                &Item::new()
+                    .with_s("user_id", "user1")
+                    .with_s("order_id", "order002")
+                    .with_s("customer_id", "c1"),
+            );
+            assert_eq!(gsi.len(), 1);
+
+            let result = gsi.query(KeyCondition::pk("c1")).unwrap();
+            let item = &result.items[0];
+            assert!(!item.contains("amount"));
+            let AttributeValue::M(customer) = item.get("customer").unwrap() else {
+                panic!("expected a map");
+            };
+            assert_eq!(customer.len(), 1);
+            assert_eq!(customer.get("id").unwrap().as_s(), Some("c1"));
+        }
+
+        #[test]
+        fn rejects_paths_for_other_attributes() {
+            let err = GsiBuilder::new("gsi", KeySchema::simple("a", KeyType::S))
+                .key_path("b", AttributePath::new("x").key("b"))
+                .build(table_schema())
+                .unwrap_err();
+            assert!(err.is_validation_error());
+            assert!(
+                err.to_string()
+                    .contains("'b', which is not a key attribute")
+            );
+        }
+    }
+
+    mod multi_valued {
+        use super::*;
+        use std::collections::BTreeSet;
+
+        fn tags(values: &[&str]) -> AttributeValue {
+            AttributeValue::Ss(
+                values
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<BTreeSet<_>>(),
+            )
+        }
+
+        fn post(id: &str, tag_values: &[&str]) -> Item {
+            Item::new()
+                .with_s("user_id", "user1")
+                .with_s("order_id", id)
+                .with("tags", tags(tag_values))
+        }
+
+        #[test]
+        fn one_entry_per_element() {
+            let mut gsi = GsiBuilder::new("by-tag", KeySchema::simple("tags", KeyType::S))
+                .multi_valued("tags")
+                .build(table_schema())
+                .unwrap();
+            let key = PrimaryKey::composite("user1", "p1");
+
+            gsi.put(key.clone(), &post("p1", &["rust", "db"]));
+            gsi.put(PrimaryKey::composite("user1", "p2"), &post("p2", &["rust"]));
+            assert_eq!(gsi.len(), 3);
+            assert_eq!(gsi.describe().item_count, 2);
+            assert_eq!(gsi.query(KeyCondition::pk("rust")).unwrap().count, 2);
+            assert_eq!(gsi.query(KeyCondition::pk("db")).unwrap().count, 1);
+
+            gsi.put(key.clone(), &post("p1", &["web"]));
+            assert_eq!(gsi.len(), 2);
+            assert_eq!(gsi.query(KeyCondition::pk("db")).unwrap().count, 0);
+
+            // lists work too; non-matching elements are skipped
+            let list = Item::new()
+                .with_s("user_id", "user1")
+                .with_s("order_id", "p1")
+                .with_l(
+                    "tags",
+                    vec![
+                        AttributeValue::S("db".into()),
+                        AttributeValue::N("1".into()),
+                    ],
+                );
+            gsi.put(key.clone(), &list);
+            assert_eq!(gsi.query(KeyCondition::pk("db")).unwrap().count, 1);
+
+            gsi.delete(&key);
+            assert_eq!(gsi.len(), 1);
+        }
+
+        #[test]
+        fn results_list_each_item_once() {
+            let mut gsi = GsiBuilder::new(
+                "by-user-tag",
+                KeySchema::composite("user_id", KeyType::S, "tags", KeyType::S),
+            )
+            .multi_valued("tags")
+            .build(table_schema())
+            .unwrap();
+
+            gsi.put(
+                PrimaryKey::composite("user1", "p1"),
+                &post("p1", &["a", "b", "c"]),
+            );
+            gsi.put(PrimaryKey::composite("user1", "p2"), &post("p2", &["b"]));
+
+            let result = gsi.query(KeyCondition::pk("user1")).unwrap();
+            assert_eq!(result.count, 2);
+            let result = gsi
+                .query_with_options(KeyCondition::pk("user1"), QueryOptions::new().with_limit(1))
+                .unwrap();
+            assert_eq!(result.count, 1);
+
+            let count = gsi
+                .count(&KeyCondition::pk("user1").sk_ge("b"), None)
+                .unwrap();
+            assert_eq!(count.count, 2);
+        }
+
+        #[test]
+        fn cannot_be_unique() {
+            let err = GsiBuilder::new("by-tag", KeySchema::simple("tags", KeyType::S))
+                .multi_valued("tags")
+                .unique()
+                .build(table_schema())
+                .unwrap_err();
+            assert!(err.is_validation_error());
+            assert!(err.to_string().contains("can't be unique"));
+        }
+
+        #[test]
+        fn must_be_a_key_attribute() {
+            let err = GsiBuilder::new("by-tag", KeySchema::simple("tags", KeyType::S))
+                .multi_valued("labels")
+                .build(table_schema())
+                .unwrap_err();
+            assert!(err.is_validation_error());
+            assert!(err.to_string().contains("'labels' is not a key attribute"));
+        }
+    }
 }
@@ -1,17 +1,19 @@
+use crate::condition::{AttributePath, Condition, evaluate};
 use crate::error::TableResult;
 use crate::query::{KeyCondition, QueryExecutor, QueryOptions, QueryResult};
-use crate::types::{
-    Item, KeyAttribute, KeySchema, KeyType, KeyValue, PrimaryKey, encode_key_component,
-};
+use crate::types::{Item, KeyAttribute, KeySchema, KeyType, KeyValue, PrimaryKey};
 
-use super::projection::Projection;
-use super::storage::IndexStorage;
+use super::description::{IndexDescription, IndexStatus};
+use super::projection::{Projection, project_path};
+use super::storage::{IndexKey, IndexStorage};
 
 /// Local Secondary Index - same partition key as table, different sort key.
 #[derive(Debug)]
 pub struct LocalSecondaryIndex {
     name: String,
     sort_key: KeyAttribute,
+    // read from inside a document rather than from the top level
+    sort_key_path: Option<AttributePath>,
     projection: Projection,
     table_schema: KeySchema,
     storage: IndexStorage<Item>,
@@ -27,6 +29,7 @@ impl LocalSecondaryIndex {
         Self {
             name: name.into(),
             sort_key,
+            sort_key_path: None,
             projection,
             table_schema,
             storage: IndexStorage::new(),
@@ -46,6 +49,10 @@ impl LocalSecondaryIndex {
         self.sort_key.key_type
     }
     #[inline]
+    pub fn sort_key_path(&self) -> Option<&AttributePath> {
+        self.sort_key_path.as_ref()
+    }
+    #[inline]
     pub fn projection(&self) -> &Projection {
         &self.projection
     }
@@ -65,6 +72,31 @@ impl LocalSecondaryIndex {
         self.storage.is_empty()
     }
 
+    pub fn size_bytes(&self) -> usize {
+        self.storage.values().map(|item| item.encoded_size()).sum()
+    }
+
+    pub fn describe(&self) -> IndexDescription {
+        IndexDescription {
+            name: self.name.clone(),
+            key_schema: self.schema(),
+            additional_partition_keys: Vec::new(),
+            additional_sort_keys: Vec::new(),
+            key_paths: self
+                .sort_key_path
+                .iter()
+                .map(|path| (self.sort_key.name.clone(), path.clone()))
+                .collect(),
+            multi_valued: None,
+            filter: None,
+            unique: false,
+            projection: self.projection.clone(),
+            item_count: self.len(),
+            size_bytes: self.size_bytes(),
+            status: IndexStatus::Active,
+        }
+    }
+
     pub fn put(&mut self, table_key: &PrimaryKey, item: &Item) -> Option<Item> {
         let lsi_sk = match self.extract_lsi_sort_key(item) {
             Some(sk) => sk,
@@ -75,13 +107,19 @@ impl LocalSecondaryIndex {
             }
         };
 
-        let storage_key = self.make_storage_key(&table_key.pk, &lsi_sk, table_key);
+        let index_key = IndexKey {
+            partition: vec![table_key.pk.clone()],
+            sort: vec![lsi_sk],
+        };
         let table_storage_key = table_key.to_storage_key();
-        let projected = self
+        let mut projected = self
             .projection
             .project_item(item, &self.table_schema, &self.schema());
+        if let Some(path) = &self.sort_key_path {
+            project_path(item, path, &mut projected);
+        }
 
-        self.storage.put(table_storage_key, storage_key, projected)
+        self.storage.put(table_storage_key, &index_key, projected)
     }
 
     pub fn delete(&mut self, table_key: &PrimaryKey) -> Option<Item> {
@@ -102,43 +140,57 @@ impl LocalSecondaryIndex {
         let executor = QueryExecutor::new(&schema);
         executor.validate_condition(&condition)?;
 
-        let items = self.storage.values().filter_map(|item| {
-            let pk = self.extract_pk_from_item(item)?;
-            let sk = self.extract_lsi_sort_key(item)?;
-            Some((PrimaryKey { pk, sk: Some(sk) }, item.clone()))
-        });
+        let items: Vec<Item> = self
+            .storage
+            .query(&condition, &options)
+            .into_iter()
+            .cloned()
+            .collect();
+
+        Ok(QueryResult {
+            count: items.len(),
+            scanned_count: items.len(),
+            items,
+        })
+    }
+
+    pub fn count(
+        &self,
+        condition: &KeyCondition,
+        filter: Option<&Condition>,
+    ) -> TableResult<QueryResult> {
+        let schema = self.schema();
+        let executor = QueryExecutor::new(&schema);
+        executor.validate_condition(condition)?;
+
+        let mut result = QueryResult::empty();
+        for item in self.storage.range(condition) {
+            result.scanned_count += 1;
+            if filter.is_none_or(|filter| evaluate(filter, item).unwrap_or(false)) {
+                result.count += 1;
+            }
+        }
 
-        executor.execute(items, &condition, &options)
+        Ok(result)
     }
 
     pub fn clear(&mut self) {
         self.storage.clear();
     }
 
-    fn extract_pk_from_item(&self, item: &Item) -> Option<KeyValue> {
-        let attr = item.get(self.table_schema.pk_name())?;
-        KeyValue::from_attribute_with_type(attr, self.table_schema.partition_key.key_type)
-    }
-
     fn extract_lsi_sort_key(&self, item: &Item) -> Option<KeyValue> {
-        let attr = item.get(&self.sort_key.name)?;
+        let attr = match &self.sort_key_path {
+            Some(path) => path.resolve(item)?,
+            None => item.get(&self.sort_key.name)?,
+        };
         KeyValue::from_attribute_with_type(attr, self.sort_key.key_type)
     }
-
-    #[inline]
-    fn make_storage_key(&self, pk: &KeyValue, lsi_sk: &KeyValue, table_key: &PrimaryKey) -> String {
-        format!(
-            "{}|{}|{}",
-            encode_key_component(pk),
-            encode_key_component(lsi_sk),
-            table_key.to_storage_key()
-        )
-    }
 }
 
 pub struct LsiBuilder {
     name: String,
     sort_key: KeyAttribute,
+    sort_key_path: Option<AttributePath>,
     projection: Projection,
 }
 
@@ -151,10 +203,18 @@ impl LsiBuilder {
         Self {
             name: name.into(),
             sort_key: KeyAttribute::new(sort_key_name, sort_key_type),
+            sort_key_path: None,
             projection: Projection::All,
         }
     }
 
+    // reads the sort key from `path`, e.g. `meta.created_at`, instead of the top level;
+    // queries still refer to it by name
+    pub fn sort_key_path(mut self, path: impl Into<AttributePath>) -> Self {
+        self.sort_key_path = Some(path.into());
+        self
+    }
+
     pub fn projection(mut self, projection: Projection) -> Self {
         self.projection = projection;
         self
@@ -175,7 +235,10 @@ impl LsiBuilder {
     }
 
     pub fn build(self, table_schema: KeySchema) -> LocalSecondaryIndex {
-        LocalSecondaryIndex::new(self.name, self.sort_key, self.projection, table_schema)
+        let mut lsi =
+            LocalSecondaryIndex::new(self.name, self.sort_key, self.projection, table_schema);
+        lsi.sort_key_path = self.sort_key_path;
+        lsi
     }
 }
 
@@ -246,4 +309,35 @@ mod tests {
         let result = lsi.query(KeyCondition::pk("user1")).unwrap();
         assert_eq!(result.count, 5);
     }
+
+    #[test]
+    fn nested_sort_key() {
+        use crate::types::AttributeValue;
+        use std::collections::BTreeMap;
+
+        let mut lsi = LsiBuilder::new("by-created", "created_at", KeyType::S)
+            .sort_key_path(AttributePath::new("meta").key("created_at"))
+            .keys_only()
+            .build(table_schema());
+
+        for (order, created) in [("order001", "2026-02-01"), ("order002", "2026-01-01")] {
+            let mut meta = BTreeMap::new();
+            meta.insert("created_at".to_string(), AttributeValue::S(created.into()));
+            meta.insert("author".to_string(), AttributeValue::S("ann".into()));
+            let item = sample_order("user1", order, "2026-03-01", 100)
+                .with("meta", AttributeValue::M(meta));
+            lsi.put(&PrimaryKey::composite("user1", order), &item);
+        }
+
+        let result = lsi
+            .query(KeyCondition::pk("user1").sk_lt("2026-01-15"))
+            .unwrap();
+        assert_eq!(result.count, 1);
+        let item = &result.items[0];
+        assert_eq!(item.get("order_id").unwrap().as_s(), Some("order002"));
+        let Some(AttributeValue::M(meta)) = item.get("meta") else {
+            panic!("expected a map");
+        };
+        assert_eq!(meta.len(), 1);
+    }
 }
@@ -1,9 +1,14 @@
+mod description;
 mod gsi;
 mod lsi;
 mod projection;
+mod registry;
 mod storage;
 
-pub use gsi::{GlobalSecondaryIndex, GsiBuilder};
+pub use description::{IndexDescription, IndexStatus};
+pub(crate) use gsi::GsiView;
+pub use gsi::{GlobalSecondaryIndex, GsiBuilder, MAX_KEY_ATTRIBUTES};
 pub use lsi::{LocalSecondaryIndex, LsiBuilder};
 pub use projection::Projection;
-pub use storage::IndexStorage;
+pub(crate) use registry::{KeyIndex, KeyIndexes};
+pub use storage::{IndexEntryKey, IndexKey, IndexStorage};
@@ -1,5 +1,6 @@
-use crate::types::{Item, KeySchema};
-use std::collections::HashSet;
+use crate::condition::{AttributePath, PathSegment};
+use crate::types::{AttributeValue, Item, KeySchema};
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub enum Projection {
@@ -67,6 +68,50 @@ impl Projection {
     }
 }
 
+// copies the value at `path` into `into`, keeping the maps around it. a list can't be
+// copied in part without shifting positions, so a path through one copies the whole list
+pub(crate) fn project_path(item: &Item, path: &AttributePath, into: &mut Item) {
+    let Some(root) = path.root() else {
+        return;
+    };
+    let Some(value) = item.get(root) else {
+        return;
+    };
+    let keys: Vec<&str> = path.segments()[1..]
+        .iter()
+        .map_while(|segment| match segment {
+            PathSegment::Key(key) => Some(key.as_str()),
+            PathSegment::Index(_) => None,
+        })
+        .collect();
+
+    if let Some(merged) = merge_path(value, &keys, into.get(root).cloned()) {
+        into.set(root, merged);
+    }
+}
+
+fn merge_path(
+    source: &AttributeValue,
+    keys: &[&str],
+    target: Option<AttributeValue>,
+) -> Option<AttributeValue> {
+    let Some((key, rest)) = keys.split_first() else {
+        return Some(source.clone());
+    };
+    let AttributeValue::M(source) = source else {
+        return None;
+    };
+
+    let mut target = match target {
+        Some(AttributeValue::M(map)) => map,
+        _ => BTreeMap::new(),
+    };
+    let existing = target.remove(*key);
+    let merged = merge_path(source.get(*key)?, rest, existing)?;
+    target.insert(key.to_string(), merged);
+    Some(AttributeValue::M(target))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,4 +177,38 @@ mod tests {
         assert!(projected.contains("amount"));
         assert!(!projected.contains("status"));
     }
+
+    #[test]
+    fn project_nested_path() {
+        let mut customer = BTreeMap::new();
+        customer.insert("id".to_string(), AttributeValue::S("c1".into()));
+        customer.insert("name".to_string(), AttributeValue::S("Ann".into()));
+        let item = test_item()
+            .with("customer", AttributeValue::M(customer))
+            .with(
+                "tags",
+                AttributeValue::L(vec![AttributeValue::S("a".into())]),
+            );
+
+        let mut projected = Item::new().with_s("pk", "user1");
+        project_path(
+            &item,
+            &AttributePath::new("customer").key("id"),
+            &mut projected,
+        );
+        project_path(&item, &AttributePath::new("tags").index(0), &mut projected);
+        project_path(
+            &item,
+            &AttributePath::new("customer").key("missing"),
+            &mut projected,
+        );
+
+        let AttributeValue::M(customer) = projected.get("customer").unwrap() else {
+            panic!("expected a map");
+        };
+        assert_eq!(customer.len(), 1);
+        assert_eq!(customer.get("id").unwrap().as_s(), Some("c1"));
+        assert_eq!(projected.get("tags"), item.get("tags"));
+        assert_eq!(projected.len(), 3);
+    }
 }
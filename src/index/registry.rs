@@ -0,0 +1,95 @@
+use std::any::{Any, TypeId};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::types::{Item, PrimaryKey};
+
+// an index kept in step with every write that stores table keys only; reads go back to the
+// table for the items
+pub(crate) trait KeyIndex: Any + Debug {
+    fn name(&self) -> &str;
+    fn put(&mut self, table_key: &PrimaryKey, item: &Item);
+    fn delete(&mut self, table_key: &PrimaryKey);
+    fn clear(&mut self);
+}
+
+// the key indexes of a table; each kind of index has its own names
+#[derive(Debug, Default)]
+pub(crate) struct KeyIndexes {
+    indexes: BTreeMap<(TypeId, String), Box<dyn KeyIndex>>,
+}
+
+impl KeyIndexes {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    // replaces any index of the same kind and name
+    pub(crate) fn insert<T: KeyIndex>(&mut self, index: T) {
+        let key = (TypeId::of::<T>(), index.name().to_string());
+        self.indexes.insert(key, Box::new(index));
+    }
+
+    pub(crate) fn remove<T: KeyIndex>(&mut self, name: &str) -> bool {
+        self.indexes
+            .remove(&(TypeId::of::<T>(), name.to_string()))
+            .is_some()
+    }
+
+    pub(crate) fn get<T: KeyIndex>(&self, name: &str) -> Option<&T> {
+        let index: &dyn Any = self
+            .indexes
+            .get(&(TypeId::of::<T>(), name.to_string()))?
+            .as_ref();
+        index.downcast_ref()
+    }
+
+    pub(crate) fn names<T: KeyIndex>(&self) -> impl Iterator<Item = &str> {
+        self.indexes
+            .keys()
+            .filter(|(kind, _)| *kind == TypeId::of::<T>())
+            .map(|(_, name)| name.as_str())
+    }
+
+    pub(crate) fn put(&mut self, table_key: &PrimaryKey, item: &Item) {
+        for index in self.indexes.values_mut() {
+            index.put(table_key, item);
+        }
+    }
+
+    pub(crate) fn delete(&mut self, table_key: &PrimaryKey) {
+        for index in self.indexes.values_mut() {
+            index.delete(table_key);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for index in self.indexes.values_mut() {
+            index.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::{GeoIndex, GeoIndexBuilder};
+    use crate::search::{TextIndex, TextIndexBuilder};
+
+    #[test]
+    fn names_are_per_kind() {
+        let mut indexes = KeyIndexes::new();
+        indexes.insert(TextIndexBuilder::new("places").attribute("name").build());
+        indexes.insert(GeoIndexBuilder::new("places").build().unwrap());
+        indexes.insert(TextIndexBuilder::new("places").attribute("city").build());
+
+        assert_eq!(indexes.names::<TextIndex>().collect::<Vec<_>>(), ["places"]);
+        assert_eq!(indexes.names::<GeoIndex>().collect::<Vec<_>>(), ["places"]);
+        let text: &TextIndex = indexes.get("places").unwrap();
+        assert_eq!(text.attributes()[0].to_string(), "city");
+
+        assert!(indexes.remove::<GeoIndex>("places"));
+        assert!(!indexes.remove::<GeoIndex>("places"));
+        assert!(indexes.get::<TextIndex>("places").is_some());
+    }
+}
@@ -1,40 +1,126 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::RangeBounds;
+
+use crate::query::{KeyCondition, QueryOptions, SortKeyOp};
+use crate::types::{KeyValue, PrimaryKey, encode_ordered_key};
+
+// an index's own key; each side has one value per key attribute, in schema order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexKey {
+    pub partition: Vec<KeyValue>,
+    pub sort: Vec<KeyValue>,
+}
+
+impl From<&PrimaryKey> for IndexKey {
+    fn from(key: &PrimaryKey) -> Self {
+        Self {
+            partition: vec![key.pk.clone()],
+            sort: key.sk.iter().cloned().collect(),
+        }
+    }
+}
+
+// entries sort by partition, then sort key attributes left to right, then base table key
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IndexEntryKey {
+    partition: Vec<u8>,
+    sort: Vec<u8>,
+    table_key: String,
+}
+
+impl IndexEntryKey {
+    pub fn new(index_key: &IndexKey, table_key: impl Into<String>) -> Self {
+        Self {
+            partition: encode_tuple(&index_key.partition),
+            sort: encode_tuple(&index_key.sort),
+            table_key: table_key.into(),
+        }
+    }
+
+    // true when both entries are for the same index key, whatever their table keys
+    pub fn same_index_key(&self, other: &Self) -> bool {
+        self.partition == other.partition && self.sort == other.sort
+    }
+
+    // sorts before every entry with this partition and sort key
+    fn lower(partition: &[u8], sort: Vec<u8>) -> Self {
+        Self {
+            partition: partition.to_vec(),
+            sort,
+            table_key: String::new(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct IndexStorage<V> {
-    /// primary data store: index_storage_key -> value
-    data: HashMap<String, V>,
-    /// reverse index: table_storage_key -> index_storage_key
-    reverse_index: HashMap<String, String>,
+    /// primary data store, ordered by index key
+    data: BTreeMap<IndexEntryKey, V>,
+    /// reverse index: table_storage_key -> index entry keys, more than one for multi-valued indexes
+    reverse_index: HashMap<String, Vec<IndexEntryKey>>,
 }
 
 impl<V> IndexStorage<V> {
     pub fn new() -> Self {
         Self {
-            data: HashMap::new(),
+            data: BTreeMap::new(),
             reverse_index: HashMap::new(),
         }
     }
 
-    pub fn put(&mut self, table_key: String, index_key: String, value: V) -> Option<V> {
+    pub fn put(&mut self, table_key: String, index_key: &IndexKey, value: V) -> Option<V> {
         let old = self.remove_by_table_key(&table_key);
 
-        self.reverse_index.insert(table_key, index_key.clone());
-        self.data.insert(index_key, value);
+        let entry_key = IndexEntryKey::new(index_key, table_key.clone());
+        self.reverse_index
+            .insert(table_key, vec![entry_key.clone()]);
+        self.data.insert(entry_key, value);
 
         old
     }
 
-    pub fn get(&self, index_key: &str) -> Option<&V> {
-        self.data.get(index_key)
+    // one entry per index key; entries the item already had are kept and only get the new value
+    pub fn put_many(&mut self, table_key: String, index_keys: &[IndexKey], value: V)
+    where
+        V: Clone,
+    {
+        let mut entry_keys: Vec<IndexEntryKey> = index_keys
+            .iter()
+            .map(|index_key| IndexEntryKey::new(index_key, table_key.clone()))
+            .collect();
+        entry_keys.sort();
+        entry_keys.dedup();
+        if entry_keys.is_empty() {
+            self.remove_by_table_key(&table_key);
+            return;
+        }
+
+        for old in self.reverse_index.remove(&table_key).unwrap_or_default() {
+            if entry_keys.binary_search(&old).is_err() {
+                self.data.remove(&old);
+            }
+        }
+        for entry_key in &entry_keys {
+            self.data.insert(entry_key.clone(), value.clone());
+        }
+        self.reverse_index.insert(table_key, entry_keys);
+    }
+
+    pub fn get(&self, table_key: &str) -> Option<&V> {
+        self.data.get(self.reverse_index.get(table_key)?.first()?)
     }
 
     pub fn remove_by_table_key(&mut self, table_key: &str) -> Option<V> {
-        if let Some(index_key) = self.reverse_index.remove(table_key) {
-            self.data.remove(&index_key)
-        } else {
-            None
+        let mut removed = None;
+        for entry_key in self.reverse_index.remove(table_key)? {
+            removed = self.data.remove(&entry_key).or(removed);
         }
+        removed
+    }
+
+    // how many entries this table item has
+    pub fn entry_count(&self, table_key: &str) -> usize {
+        self.reverse_index.get(table_key).map_or(0, Vec::len)
     }
 
     pub fn clear(&mut self) {
@@ -42,6 +128,206 @@ impl<V> IndexStorage<V> {
         self.reverse_index.clear();
     }
 
+    // entries matching the key condition in index order; only the matching range is visited
+    pub fn range(&self, condition: &KeyCondition) -> impl DoubleEndedIterator<Item = &V> {
+        self.entries_in(condition).map(|(_, v)| v)
+    }
+
+    // like `range`, but an item matched through several of its entries is returned once
+    pub fn range_distinct(&self, condition: &KeyCondition) -> impl Iterator<Item = &V> {
+        self.range_distinct_overlaid(condition, None)
+    }
+
+    pub(crate) fn range_overlaid<'a>(
+        &'a self,
+        condition: &KeyCondition,
+        overlay: Option<&'a Overlay<V>>,
+    ) -> impl Iterator<Item = &'a V> {
+        self.merged(Self::bounds(condition), true, overlay)
+            .map(|(_, v)| v)
+    }
+
+    pub(crate) fn range_distinct_overlaid<'a>(
+        &'a self,
+        condition: &KeyCondition,
+        overlay: Option<&'a Overlay<V>>,
+    ) -> impl Iterator<Item = &'a V> {
+        let mut seen = HashSet::new();
+        self.merged(Self::bounds(condition), true, overlay)
+            .filter_map(move |(key, v)| seen.insert(key.table_key.as_str()).then_some(v))
+    }
+
+    // every entry whose index key is exactly `index_key`
+    pub fn with_key(&self, index_key: &IndexKey) -> impl Iterator<Item = &V> {
+        let start = IndexEntryKey::new(index_key, String::new());
+        let mut end = start.clone();
+        end.sort.push(0);
+        self.data.range(start..end).map(|(_, v)| v)
+    }
+
+    // groups of entries that share an index key
+    pub fn duplicates(&self) -> Vec<Vec<&V>> {
+        let mut groups = Vec::new();
+        let mut current: Vec<(&IndexEntryKey, &V)> = Vec::new();
+        for (key, value) in &self.data {
+            if current
+                .last()
+                .is_some_and(|(last, _)| !last.same_index_key(key))
+            {
+                if current.len() > 1 {
+                    groups.push(current.iter().map(|(_, v)| *v).collect());
+                }
+                current.clear();
+            }
+            current.push((key, value));
+        }
+        if current.len() > 1 {
+            groups.push(current.iter().map(|(_, v)| *v).collect());
+        }
+        groups
+    }
+
+    pub fn query(&self, condition: &KeyCondition, options: &QueryOptions) -> Vec<&V> {
+        self.query_overlaid(condition, options, None)
+    }
+
+    // `query` for multi-valued indexes: the limit counts items, not entries
+    pub fn query_distinct(&self, condition: &KeyCondition, options: &QueryOptions) -> Vec<&V> {
+        self.query_distinct_overlaid(condition, options, None)
+    }
+
+    pub(crate) fn query_overlaid<'a>(
+        &'a self,
+        condition: &KeyCondition,
+        options: &QueryOptions,
+        overlay: Option<&'a Overlay<V>>,
+    ) -> Vec<&'a V> {
+        let limit = options.limit.unwrap_or(usize::MAX);
+        self.merged(Self::bounds(condition), options.scan_forward, overlay)
+            .map(|(_, v)| v)
+            .take(limit)
+            .collect()
+    }
+
+    pub(crate) fn query_distinct_overlaid<'a>(
+        &'a self,
+        condition: &KeyCondition,
+        options: &QueryOptions,
+        overlay: Option<&'a Overlay<V>>,
+    ) -> Vec<&'a V> {
+        let limit = options.limit.unwrap_or(usize::MAX);
+        let mut seen = HashSet::new();
+        self.merged(Self::bounds(condition), options.scan_forward, overlay)
+            .filter_map(|(key, v)| seen.insert(key.table_key.as_str()).then_some(v))
+            .take(limit)
+            .collect()
+    }
+
+    // every value in index order
+    pub(crate) fn values_overlaid<'a>(
+        &'a self,
+        overlay: Option<&'a Overlay<V>>,
+    ) -> impl Iterator<Item = &'a V> {
+        self.merged(.., true, overlay).map(|(_, v)| v)
+    }
+
+    fn entries_in(
+        &self,
+        condition: &KeyCondition,
+    ) -> impl DoubleEndedIterator<Item = (&IndexEntryKey, &V)> {
+        self.data.range(Self::bounds(condition))
+    }
+
+    // the entries in `range`, with the overlay's standing in for those of the table items it
+    // replaces; both sides are already in order, so they are merged as they are read
+    fn merged<'a, R>(
+        &'a self,
+        range: R,
+        forward: bool,
+        overlay: Option<&'a Overlay<V>>,
+    ) -> Box<dyn Iterator<Item = (&'a IndexEntryKey, &'a V)> + 'a>
+    where
+        R: RangeBounds<IndexEntryKey> + Clone + 'a,
+    {
+        let ordered = move |storage: &'a Self,
+                            range: R|
+              -> Box<dyn Iterator<Item = (&'a IndexEntryKey, &'a V)> + 'a> {
+            if forward {
+                Box::new(storage.data.range(range))
+            } else {
+                Box::new(storage.data.range(range).rev())
+            }
+        };
+        let Some(overlay) = overlay else {
+            return ordered(self, range);
+        };
+
+        let mut current = ordered(self, range.clone())
+            .filter(|(key, _)| !overlay.replaced.contains(&key.table_key))
+            .peekable();
+        let mut newer = ordered(&overlay.storage, range).peekable();
+        Box::new(std::iter::from_fn(move || {
+            let take_newer = match (current.peek(), newer.peek()) {
+                (Some((a, _)), Some((b, _))) => (b < a) == forward,
+                (None, _) => true,
+                (_, None) => false,
+            };
+            if take_newer {
+                newer.next()
+            } else {
+                current.next()
+            }
+        }))
+    }
+
+    fn bounds(condition: &KeyCondition) -> std::ops::Range<IndexEntryKey> {
+        let (start, mut end) = Self::bounds_of(condition);
+        if end < start {
+            end = start.clone();
+        }
+        start..end
+    }
+
+    fn bounds_of(condition: &KeyCondition) -> (IndexEntryKey, IndexEntryKey) {
+        let partition = encode_tuple(condition.partition_key_values());
+        let at = |sort: Vec<u8>| IndexEntryKey::lower(&partition, sort);
+        let mut next_partition = partition.clone();
+        next_partition.push(0);
+        let partition_end = IndexEntryKey::lower(&next_partition, Vec::new());
+
+        let prefix = encode_tuple(&condition.sort_key_equalities);
+        let with = |value: &KeyValue| {
+            let mut bytes = prefix.clone();
+            push_component(&mut bytes, value);
+            bytes
+        };
+        // every entry starting with `bytes` sorts before the returned bound
+        let past = |bytes: &[u8]| match prefix_end(bytes) {
+            Some(end) => at(end),
+            None => partition_end.clone(),
+        };
+
+        let Some(op) = &condition.sort_key else {
+            return (at(prefix.clone()), past(&prefix));
+        };
+        match op {
+            SortKeyOp::Eq(v) => (at(with(v)), past(&with(v))),
+            SortKeyOp::Lt(v) => (at(prefix.clone()), at(with(v))),
+            SortKeyOp::Le(v) => (at(prefix.clone()), past(&with(v))),
+            SortKeyOp::Gt(v) => (past(&with(v)), past(&prefix)),
+            SortKeyOp::Ge(v) => (at(with(v)), past(&prefix)),
+            SortKeyOp::Between { low, high } => (at(with(low)), past(&with(high))),
+            // numbers have no prefixes
+            SortKeyOp::BeginsWith(KeyValue::N(_)) => (at(prefix.clone()), at(prefix.clone())),
+            SortKeyOp::BeginsWith(value) => {
+                // unterminated, so longer values with this prefix also match
+                let mut bytes = prefix.clone();
+                escape_into(&mut bytes, &encode_ordered_key(value));
+                (at(bytes.clone()), past(&bytes))
+            }
+        }
+    }
+
     #[inline]
     pub fn values(&self) -> impl Iterator<Item = &V> {
         self.data.values()
@@ -50,12 +336,17 @@ impl<V> IndexStorage<V> {
     pub fn len(&self) -> usize {
         self.data.len()
     }
+    // distinct table items; differs from len for multi-valued indexes
+    #[inline]
+    pub fn item_count(&self) -> usize {
+        self.reverse_index.len()
+    }
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.data.is_empty()
     }
     #[inline]
-    pub fn iter(&self) -> impl Iterator<Item = (&String, &V)> {
+    pub fn iter(&self) -> impl Iterator<Item = (&IndexEntryKey, &V)> {
         self.data.iter()
     }
 
@@ -71,37 +362,98 @@ impl<V> Default for IndexStorage<V> {
     }
 }
 
+// newer entries for some table items, read in place of whatever the storage holds for them
+#[derive(Debug)]
+pub(crate) struct Overlay<V> {
+    storage: IndexStorage<V>,
+    replaced: HashSet<String>,
+}
+
+impl<V> Overlay<V> {
+    pub(crate) fn new() -> Self {
+        Self {
+            storage: IndexStorage::new(),
+            replaced: HashSet::new(),
+        }
+    }
+
+    // the entries `table_key` ends up with go into the returned storage
+    pub(crate) fn replace(&mut self, table_key: &str) -> &mut IndexStorage<V> {
+        self.replaced.insert(table_key.to_string());
+        &mut self.storage
+    }
+}
+
+// components are escaped and terminated so tuples compare attribute by attribute
+fn encode_tuple<'a>(values: impl IntoIterator<Item = &'a KeyValue>) -> Vec<u8> {
+    let mut out = Vec::new();
+    for value in values {
+        push_component(&mut out, value);
+    }
+    out
+}
+
+fn push_component(out: &mut Vec<u8>, value: &KeyValue) {
+    escape_into(out, &encode_ordered_key(value));
+    out.extend_from_slice(&[0x00, 0x01]);
+}
+
+fn escape_into(out: &mut Vec<u8>, bytes: &[u8]) {
+    for &b in bytes {
+        out.push(b);
+        if b == 0x00 {
+            out.push(0xFF);
+        }
+    }
+}
+
+// the smallest byte string greater than everything starting with `prefix`
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn key(pk: &str, sk: &str) -> IndexKey {
+        IndexKey::from(&PrimaryKey::composite(pk, sk))
+    }
+
     #[test]
     fn put_and_get() {
         let mut storage: IndexStorage<String> = IndexStorage::new();
-        storage.put("table_key_1".into(), "index_key_1".into(), "value1".into());
+        storage.put("table_key_1".into(), &key("a", "1"), "value1".into());
         assert_eq!(storage.len(), 1);
-        assert_eq!(storage.get("index_key_1"), Some(&"value1".to_string()));
+        assert_eq!(storage.get("table_key_1"), Some(&"value1".to_string()));
     }
 
     #[test]
     fn put_overwrite_returns_old() {
         let mut storage: IndexStorage<String> = IndexStorage::new();
 
-        storage.put("table_key_1".into(), "index_key_1".into(), "value1".into());
-        let old = storage.put("table_key_1".into(), "index_key_2".into(), "value2".into());
+        storage.put("table_key_1".into(), &key("a", "1"), "value1".into());
+        let old = storage.put("table_key_1".into(), &key("b", "2"), "value2".into());
 
         assert_eq!(old, Some("value1".to_string()));
         assert_eq!(storage.len(), 1);
-        assert_eq!(storage.get("index_key_1"), None);
-        assert_eq!(storage.get("index_key_2"), Some(&"value2".to_string()));
+        assert!(storage.range(&KeyCondition::pk("a")).next().is_none());
+        assert_eq!(storage.get("table_key_1"), Some(&"value2".to_string()));
     }
 
     #[test]
     fn remove_by_table_key() {
         let mut storage: IndexStorage<String> = IndexStorage::new();
 
-        storage.put("table_key_1".into(), "index_key_1".into(), "value1".into());
-        storage.put("table_key_2".into(), "index_key_2".into(), "value2".into());
+        storage.put("table_key_1".into(), &key("a", "1"), "value1".into());
+        storage.put("table_key_2".into(), &key("a", "2"), "value2".into());
         assert_eq!(storage.len(), 2);
 
         // remove nonexistent
@@ -115,11 +467,35 @@ mod tests {
         assert_eq!(removed, Some("value2".to_string()));
     }
 
+    #[test]
+    fn put_many_diffs_entries() {
+        let mut storage: IndexStorage<String> = IndexStorage::new();
+
+        storage.put_many(
+            "item".into(),
+            &[key("rust", ""), key("db", ""), key("rust", "")],
+            "v1".into(),
+        );
+        assert_eq!(storage.len(), 2);
+        assert_eq!(storage.item_count(), 1);
+        assert_eq!(storage.entry_count("item"), 2);
+
+        storage.put_many("item".into(), &[key("db", ""), key("web", "")], "v2".into());
+        assert_eq!(storage.entry_count("item"), 2);
+        assert_eq!(storage.with_key(&key("rust", "")).count(), 0);
+        let values: Vec<_> = storage.values().collect();
+        assert_eq!(values, ["v2", "v2"]);
+
+        storage.put_many("item".into(), &[], "v3".into());
+        assert!(storage.is_empty());
+        assert_eq!(storage.reverse_index_len(), 0);
+    }
+
     #[test]
     fn clear() {
         let mut storage: IndexStorage<String> = IndexStorage::new();
-        storage.put("t1".into(), "i1".into(), "v1".into());
-        storage.put("t2".into(), "i2".into(), "v2".into());
+        storage.put("t1".into(), &key("a", "1"), "v1".into());
+        storage.put("t2".into(), &key("a", "2"), "v2".into());
         assert_eq!(storage.len(), 2);
 
         storage.clear();
@@ -131,12 +507,183 @@ mod tests {
     #[test]
     fn values_iter() {
         let mut storage: IndexStorage<i32> = IndexStorage::new();
-        storage.put("t1".into(), "i1".into(), 1);
-        storage.put("t2".into(), "i2".into(), 2);
-        storage.put("t3".into(), "i3".into(), 3);
+        storage.put("t1".into(), &key("a", "1"), 1);
+        storage.put("t2".into(), &key("a", "2"), 2);
+        storage.put("t3".into(), &key("b", "3"), 3);
         assert_eq!(storage.len(), 3);
 
         let sum: i32 = storage.values().sum();
         assert_eq!(sum, 6);
     }
+
+    mod range {
+        use super::*;
+
+        fn storage() -> IndexStorage<String> {
+            let mut storage = IndexStorage::new();
+            for (pk, sk) in [
+                ("a", "apple"),
+                ("a", "apricot"),
+                ("a", "banana"),
+                ("a", "cherry"),
+                ("ab", "apple"),
+                ("b", "apple"),
+            ] {
+                storage.put(
+                    format!("{}/{}", pk, sk),
+                    &key(pk, sk),
+                    format!("{}/{}", pk, sk),
+                );
+            }
+            // same index key, ordered by table key
+            storage.put("z".into(), &key("a", "banana"), "a/banana#2".into());
+            storage
+        }
+
+        fn collect(storage: &IndexStorage<String>, condition: KeyCondition) -> Vec<&str> {
+            storage.range(&condition).map(String::as_str).collect()
+        }
+
+        #[test]
+        fn partition_only() {
+            let storage = storage();
+            assert_eq!(
+                collect(&storage, KeyCondition::pk("a")),
+                vec!["a/apple", "a/apricot", "a/banana", "a/banana#2", "a/cherry"]
+            );
+            assert_eq!(collect(&storage, KeyCondition::pk("ab")), vec!["ab/apple"]);
+            assert!(collect(&storage, KeyCondition::pk("c")).is_empty());
+        }
+
+        #[test]
+        fn sort_key_operators() {
+            let storage = storage();
+            let a = || KeyCondition::pk("a");
+            assert_eq!(
+                collect(&storage, a().sk_eq("banana")),
+                vec!["a/banana", "a/banana#2"]
+            );
+            assert_eq!(
+                collect(&storage, a().sk_lt("banana")),
+                vec!["a/apple", "a/apricot"]
+            );
+            assert_eq!(collect(&storage, a().sk_le("banana")).len(), 4);
+            assert_eq!(collect(&storage, a().sk_gt("banana")), vec!["a/cherry"]);
+            assert_eq!(collect(&storage, a().sk_ge("banana")).len(), 3);
+            assert_eq!(
+                collect(&storage, a().sk_begins_with("ap")),
+                vec!["a/apple", "a/apricot"]
+            );
+            assert_eq!(
+                collect(&storage, a().sk_between("apricot", "banana")),
+                vec!["a/apricot", "a/banana", "a/banana#2"]
+            );
+            assert!(collect(&storage, a().sk_between("z", "a")).is_empty());
+        }
+
+        #[test]
+        fn numeric_sort_keys() {
+            let mut storage = IndexStorage::new();
+            for n in [100, 9, -3, 25, 0] {
+                let k = PrimaryKey::composite("p", KeyValue::N(n.to_string()));
+                storage.put(n.to_string(), &IndexKey::from(&k), n);
+            }
+
+            let all = KeyCondition::pk("p");
+            let values: Vec<i32> = storage.range(&all).copied().collect();
+            assert_eq!(values, vec![-3, 0, 9, 25, 100]);
+
+            let reversed = storage.query(&all, &QueryOptions::new().reverse().with_limit(2));
+            assert_eq!(reversed, vec![&100, &25]);
+
+            let condition = KeyCondition::pk("p").sk_ge(KeyValue::N("9.0".into()));
+            let values: Vec<i32> = storage.range(&condition).copied().collect();
+            assert_eq!(values, vec![9, 25, 100]);
+        }
+
+        #[test]
+        fn exact_key_and_duplicates() {
+            let storage = storage();
+            let banana: Vec<&str> = storage
+                .with_key(&key("a", "banana"))
+                .map(String::as_str)
+                .collect();
+            assert_eq!(banana, vec!["a/banana", "a/banana#2"]);
+            assert_eq!(storage.with_key(&key("a", "ban")).count(), 0);
+
+            let duplicates = storage.duplicates();
+            assert_eq!(duplicates, vec![vec!["a/banana", "a/banana#2"]]);
+        }
+
+        #[test]
+        fn multi_attribute_keys() {
+            let mut storage = IndexStorage::new();
+            let entries = [
+                ("acme", "eu", "open", 3),
+                ("acme", "eu", "open", 10),
+                ("acme", "eu", "opened", 1),
+                ("acme", "eu", "closed", 5),
+                ("acme", "us", "open", 7),
+            ];
+            for (i, (tenant, region, status, n)) in entries.into_iter().enumerate() {
+                let key = IndexKey {
+                    partition: vec![tenant.into(), region.into()],
+                    sort: vec![status.into(), KeyValue::N(n.to_string())],
+                };
+                storage.put(i.to_string(), &key, i);
+            }
+
+            let eu = || KeyCondition::pk("acme").and_pk("eu");
+            let found = |condition: KeyCondition| -> Vec<usize> {
+                storage.range(&condition).copied().collect()
+            };
+
+            assert_eq!(found(eu()), vec![3, 0, 1, 2]);
+            assert_eq!(found(eu().sk_eq("open")), vec![0, 1]);
+            assert_eq!(found(eu().sk_begins_with("open")), vec![0, 1, 2]);
+            assert_eq!(found(eu().sk_gt("closed")), vec![0, 1, 2]);
+            assert_eq!(found(eu().sk_le("open")), vec![3, 0, 1]);
+            assert_eq!(
+                found(eu().and_sk_eq("open").sk_ge(KeyValue::N("5".into()))),
+                vec![1]
+            );
+            assert_eq!(
+                found(eu().and_sk_eq("open").sk_lt(KeyValue::N("5".into()))),
+                vec![0]
+            );
+            assert_eq!(found(KeyCondition::pk("acme").and_pk("us")), vec![4]);
+            assert!(found(KeyCondition::pk("acme")).is_empty());
+        }
+    }
+
+    #[test]
+    fn overlay_replaces_table_items() {
+        let mut storage: IndexStorage<&str> = IndexStorage::new();
+        for (table_key, sk, value) in [("t1", "1", "old 1"), ("t2", "2", "old 2"), ("t3", "3", "3")]
+        {
+            storage.put(table_key.into(), &key("a", sk), value);
+        }
+
+        let mut overlay = Overlay::new();
+        // t1 moves past t3, t2 goes away and t4 is new
+        overlay
+            .replace("t1")
+            .put("t1".into(), &key("a", "4"), "new 1");
+        overlay.replace("t2");
+        overlay.replace("t4").put("t4".into(), &key("a", "0"), "4");
+
+        let all = KeyCondition::pk("a");
+        let forward = storage.query_overlaid(&all, &QueryOptions::new(), Some(&overlay));
+        assert_eq!(forward, [&"4", &"3", &"new 1"]);
+        let reverse = storage.query_overlaid(
+            &all,
+            &QueryOptions::new().reverse().with_limit(2),
+            Some(&overlay),
+        );
+        assert_eq!(reverse, [&"new 1", &"3"]);
+        assert_eq!(storage.values_overlaid(Some(&overlay)).count(), 3);
+
+        // the storage itself is untouched
+        assert_eq!(storage.query(&all, &QueryOptions::new()).len(), 3);
+    }
 }
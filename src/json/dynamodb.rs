@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::error::JsonError;
+use super::parser::parse;
+use super::value::JsonValue;
+use crate::types::{AttributeValue, Item};
+use crate::utils::{base64_decode, base64_encode};
+
+pub fn attribute_to_json(value: &AttributeValue) -> JsonValue {
+    let (tag, inner) = match value {
+        AttributeValue::S(s) => ("S", JsonValue::String(s.clone())),
+        AttributeValue::N(n) => ("N", JsonValue::String(n.clone())),
+        AttributeValue::B(b) => ("B", JsonValue::String(base64_encode(b))),
+        AttributeValue::Bool(b) => ("BOOL", JsonValue::Bool(*b)),
+        AttributeValue::Null => ("NULL", JsonValue::Bool(true)),
+        AttributeValue::M(map) => ("M", map_to_json(map)),
+        AttributeValue::L(list) => (
+            "L",
+            JsonValue::Array(list.iter().map(attribute_to_json).collect()),
+        ),
+        AttributeValue::Ss(set) => ("SS", strings_to_json(set.iter().cloned())),
+        AttributeValue::Ns(set) => ("NS", strings_to_json(set.iter().cloned())),
+        AttributeValue::Bs(set) => ("BS", strings_to_json(set.iter().map(|b| base64_encode(b)))),
+    };
+    JsonValue::from([(tag, inner)])
+}
+
+pub fn attribute_from_json(value: &JsonValue) -> Result<AttributeValue, JsonError> {
+    let object = value.as_object().ok_or_else(|| {
+        JsonError::format(format!(
+            "expected attribute value object, found {}",
+            value.type_name()
+        ))
+    })?;
+    if object.len() != 1 {
+        return Err(JsonError::format(format!(
+            "attribute value must have exactly one type key, found {}",
+            object.len()
+        )));
+    }
+    let (tag, inner) = object.iter().next().unwrap();
+
+    match tag.as_str() {
+        "S" => Ok(AttributeValue::S(expect_string(tag, inner)?.to_string())),
+        "N" => Ok(AttributeValue::N(expect_number(tag, inner)?)),
+        "B" => decode_binary(expect_string(tag, inner)?).map(AttributeValue::B),
+        "BOOL" => inner
+            .as_bool()
+            .map(AttributeValue::Bool)
+            .ok_or_else(|| type_error(tag, "bool", inner)),
+        "NULL" => match inner {
+            JsonValue::Bool(true) => Ok(AttributeValue::Null),
+            _ => Err(JsonError::format("NULL must be true")),
+        },
+        "M" => {
+            let map = inner
+                .as_object()
+                .ok_or_else(|| type_error(tag, "object", inner))?;
+            map_from_json(map).map(AttributeValue::M)
+        }
+        "L" => {
+            let list = inner
+                .as_array()
+                .ok_or_else(|| type_error(tag, "array", inner))?;
+            list.iter()
+                .map(attribute_from_json)
+                .collect::<Result<_, _>>()
+                .map(AttributeValue::L)
+        }
+        "SS" => {
+            let set = expect_array(tag, inner)?
+                .iter()
+                .map(|v| expect_string(tag, v).map(str::to_string))
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            non_empty(tag, &set)?;
+            Ok(AttributeValue::Ss(set))
+        }
+        "NS" => {
+            let set = expect_array(tag, inner)?
+                .iter()
+                .map(|v| expect_number(tag, v))
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            non_empty(tag, &set)?;
+            Ok(AttributeValue::Ns(set))
+        }
+        "BS" => {
+            let set = expect_array(tag, inner)?
+                .iter()
+                .map(|v| decode_binary(expect_string(tag, v)?))
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            non_empty(tag, &set)?;
+            Ok(AttributeValue::Bs(set))
+        }
+        other => Err(JsonError::format(format!(
+            "unknown attribute type: {}",
+            other
+        ))),
+    }
+}
+
+pub fn item_to_json(item: &Item) -> JsonValue {
+    JsonValue::Object(
+        item.iter()
+            .map(|(name, value)| (name.to_string(), attribute_to_json(value)))
+            .collect(),
+    )
+}
+
+pub fn item_from_json(value: &JsonValue) -> Result<Item, JsonError> {
+    let object = value.as_object().ok_or_else(|| {
+        JsonError::format(format!("expected item object, found {}", value.type_name()))
+    })?;
+
+    map_from_json(object).map(Item::from)
+}
+
+pub fn item_to_dynamodb_json(item: &Item) -> String {
+    item_to_json(item).to_string()
+}
+
+pub fn item_from_dynamodb_json(input: &str) -> Result<Item, JsonError> {
+    item_from_json(&parse(input)?)
+}
+
+fn map_to_json(map: &BTreeMap<String, AttributeValue>) -> JsonValue {
+    JsonValue::Object(
+        map.iter()
+            .map(|(name, value)| (name.clone(), attribute_to_json(value)))
+            .collect(),
+    )
+}
+
+fn map_from_json(
+    map: &BTreeMap<String, JsonValue>,
+) -> Result<BTreeMap<String, AttributeValue>, JsonError> {
+    map.iter()
+        .map(|(name, value)| {
+            attribute_from_json(value)
+                .map(|v| (name.clone(), v))
+                .map_err(|e| JsonError::format(format!("{}: {}", name, e)))
+        })
+        .collect()
+}
+
+fn strings_to_json(values: impl Iterator<Item = String>) -> JsonValue {
+    JsonValue::Array(values.map(JsonValue::String).collect())
+}
+
+fn type_error(tag: &str, expected: &str, found: &JsonValue) -> JsonError {
+    JsonError::format(format!(
+        "{} expects {}, found {}",
+        tag,
+        expected,
+        found.type_name()
+    ))
+}
+
+fn expect_string<'a>(tag: &str, value: &'a JsonValue) -> Result<&'a str, JsonError> {
+    value
+        .as_str()
+        .ok_or_else(|| type_error(tag, "string", value))
+}
+
+fn expect_array<'a>(tag: &str, value: &'a JsonValue) -> Result<&'a Vec<JsonValue>, JsonError> {
+    value
+        .as_array()
+        .ok_or_else(|| type_error(tag, "array", value))
+}
+
+// plain json numbers are accepted as well as the usual string form
+fn expect_number(tag: &str, value: &JsonValue) -> Result<String, JsonError> {
+    if let JsonValue::Number(n) = value {
+        return Ok(n.clone());
+    }
+    let s = expect_string(tag, value)?;
+    match parse(s) {
+        Ok(JsonValue::Number(n)) => Ok(n),
+        _ => Err(JsonError::format(format!("invalid number: {}", s))),
+    }
+}
+
+fn non_empty<T>(tag: &str, set: &BTreeSet<T>) -> Result<(), JsonError> {
+    if set.is_empty() {
+        return Err(JsonError::format(format!("{} must not be empty", tag)));
+    }
+    Ok(())
+}
+
+fn decode_binary(s: &str) -> Result<Vec<u8>, JsonError> {
+    base64_decode(s).ok_or_else(|| JsonError::format(format!("invalid base64: {}", s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_item() -> Item {
+        let mut nested = BTreeMap::new();
+        nested.insert("city".to_string(), AttributeValue::S("Paris".into()));
+
+        Item::new()
+            .with_s("pk", "user#1")
+            .with_n("age", 42)
+            .with_b("avatar", vec![0u8, 1, 2, 255])
+            .with_bool("active", true)
+            .with_null("deleted")
+            .with_m("address", nested)
+            .with_l(
+                "tags",
+                vec![AttributeValue::S("a".into()), AttributeValue::N("1".into())],
+            )
+            .with(
+                "colors",
+                AttributeValue::Ss(["red".to_string(), "blue".to_string()].into()),
+            )
+            .with(
+                "scores",
+                AttributeValue::Ns(["1.5".to_string(), "-3".to_string()].into()),
+            )
+            .with("blobs", AttributeValue::Bs([vec![1u8], vec![2, 3]].into()))
+    }
+
+    #[test]
+    fn roundtrip_all_types() {
+        let item = sample_item();
+        let json = item_to_dynamodb_json(&item);
+        assert_eq!(item_from_dynamodb_json(&json).unwrap(), item);
+    }
+
+    #[test]
+    fn encodes_dynamodb_shapes() {
+        let item = Item::new()
+            .with_n("n", 5)
+            .with_b("b", b"hi".to_vec())
+            .with_null("z");
+        assert_eq!(
+            item_to_dynamodb_json(&item),
+            r#"{"b":{"B":"aGk="},"n":{"N":"5"},"z":{"NULL":true}}"#
+        );
+    }
+
+    #[test]
+    fn accepts_bare_numbers() {
+        let item = item_from_dynamodb_json(
+            r#"{"pk": {"S": "a"}, "n": {"N": 7}, "ns": {"NS": [1, "2.5"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(item.get("pk"), Some(&AttributeValue::S("a".into())));
+        assert_eq!(item.get("n"), Some(&AttributeValue::N("7".into())));
+        assert_eq!(
+            item.get("ns"),
+            Some(&AttributeValue::Ns(
+                ["1".to_string(), "2.5".to_string()].into()
+            ))
+        );
+    }
+
+    #[test]
+    fn single_map_attribute_named_item() {
+        let item = item_from_dynamodb_json(r#"{"Item": {"M": {"a": {"S": "x"}}}}"#).unwrap();
+        assert_eq!(item.len(), 1);
+        assert!(item.get("Item").unwrap().as_m().is_some());
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        assert!(item_from_dynamodb_json(r#"{"a": {"X": "1"}}"#).is_err());
+        assert!(item_from_dynamodb_json(r#"{"a": {"S": 1}}"#).is_err());
+        assert!(item_from_dynamodb_json(r#"{"a": {"N": "abc"}}"#).is_err());
+        assert!(item_from_dynamodb_json(r#"{"a": {"B": "***"}}"#).is_err());
+        assert!(item_from_dynamodb_json(r#"{"a": {"S": "x", "N": "1"}}"#).is_err());
+        assert!(item_from_dynamodb_json(r#"{"a": "plain"}"#).is_err());
+
+        for tag in ["SS", "NS", "BS"] {
+            let err = item_from_dynamodb_json(&format!(r#"{{"a": {{"{}": []}}}}"#, tag));
+            assert_eq!(
+                err.unwrap_err().to_string(),
+                format!("a: {} must not be empty", tag)
+            );
+        }
+        let err = item_from_dynamodb_json(r#"{"a": {"S": "x"}, "a": {"S": "y"}}"#).unwrap_err();
+        assert_eq!(err.position(), Some(18));
+
+        let err = item_from_dynamodb_json(r#"{"a": {"M": {"b": {"BOOL": 1}}}}"#).unwrap_err();
+        assert_eq!(err.to_string(), "a: b: BOOL expects bool, found number");
+    }
+}
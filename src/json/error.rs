@@ -0,0 +1,41 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    Syntax { position: usize, message: String },
+    // well-formed json that doesn't have the expected shape
+    Format(String),
+}
+
+impl JsonError {
+    pub fn syntax(position: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            position,
+            message: message.into(),
+        }
+    }
+
+    pub fn format(message: impl Into<String>) -> Self {
+        Self::Format(message.into())
+    }
+
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            Self::Syntax { position, .. } => Some(*position),
+            Self::Format(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { position, message } => {
+                write!(f, "invalid json at position {}: {}", position, message)
+            }
+            Self::Format(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
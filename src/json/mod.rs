@@ -0,0 +1,14 @@
+mod dynamodb;
+mod error;
+mod parser;
+mod plain;
+mod value;
+
+pub use dynamodb::{
+    attribute_from_json, attribute_to_json, item_from_dynamodb_json, item_from_json,
+    item_to_dynamodb_json, item_to_json,
+};
+pub use error::JsonError;
+pub use parser::parse;
+pub use plain::{attribute_from_plain_json, attribute_to_plain_json};
+pub use value::JsonValue;
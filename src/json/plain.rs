@@ -0,0 +1,51 @@
+use super::value::JsonValue;
+use crate::types::AttributeValue;
+use crate::utils::base64_encode;
+
+// ordinary json documents, as opposed to the typed dynamodb format
+pub fn attribute_from_plain_json(value: &JsonValue) -> AttributeValue {
+    match value {
+        JsonValue::Null => AttributeValue::Null,
+        JsonValue::Bool(b) => AttributeValue::Bool(*b),
+        JsonValue::Number(n) => AttributeValue::N(n.clone()),
+        JsonValue::String(s) => AttributeValue::S(s.clone()),
+        JsonValue::Array(items) => {
+            AttributeValue::L(items.iter().map(attribute_from_plain_json).collect())
+        }
+        JsonValue::Object(map) => AttributeValue::M(
+            map.iter()
+                .map(|(k, v)| (k.clone(), attribute_from_plain_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+// sets become arrays and binary becomes base64, so this is lossy
+pub fn attribute_to_plain_json(value: &AttributeValue) -> JsonValue {
+    match value {
+        AttributeValue::S(s) => JsonValue::String(s.clone()),
+        AttributeValue::N(n) => JsonValue::Number(n.clone()),
+        AttributeValue::B(b) => JsonValue::String(base64_encode(b)),
+        AttributeValue::Bool(b) => JsonValue::Bool(*b),
+        AttributeValue::Null => JsonValue::Null,
+        AttributeValue::M(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), attribute_to_plain_json(v)))
+                .collect(),
+        ),
+        AttributeValue::L(list) => {
+            JsonValue::Array(list.iter().map(attribute_to_plain_json).collect())
+        }
+        AttributeValue::Ss(set) => {
+            JsonValue::Array(set.iter().cloned().map(JsonValue::String).collect())
+        }
+        AttributeValue::Ns(set) => {
+            JsonValue::Array(set.iter().cloned().map(JsonValue::Number).collect())
+        }
+        AttributeValue::Bs(set) => JsonValue::Array(
+            set.iter()
+                .map(|b| JsonValue::String(base64_encode(b)))
+                .collect(),
+        ),
+    }
+}
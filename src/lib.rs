@@ -1,39 +1,66 @@
+pub mod aggregate;
 pub mod batch;
 pub mod condition;
+pub mod csv;
+pub mod entity;
 pub mod error;
+pub mod expression;
+pub mod geo;
 pub mod index;
+pub mod json;
+pub mod partiql;
 pub mod query;
+pub mod search;
 pub mod storage;
+mod syntax;
 pub mod table;
 pub mod transaction;
 pub mod types;
 pub mod update;
 pub mod utils;
+pub mod vector;
 
+pub use aggregate::{Aggregate, AggregateGroup, AggregateResult, AggregateValue, Aggregation};
 pub use batch::{
     BatchExecutor, BatchGetRequest, BatchGetResult, BatchWriteItem, BatchWriteRequest,
     BatchWriteResult,
 };
+pub use csv::{ColumnType, CsvError, CsvMapping};
+pub use entity::{Entity, EntityDecode, GsiKeyTemplate, KeyTemplate, Repository};
 pub use error::{StorageError, StorageResult, TableError, TableResult, TransactionCancelReason};
-pub use index::{GlobalSecondaryIndex, GsiBuilder, LocalSecondaryIndex, LsiBuilder, Projection};
-pub use query::{KeyCondition, QueryOptions, QueryResult, SortKeyOp};
+pub use expression::ExpressionError;
+pub use geo::{BoundingBox, GeoHit, GeoIndex, GeoIndexBuilder, GeoPoint};
+pub use index::{
+    GlobalSecondaryIndex, GsiBuilder, IndexDescription, IndexStatus, LocalSecondaryIndex,
+    LsiBuilder, Projection,
+};
+pub use json::{JsonError, JsonValue, item_from_dynamodb_json, item_to_dynamodb_json};
+pub use partiql::{PartiqlError, Plan, Statement};
+pub use query::{KeyCondition, QueryOptions, QueryResult, Select, SortKeyOp};
+pub use search::{
+    SearchCursor, SearchHit, SearchQuery, SearchRequest, SearchResult, SimpleTokenizer, TextIndex,
+    TextIndexBuilder, Tokenizer,
+};
 pub use storage::{MemoryStorage, Storage, StorageExt};
 pub use table::{
-    DeleteRequest, GetRequest, PutRequest, QueryRequest, ScanRequest, Table, TableBuilder,
-    UpdateRequest,
+    Clock, DeleteRequest, EventualConsistency, GetRequest, ImportError, ImportResult, ManualClock,
+    PutRequest, QueryIter, QueryRequest, ScanRequest, SystemClock, Table, TableBuilder,
+    TableDescription, UpdateRequest,
 };
 pub use transaction::{
     TransactGetItem, TransactGetRequest, TransactGetResult, TransactWriteItem,
     TransactWriteRequest, TransactionExecutor, TransactionFailureReason,
 };
 pub use types::{
-    AttributeValue, DecodeError, Item, KeyAttribute, KeySchema, KeyType, KeyValidationError,
-    KeyValue, PrimaryKey, ReturnValue, WriteResult, encode_key_component,
+    AttributeValue, ConversionError, DecodeError, FromAttributeValue, Item, KeyAttribute,
+    KeySchema, KeyType, KeyValidationError, KeyValue, PrimaryKey, ReturnValue, ToAttributeValue,
+    WriteResult, encode_key_component,
 };
 pub use update::{UpdateAction, UpdateExecutor, UpdateExpression};
 pub use utils::{
     add_numeric_strings, compare_key_values, compare_numeric_strings, escape_key_chars,
     numbers_equal,
 };
+pub use vector::{HnswConfig, Metric, VectorHit, VectorIndex, VectorIndexBuilder, pack_vector};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,83 @@
+use crate::condition::{AttributePath, CompareOp};
+use crate::types::AttributeValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Literal(AttributeValue),
+    // `?` placeholders, numbered from 0 in order of appearance
+    Parameter(usize),
+    Map(Vec<(String, Value)>),
+    List(Vec<Value>),
+    // << ... >>; becomes a string, number or binary set once bound
+    Set(Vec<Value>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Compare {
+        path: AttributePath,
+        op: CompareOp,
+        value: Value,
+    },
+    Between {
+        path: AttributePath,
+        low: Value,
+        high: Value,
+    },
+    In {
+        path: AttributePath,
+        values: Vec<Value>,
+    },
+    BeginsWith {
+        path: AttributePath,
+        prefix: Value,
+    },
+    Contains {
+        path: AttributePath,
+        operand: Value,
+    },
+    // `attribute_exists(path)` or `path IS NOT MISSING`
+    Exists(AttributePath),
+    // `attribute_not_exists(path)` or `path IS MISSING`
+    Missing(AttributePath),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Select {
+        table: String,
+        // FROM table."index"
+        index: Option<String>,
+        // `None` for SELECT *
+        projection: Option<Vec<AttributePath>>,
+        filter: Option<Expr>,
+    },
+    Insert {
+        table: String,
+        value: Value,
+    },
+    Update {
+        table: String,
+        set: Vec<(AttributePath, Value)>,
+        remove: Vec<AttributePath>,
+        filter: Option<Expr>,
+    },
+    Delete {
+        table: String,
+        filter: Option<Expr>,
+    },
+}
+
+impl Statement {
+    pub fn table(&self) -> &str {
+        match self {
+            Self::Select { table, .. }
+            | Self::Insert { table, .. }
+            | Self::Update { table, .. }
+            | Self::Delete { table, .. } => table,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+
+use crate::syntax::SyntaxError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartiqlError {
+    // byte offset into the statement
+    Syntax { position: usize, message: String },
+    // well-formed statement that can't run against this table
+    Plan(String),
+}
+
+impl PartiqlError {
+    pub fn syntax(position: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            position,
+            message: message.into(),
+        }
+    }
+
+    pub fn plan(message: impl Into<String>) -> Self {
+        Self::Plan(message.into())
+    }
+
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            Self::Syntax { position, .. } => Some(*position),
+            Self::Plan(_) => None,
+        }
+    }
+}
+
+impl From<SyntaxError> for PartiqlError {
+    fn from(error: SyntaxError) -> Self {
+        Self::syntax(error.position, error.message)
+    }
+}
+
+impl fmt::Display for PartiqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { position, message } => {
+                write!(f, "invalid statement at position {}: {}", position, message)
+            }
+            Self::Plan(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for PartiqlError {}
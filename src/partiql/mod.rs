@@ -0,0 +1,9 @@
+mod ast;
+mod error;
+mod parser;
+mod planner;
+
+pub use ast::{Expr, Statement, Value};
+pub use error::PartiqlError;
+pub use planner::Plan;
+pub(crate) use planner::{KeyLayout, plan};
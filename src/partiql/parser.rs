@@ -0,0 +1,478 @@
+use crate::condition::{AttributePath, CompareOp};
+use crate::syntax::{Dialect, Token, Tokens};
+use crate::types::AttributeValue;
+
+use super::ast::{Expr, Statement, Value};
+use super::error::PartiqlError;
+
+// nesting limit for literals and parenthesized conditions
+const MAX_DEPTH: usize = 64;
+
+impl Statement {
+    pub fn parse(input: &str) -> Result<Self, PartiqlError> {
+        let mut parser = Parser {
+            tokens: Tokens::new(input, Dialect::Partiql)?,
+            params: 0,
+        };
+        let statement = parser.statement()?;
+        parser.tokens.expect_end()?;
+        Ok(statement)
+    }
+}
+
+struct Parser {
+    tokens: Tokens,
+    params: usize,
+}
+
+impl Parser {
+    fn statement(&mut self) -> Result<Statement, PartiqlError> {
+        if self.tokens.accept_keyword("SELECT") {
+            self.select()
+        } else if self.tokens.accept_keyword("INSERT") {
+            self.insert()
+        } else if self.tokens.accept_keyword("UPDATE") {
+            self.update()
+        } else if self.tokens.accept_keyword("DELETE") {
+            self.delete()
+        } else {
+            Err(self
+                .tokens
+                .unexpected("SELECT, INSERT, UPDATE or DELETE")
+                .into())
+        }
+    }
+
+    fn select(&mut self) -> Result<Statement, PartiqlError> {
+        let projection = if self.tokens.accept(&Token::Star) {
+            None
+        } else {
+            let mut paths = vec![self.path()?];
+            while self.tokens.accept(&Token::Comma) {
+                paths.push(self.path()?);
+            }
+            Some(paths)
+        };
+        self.tokens.expect_keyword("FROM")?;
+        let table = self.name()?;
+        let index = if self.tokens.accept(&Token::Dot) {
+            Some(self.name()?)
+        } else {
+            None
+        };
+        let filter = self.where_clause()?;
+        Ok(Statement::Select {
+            table,
+            index,
+            projection,
+            filter,
+        })
+    }
+
+    fn insert(&mut self) -> Result<Statement, PartiqlError> {
+        self.tokens.expect_keyword("INTO")?;
+        let table = self.name()?;
+        self.tokens.expect_keyword("VALUE")?;
+        if *self.tokens.peek() != Token::LBrace {
+            return Err(self.tokens.unexpected("an item in braces").into());
+        }
+        let value = self.value(0)?;
+        Ok(Statement::Insert { table, value })
+    }
+
+    fn update(&mut self) -> Result<Statement, PartiqlError> {
+        let table = self.name()?;
+        let mut set = Vec::new();
+        let mut remove = Vec::new();
+        // clauses may repeat, as in `SET a = 1 SET b = 2 REMOVE c`
+        loop {
+            if self.tokens.accept_keyword("SET") {
+                loop {
+                    let path = self.path()?;
+                    self.tokens.expect(Token::Compare(CompareOp::Eq), "'='")?;
+                    set.push((path, self.value(0)?));
+                    if !self.tokens.accept(&Token::Comma) {
+                        break;
+                    }
+                }
+            } else if self.tokens.accept_keyword("REMOVE") {
+                loop {
+                    remove.push(self.path()?);
+                    if !self.tokens.accept(&Token::Comma) {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+        if set.is_empty() && remove.is_empty() {
+            return Err(self.tokens.unexpected("SET or REMOVE").into());
+        }
+        let filter = self.where_clause()?;
+        Ok(Statement::Update {
+            table,
+            set,
+            remove,
+            filter,
+        })
+    }
+
+    fn delete(&mut self) -> Result<Statement, PartiqlError> {
+        self.tokens.expect_keyword("FROM")?;
+        let table = self.name()?;
+        let filter = self.where_clause()?;
+        Ok(Statement::Delete { table, filter })
+    }
+
+    fn where_clause(&mut self) -> Result<Option<Expr>, PartiqlError> {
+        if self.tokens.accept_keyword("WHERE") {
+            self.or(0).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn name(&mut self) -> Result<String, PartiqlError> {
+        name(&mut self.tokens)
+    }
+
+    fn path(&mut self) -> Result<AttributePath, PartiqlError> {
+        self.tokens.path(name)
+    }
+
+    fn or(&mut self, depth: usize) -> Result<Expr, PartiqlError> {
+        let mut expr = self.and(depth)?;
+        while self.tokens.accept_keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and(depth)?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self, depth: usize) -> Result<Expr, PartiqlError> {
+        let mut expr = self.not(depth)?;
+        while self.tokens.accept_keyword("AND") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not(depth)?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self, depth: usize) -> Result<Expr, PartiqlError> {
+        if self.tokens.accept_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.not(depth + 1)?)));
+        }
+        self.predicate(depth)
+    }
+
+    fn predicate(&mut self, depth: usize) -> Result<Expr, PartiqlError> {
+        if depth >= MAX_DEPTH {
+            return Err(PartiqlError::syntax(
+                self.tokens.position(),
+                "nested too deeply",
+            ));
+        }
+        if self.tokens.accept(&Token::LParen) {
+            let expr = self.or(depth + 1)?;
+            self.tokens.expect(Token::RParen, "')'")?;
+            return Ok(expr);
+        }
+
+        // functions are bare names followed by a parenthesis
+        if let Token::Ident(name) = self.tokens.peek().clone()
+            && self.tokens.peek_second() == &Token::LParen
+        {
+            return self.function(&name);
+        }
+
+        let path = self.path()?;
+        if let Token::Compare(op) = self.tokens.peek().clone() {
+            self.tokens.advance();
+            let value = self.value(0)?;
+            return Ok(Expr::Compare { path, op, value });
+        }
+        if self.tokens.accept_keyword("BETWEEN") {
+            let low = self.value(0)?;
+            self.tokens.expect_keyword("AND")?;
+            let high = self.value(0)?;
+            return Ok(Expr::Between { path, low, high });
+        }
+        if self.tokens.accept_keyword("IN") {
+            let close = if self.tokens.accept(&Token::LBracket) {
+                Token::RBracket
+            } else {
+                self.tokens.expect(Token::LParen, "'(' or '['")?;
+                Token::RParen
+            };
+            let mut values = vec![self.value(0)?];
+            while self.tokens.accept(&Token::Comma) {
+                values.push(self.value(0)?);
+            }
+            self.tokens.expect(close, "the end of the IN list")?;
+            return Ok(Expr::In { path, values });
+        }
+        if self.tokens.accept_keyword("IS") {
+            let negated = self.tokens.accept_keyword("NOT");
+            self.tokens.expect_keyword("MISSING")?;
+            return Ok(if negated {
+                Expr::Exists(path)
+            } else {
+                Expr::Missing(path)
+            });
+        }
+        Err(self.tokens.unexpected("a comparison").into())
+    }
+
+    fn function(&mut self, name: &str) -> Result<Expr, PartiqlError> {
+        let start = self.tokens.position();
+        self.tokens.advance();
+        self.tokens.expect(Token::LParen, "'('")?;
+        let path = self.path()?;
+        let expr = match name.to_ascii_lowercase().as_str() {
+            "begins_with" => {
+                self.tokens.expect(Token::Comma, "','")?;
+                Expr::BeginsWith {
+                    path,
+                    prefix: self.value(0)?,
+                }
+            }
+            "contains" => {
+                self.tokens.expect(Token::Comma, "','")?;
+                Expr::Contains {
+                    path,
+                    operand: self.value(0)?,
+                }
+            }
+            "attribute_exists" => Expr::Exists(path),
+            "attribute_not_exists" => Expr::Missing(path),
+            _ => {
+                return Err(PartiqlError::syntax(
+                    start,
+                    format!("unknown function '{}'", name),
+                ));
+            }
+        };
+        self.tokens.expect(Token::RParen, "')'")?;
+        Ok(expr)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, PartiqlError> {
+        if depth >= MAX_DEPTH {
+            return Err(PartiqlError::syntax(
+                self.tokens.position(),
+                "nested too deeply",
+            ));
+        }
+        let value = match self.tokens.peek().clone() {
+            Token::Param => {
+                self.params += 1;
+                Value::Parameter(self.params - 1)
+            }
+            Token::Str(s) => Value::Literal(AttributeValue::S(s)),
+            Token::Number(n) => Value::Literal(AttributeValue::N(n)),
+            Token::Ident(word) if word.eq_ignore_ascii_case("TRUE") => {
+                Value::Literal(AttributeValue::Bool(true))
+            }
+            Token::Ident(word) if word.eq_ignore_ascii_case("FALSE") => {
+                Value::Literal(AttributeValue::Bool(false))
+            }
+            Token::Ident(word) if word.eq_ignore_ascii_case("NULL") => {
+                Value::Literal(AttributeValue::Null)
+            }
+            Token::LBrace => {
+                self.tokens.advance();
+                let mut entries = Vec::new();
+                if !self.tokens.accept(&Token::RBrace) {
+                    loop {
+                        let key = match self.tokens.peek().clone() {
+                            Token::Str(key) | Token::Quoted(key) => key,
+                            _ => {
+                                return Err(self
+                                    .tokens
+                                    .unexpected("a quoted attribute name")
+                                    .into());
+                            }
+                        };
+                        self.tokens.advance();
+                        self.tokens.expect(Token::Colon, "':'")?;
+                        entries.push((key, self.value(depth + 1)?));
+                        if !self.tokens.accept(&Token::Comma) {
+                            break;
+                        }
+                    }
+                    self.tokens.expect(Token::RBrace, "'}'")?;
+                }
+                return Ok(Value::Map(entries));
+            }
+            Token::LBracket => {
+                self.tokens.advance();
+                let values = self.values_until(Token::RBracket, "']'", depth)?;
+                return Ok(Value::List(values));
+            }
+            Token::LBag => {
+                self.tokens.advance();
+                let values = self.values_until(Token::RBag, "'>>'", depth)?;
+                return Ok(Value::Set(values));
+            }
+            _ => return Err(self.tokens.unexpected("a value").into()),
+        };
+        self.tokens.advance();
+        Ok(value)
+    }
+
+    fn values_until(
+        &mut self,
+        close: Token,
+        what: &str,
+        depth: usize,
+    ) -> Result<Vec<Value>, PartiqlError> {
+        let mut values = Vec::new();
+        if self.tokens.accept(&close) {
+            return Ok(values);
+        }
+        loop {
+            values.push(self.value(depth + 1)?);
+            if !self.tokens.accept(&Token::Comma) {
+                break;
+            }
+        }
+        self.tokens.expect(close, what)?;
+        Ok(values)
+    }
+}
+
+// plain or "quoted"
+fn name(tokens: &mut Tokens) -> Result<String, PartiqlError> {
+    match tokens.peek().clone() {
+        Token::Ident(name) | Token::Quoted(name) => {
+            tokens.advance();
+            Ok(name)
+        }
+        _ => Err(tokens.unexpected("a name").into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(value: &str) -> Value {
+        Value::Literal(AttributeValue::S(value.into()))
+    }
+
+    #[test]
+    fn select_with_key_range() {
+        let statement =
+            Statement::parse("SELECT * FROM orders WHERE user_id = ? AND order_id BETWEEN ? AND ?")
+                .unwrap();
+        let Statement::Select {
+            table,
+            index,
+            projection,
+            filter: Some(Expr::And(left, right)),
+        } = statement
+        else {
+            panic!("unexpected {:?}", statement);
+        };
+        assert_eq!((table.as_str(), index, projection), ("orders", None, None));
+        assert_eq!(
+            *left,
+            Expr::Compare {
+                path: AttributePath::new("user_id"),
+                op: CompareOp::Eq,
+                value: Value::Parameter(0),
+            }
+        );
+        assert_eq!(
+            *right,
+            Expr::Between {
+                path: AttributePath::new("order_id"),
+                low: Value::Parameter(1),
+                high: Value::Parameter(2),
+            }
+        );
+    }
+
+    #[test]
+    fn select_from_index_with_projection() {
+        let statement = Statement::parse(
+            r#"select total, "items"[0].sku FROM "orders"."by_status" WHERE status IN ('a', 'b') OR NOT attribute_exists(x)"#,
+        )
+        .unwrap();
+        let Statement::Select {
+            index,
+            projection,
+            filter,
+            ..
+        } = statement
+        else {
+            panic!();
+        };
+        assert_eq!(index.as_deref(), Some("by_status"));
+        assert_eq!(
+            projection.unwrap(),
+            [
+                AttributePath::new("total"),
+                AttributePath::new("items").index(0).key("sku"),
+            ]
+        );
+        assert!(matches!(filter, Some(Expr::Or(_, ref not)) if matches!(**not, Expr::Not(_))));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let Statement::Delete {
+            filter: Some(filter),
+            ..
+        } = Statement::parse("DELETE FROM t WHERE a = 1 OR b = 2 AND c IS MISSING").unwrap()
+        else {
+            panic!();
+        };
+        let Expr::Or(_, right) = filter else {
+            panic!("{:?}", filter);
+        };
+        assert!(matches!(*right, Expr::And(_, ref c) if matches!(**c, Expr::Missing(_))));
+    }
+
+    #[test]
+    fn insert_and_update_values() {
+        let statement = Statement::parse(
+            "INSERT INTO t VALUE {'id': ?, 'tags': <<'x', 'y'>>, 'meta': {'n': [1, true, null]}}",
+        )
+        .unwrap();
+        let Statement::Insert {
+            value: Value::Map(entries),
+            ..
+        } = statement
+        else {
+            panic!();
+        };
+        assert_eq!(entries[0], ("id".to_string(), Value::Parameter(0)));
+        assert_eq!(entries[1].1, Value::Set(vec![s("x"), s("y")]));
+
+        let statement =
+            Statement::parse("UPDATE t SET a = 'x', b.c = ? REMOVE d SET e = 1 WHERE id = ?")
+                .unwrap();
+        let Statement::Update { set, remove, .. } = statement else {
+            panic!();
+        };
+        assert_eq!(set.len(), 3);
+        assert_eq!(set[1].1, Value::Parameter(0));
+        assert_eq!(remove, [AttributePath::new("d")]);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let err = Statement::parse("SELECT * FROM t WHERE a = ").unwrap_err();
+        assert_eq!(
+            err,
+            PartiqlError::syntax(26, "expected a value, found the end of the statement")
+        );
+        let err = Statement::parse("SELECT * t").unwrap_err();
+        assert_eq!(err.position(), Some(9));
+        let err = Statement::parse("DELETE FROM t WHERE frob(a)").unwrap_err();
+        assert_eq!(err, PartiqlError::syntax(20, "unknown function 'frob'"));
+        assert!(Statement::parse("UPDATE t WHERE a = 1").is_err());
+        assert!(Statement::parse("INSERT INTO t VALUE 'x'").is_err());
+        assert!(Statement::parse("SELECT * FROM t extra").is_err());
+    }
+}
@@ -0,0 +1,550 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::condition::{AttributePath, CompareOp, Condition};
+use crate::query::{KeyCondition, Select, SortKeyOp};
+use crate::types::{AttributeValue, Item, KeyType, KeyValue, PrimaryKey};
+use crate::update::UpdateExpression;
+
+use super::ast::{Expr, Statement, Value};
+use super::error::PartiqlError;
+
+// what a statement turns into, with every parameter bound
+#[derive(Debug, Clone)]
+pub enum Plan {
+    Query {
+        index: Option<String>,
+        key_condition: KeyCondition,
+        filter: Option<Condition>,
+        select: Select,
+    },
+    Scan {
+        index: Option<String>,
+        filter: Option<Condition>,
+        select: Select,
+    },
+    // fails if the item already exists
+    Insert {
+        item: Item,
+    },
+    // fails unless the item exists and `condition` holds
+    Update {
+        key: PrimaryKey,
+        expression: UpdateExpression,
+        condition: Condition,
+    },
+    Delete {
+        key: PrimaryKey,
+        condition: Option<Condition>,
+    },
+}
+
+// key attributes of a table or index, in key order
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KeyLayout {
+    pub(crate) partition: Vec<(AttributePath, KeyType)>,
+    pub(crate) sort: Vec<(AttributePath, KeyType)>,
+}
+
+pub(crate) fn plan(
+    statement: Statement,
+    params: &[AttributeValue],
+    table_keys: &KeyLayout,
+    index_keys: Option<&KeyLayout>,
+) -> Result<Plan, PartiqlError> {
+    let mut binder = Binder { params, used: 0 };
+    let plan = match statement {
+        Statement::Select {
+            index,
+            projection,
+            filter,
+            ..
+        } => {
+            let select = match projection {
+                Some(paths) => Select::SpecificAttributes(paths),
+                None => Select::AllAttributes,
+            };
+            let conjuncts = filter.map(split_conjuncts).unwrap_or_default();
+            let layout = index_keys.unwrap_or(table_keys);
+            let mut rest = conjuncts.clone();
+            match binder.key_condition(layout, &mut rest)? {
+                Some(key_condition) => Plan::Query {
+                    index,
+                    key_condition,
+                    filter: binder.conjunction(rest)?,
+                    select,
+                },
+                None => Plan::Scan {
+                    index,
+                    filter: binder.conjunction(conjuncts)?,
+                    select,
+                },
+            }
+        }
+        Statement::Insert { value, .. } => match binder.bind(&value)? {
+            AttributeValue::M(map) => Plan::Insert {
+                item: Item::from(map),
+            },
+            _ => return Err(PartiqlError::plan("INSERT needs an item")),
+        },
+        Statement::Update {
+            set,
+            remove,
+            filter,
+            ..
+        } => {
+            let mut rest = filter.map(split_conjuncts).unwrap_or_default();
+            let key = binder.primary_key(table_keys, &mut rest, "UPDATE")?;
+
+            let key_paths: Vec<&AttributePath> = table_keys
+                .partition
+                .iter()
+                .chain(&table_keys.sort)
+                .map(|(path, _)| path)
+                .collect();
+            let mut expression = UpdateExpression::new();
+            for (path, value) in &set {
+                if key_paths.contains(&path) {
+                    return Err(PartiqlError::plan(format!(
+                        "key attribute '{}' can't be updated",
+                        path
+                    )));
+                }
+                expression = expression.set(path.clone(), binder.bind(value)?);
+            }
+            for path in remove {
+                if key_paths.contains(&&path) {
+                    return Err(PartiqlError::plan(format!(
+                        "key attribute '{}' can't be removed",
+                        path
+                    )));
+                }
+                expression = expression.remove(path);
+            }
+
+            let exists = Condition::attr_exists(table_keys.partition[0].0.clone());
+            let condition = match binder.conjunction(rest)? {
+                Some(condition) => exists.and(condition),
+                None => exists,
+            };
+            Plan::Update {
+                key,
+                expression,
+                condition,
+            }
+        }
+        Statement::Delete { filter, .. } => {
+            let mut rest = filter.map(split_conjuncts).unwrap_or_default();
+            let key = binder.primary_key(table_keys, &mut rest, "DELETE")?;
+            Plan::Delete {
+                key,
+                condition: binder.conjunction(rest)?,
+            }
+        }
+    };
+
+    if binder.used != params.len() {
+        return Err(PartiqlError::plan(format!(
+            "statement has {} parameter(s), got {}",
+            binder.used,
+            params.len()
+        )));
+    }
+    Ok(plan)
+}
+
+fn split_conjuncts(expr: Expr) -> Vec<Expr> {
+    match expr {
+        Expr::And(left, right) => {
+            let mut conjuncts = split_conjuncts(*left);
+            conjuncts.extend(split_conjuncts(*right));
+            conjuncts
+        }
+        other => vec![other],
+    }
+}
+
+// removes the first `path = value` conjunct
+fn take_equality(conjuncts: &mut Vec<Expr>, key: &AttributePath) -> Option<Value> {
+    let i = conjuncts.iter().position(
+        |expr| matches!(expr, Expr::Compare { path, op: CompareOp::Eq, .. } if path == key),
+    )?;
+    match conjuncts.remove(i) {
+        Expr::Compare { value, .. } => Some(value),
+        _ => None,
+    }
+}
+
+// removes the first conjunct a sort key condition can express
+fn take_range(conjuncts: &mut Vec<Expr>, key: &AttributePath) -> Option<Expr> {
+    let i = conjuncts.iter().position(|expr| match expr {
+        Expr::Compare { path, op, .. } => path == key && *op != CompareOp::Ne,
+        Expr::Between { path, .. } | Expr::BeginsWith { path, .. } => path == key,
+        _ => false,
+    })?;
+    Some(conjuncts.remove(i))
+}
+
+struct Binder<'a> {
+    params: &'a [AttributeValue],
+    // one past the highest parameter bound so far
+    used: usize,
+}
+
+impl Binder<'_> {
+    fn bind(&mut self, value: &Value) -> Result<AttributeValue, PartiqlError> {
+        Ok(match value {
+            Value::Literal(value) => value.clone(),
+            Value::Parameter(i) => {
+                self.used = self.used.max(i + 1);
+                self.params.get(*i).cloned().ok_or_else(|| {
+                    PartiqlError::plan(format!(
+                        "statement uses parameter {}, got {}",
+                        i + 1,
+                        self.params.len()
+                    ))
+                })?
+            }
+            Value::Map(entries) => {
+                let mut map = BTreeMap::new();
+                for (key, value) in entries {
+                    if map.insert(key.clone(), self.bind(value)?).is_some() {
+                        return Err(PartiqlError::plan(format!(
+                            "attribute '{}' appears twice",
+                            key
+                        )));
+                    }
+                }
+                AttributeValue::M(map)
+            }
+            Value::List(values) => AttributeValue::L(
+                values
+                    .iter()
+                    .map(|value| self.bind(value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Set(values) => {
+                let values: Vec<AttributeValue> = values
+                    .iter()
+                    .map(|value| self.bind(value))
+                    .collect::<Result<_, _>>()?;
+                set_of(values)?
+            }
+        })
+    }
+
+    fn key_value(
+        &mut self,
+        value: &Value,
+        path: &AttributePath,
+        key_type: KeyType,
+    ) -> Result<KeyValue, PartiqlError> {
+        let value = self.bind(value)?;
+        KeyValue::from_attribute_with_type(&value, key_type).ok_or_else(|| {
+            PartiqlError::plan(format!(
+                "key attribute '{}' is {:?}, got {}",
+                path,
+                key_type,
+                value.type_name()
+            ))
+        })
+    }
+
+    // `None` unless every partition attribute is pinned by an equality; consumes what it uses
+    fn key_condition(
+        &mut self,
+        layout: &KeyLayout,
+        conjuncts: &mut Vec<Expr>,
+    ) -> Result<Option<KeyCondition>, PartiqlError> {
+        let mut partition = Vec::new();
+        for (path, key_type) in &layout.partition {
+            let Some(value) = take_equality(conjuncts, path) else {
+                return Ok(None);
+            };
+            partition.push(self.key_value(&value, path, *key_type)?);
+        }
+        let mut partition = partition.into_iter();
+        let Some(first) = partition.next() else {
+            return Ok(None);
+        };
+        let mut condition = KeyCondition::pk(first);
+        for value in partition {
+            condition = condition.and_pk(value);
+        }
+
+        // leading sort attributes need equalities; the last one used can take any range
+        for (i, (path, key_type)) in layout.sort.iter().enumerate() {
+            let last = i + 1 == layout.sort.len();
+            if !last && let Some(value) = take_equality(conjuncts, path) {
+                condition = condition.and_sk_eq(self.key_value(&value, path, *key_type)?);
+                continue;
+            }
+            if let Some(range) = take_range(conjuncts, path) {
+                condition = condition.sk(self.sort_key_op(range, *key_type)?);
+            }
+            break;
+        }
+        Ok(Some(condition))
+    }
+
+    fn sort_key_op(&mut self, range: Expr, key_type: KeyType) -> Result<SortKeyOp, PartiqlError> {
+        Ok(match range {
+            Expr::Compare { path, op, value } => {
+                let value = self.key_value(&value, &path, key_type)?;
+                match op {
+                    CompareOp::Eq => SortKeyOp::Eq(value),
+                    CompareOp::Lt => SortKeyOp::Lt(value),
+                    CompareOp::Le => SortKeyOp::Le(value),
+                    CompareOp::Gt => SortKeyOp::Gt(value),
+                    CompareOp::Ge => SortKeyOp::Ge(value),
+                    CompareOp::Ne => unreachable!("<> is never a key range"),
+                }
+            }
+            Expr::Between { path, low, high } => SortKeyOp::Between {
+                low: self.key_value(&low, &path, key_type)?,
+                high: self.key_value(&high, &path, key_type)?,
+            },
+            Expr::BeginsWith { path, prefix } => {
+                SortKeyOp::BeginsWith(self.key_value(&prefix, &path, key_type)?)
+            }
+            _ => unreachable!("take_range only returns key ranges"),
+        })
+    }
+
+    fn primary_key(
+        &mut self,
+        layout: &KeyLayout,
+        conjuncts: &mut Vec<Expr>,
+        verb: &str,
+    ) -> Result<PrimaryKey, PartiqlError> {
+        let mut values = Vec::new();
+        for (path, key_type) in layout.partition.iter().chain(&layout.sort) {
+            let value = take_equality(conjuncts, path).ok_or_else(|| {
+                PartiqlError::plan(format!(
+                    "{} needs an equality on key attribute '{}'",
+                    verb, path
+                ))
+            })?;
+            values.push(self.key_value(&value, path, *key_type)?);
+        }
+        let mut values = values.into_iter();
+        let pk = values
+            .next()
+            .ok_or_else(|| PartiqlError::plan("table has no key attributes"))?;
+        Ok(PrimaryKey {
+            pk,
+            sk: values.next(),
+        })
+    }
+
+    fn conjunction(&mut self, conjuncts: Vec<Expr>) -> Result<Option<Condition>, PartiqlError> {
+        let mut conjunction: Option<Condition> = None;
+        for expr in conjuncts {
+            let condition = self.condition(expr)?;
+            conjunction = Some(match conjunction {
+                Some(left) => left.and(condition),
+                None => condition,
+            });
+        }
+        Ok(conjunction)
+    }
+
+    fn condition(&mut self, expr: Expr) -> Result<Condition, PartiqlError> {
+        Ok(match expr {
+            Expr::Compare { path, op, value } => Condition::Compare {
+                path,
+                op,
+                value: self.bind(&value)?,
+            },
+            Expr::Between { path, low, high } => Condition::Between {
+                path,
+                low: self.bind(&low)?,
+                high: self.bind(&high)?,
+            },
+            Expr::In { path, values } => {
+                let mut alternatives = Vec::with_capacity(values.len());
+                for value in &values {
+                    alternatives.push(Condition::eq(path.clone(), self.bind(value)?));
+                }
+                alternatives
+                    .into_iter()
+                    .reduce(Condition::or)
+                    .ok_or_else(|| PartiqlError::plan("IN needs at least one value"))?
+            }
+            Expr::BeginsWith { path, prefix } => Condition::BeginsWith {
+                path,
+                prefix: self.bind(&prefix)?,
+            },
+            Expr::Contains { path, operand } => Condition::Contains {
+                path,
+                operand: self.bind(&operand)?,
+            },
+            Expr::Exists(path) => Condition::AttributeExists(path),
+            Expr::Missing(path) => Condition::AttributeNotExists(path),
+            Expr::And(left, right) => self.condition(*left)?.and(self.condition(*right)?),
+            Expr::Or(left, right) => self.condition(*left)?.or(self.condition(*right)?),
+            Expr::Not(inner) => self.condition(*inner)?.not(),
+        })
+    }
+}
+
+fn set_of(values: Vec<AttributeValue>) -> Result<AttributeValue, PartiqlError> {
+    let mixed = || PartiqlError::plan("set elements must all be strings, numbers or binaries");
+    match values.first() {
+        None => Err(PartiqlError::plan("sets can't be empty")),
+        Some(AttributeValue::S(_)) => values
+            .into_iter()
+            .map(|v| match v {
+                AttributeValue::S(s) => Ok(s),
+                _ => Err(mixed()),
+            })
+            .collect::<Result<BTreeSet<_>, _>>()
+            .map(AttributeValue::Ss),
+        Some(AttributeValue::N(_)) => values
+            .into_iter()
+            .map(|v| match v {
+                AttributeValue::N(n) => Ok(n),
+                _ => Err(mixed()),
+            })
+            .collect::<Result<BTreeSet<_>, _>>()
+            .map(AttributeValue::Ns),
+        Some(AttributeValue::B(_)) => values
+            .into_iter()
+            .map(|v| match v {
+                AttributeValue::B(b) => Ok(b),
+                _ => Err(mixed()),
+            })
+            .collect::<Result<BTreeSet<_>, _>>()
+            .map(AttributeValue::Bs),
+        Some(_) => Err(mixed()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orders() -> KeyLayout {
+        KeyLayout {
+            partition: vec![(AttributePath::new("user_id"), KeyType::S)],
+            sort: vec![(AttributePath::new("order_id"), KeyType::N)],
+        }
+    }
+
+    fn plan_for(sql: &str, params: &[AttributeValue]) -> Result<Plan, PartiqlError> {
+        plan(Statement::parse(sql).unwrap(), params, &orders(), None)
+    }
+
+    fn s(value: &str) -> AttributeValue {
+        AttributeValue::S(value.into())
+    }
+
+    fn n(value: &str) -> AttributeValue {
+        AttributeValue::N(value.into())
+    }
+
+    #[test]
+    fn key_equality_becomes_a_query() {
+        let plan = plan_for(
+            "SELECT * FROM orders WHERE order_id BETWEEN ? AND ? AND user_id = ? AND total > 5",
+            &[n("1"), n("9"), s("u1")],
+        )
+        .unwrap();
+        let Plan::Query {
+            key_condition,
+            filter,
+            ..
+        } = plan
+        else {
+            panic!("{:?}", plan);
+        };
+        assert_eq!(key_condition.partition_key, KeyValue::S("u1".into()));
+        assert_eq!(
+            key_condition.sort_key,
+            Some(SortKeyOp::between(
+                KeyValue::N("1".into()),
+                KeyValue::N("9".into())
+            ))
+        );
+        assert!(matches!(
+            filter,
+            Some(Condition::Compare {
+                op: CompareOp::Gt,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn anything_else_is_a_scan() {
+        for sql in [
+            "SELECT * FROM orders WHERE total > 5",
+            "SELECT * FROM orders WHERE user_id = 'a' OR user_id = 'b'",
+            "SELECT * FROM orders WHERE user_id <> 'a'",
+        ] {
+            assert!(
+                matches!(
+                    plan_for(sql, &[]).unwrap(),
+                    Plan::Scan {
+                        filter: Some(_),
+                        ..
+                    }
+                ),
+                "{}",
+                sql
+            );
+        }
+        assert!(matches!(
+            plan_for("SELECT * FROM orders", &[]).unwrap(),
+            Plan::Scan { filter: None, .. }
+        ));
+    }
+
+    #[test]
+    fn writes_need_the_whole_key() {
+        let plan = plan_for(
+            "UPDATE orders SET status = ? WHERE user_id = 'u1' AND order_id = 3 AND status = 'new'",
+            &[s("paid")],
+        )
+        .unwrap();
+        let Plan::Update { key, condition, .. } = plan else {
+            panic!();
+        };
+        assert_eq!(key, PrimaryKey::composite("u1", KeyValue::N("3".into())));
+        assert!(matches!(condition, Condition::And(_, _)));
+
+        let err = plan_for("DELETE FROM orders WHERE user_id = 'u1'", &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "DELETE needs an equality on key attribute 'order_id'"
+        );
+        assert!(
+            plan_for(
+                "UPDATE orders SET user_id = 'x' WHERE user_id = 'u1' AND order_id = 1",
+                &[]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parameters_and_types_are_checked() {
+        let err = plan_for("SELECT * FROM orders WHERE user_id = ?", &[]).unwrap_err();
+        assert_eq!(err.to_string(), "statement uses parameter 1, got 0");
+        let err = plan_for("SELECT * FROM orders WHERE user_id = 'a'", &[s("x")]).unwrap_err();
+        assert_eq!(err.to_string(), "statement has 0 parameter(s), got 1");
+        let err = plan_for("SELECT * FROM orders WHERE user_id = 1", &[]).unwrap_err();
+        assert_eq!(err.to_string(), "key attribute 'user_id' is S, got N");
+
+        let plan = plan_for(
+            "INSERT INTO orders VALUE {'user_id': ?, 'order_id': 1, 'tags': <<'a', 'b'>>}",
+            &[s("u1")],
+        )
+        .unwrap();
+        let Plan::Insert { item } = plan else {
+            panic!();
+        };
+        assert_eq!(item.get("user_id"), Some(&s("u1")));
+        assert!(matches!(item.get("tags"), Some(AttributeValue::Ss(tags)) if tags.len() == 2));
+        assert!(plan_for("INSERT INTO orders VALUE {'tags': <<'a', 1>>}", &[]).is_err());
+    }
+}
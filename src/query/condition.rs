@@ -1,4 +1,4 @@
-use crate::types::KeyValue;
+use crate::types::{KeyValue, PrimaryKey};
 use std::cmp::Ordering;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -102,6 +102,10 @@ fn key_begins_with(value: &KeyValue, prefix: &KeyValue) -> bool {
 pub struct KeyCondition {
     pub partition_key: KeyValue,
     pub sort_key: Option<SortKeyOp>,
+    // multi-attribute index keys: values for the partition attributes after the first, and
+    // equality on the leading sort attributes; `sort_key` applies to the attribute after those
+    pub additional_partition_keys: Vec<KeyValue>,
+    pub sort_key_equalities: Vec<KeyValue>,
 }
 
 impl KeyCondition {
@@ -109,14 +113,28 @@ impl KeyCondition {
         Self {
             partition_key: pk.into(),
             sort_key: None,
+            additional_partition_keys: Vec::new(),
+            sort_key_equalities: Vec::new(),
         }
     }
 
     pub fn pk_sk(pk: impl Into<KeyValue>, sk_op: SortKeyOp) -> Self {
-        Self {
-            partition_key: pk.into(),
-            sort_key: Some(sk_op),
-        }
+        Self::pk(pk).sk(sk_op)
+    }
+
+    pub fn and_pk(mut self, value: impl Into<KeyValue>) -> Self {
+        self.additional_partition_keys.push(value.into());
+        self
+    }
+
+    pub fn and_sk_eq(mut self, value: impl Into<KeyValue>) -> Self {
+        self.sort_key_equalities.push(value.into());
+        self
+    }
+
+    pub fn sk(mut self, op: SortKeyOp) -> Self {
+        self.sort_key = Some(op);
+        self
     }
 
     pub fn sk_eq(mut self, value: impl Into<KeyValue>) -> Self {
@@ -147,6 +165,26 @@ impl KeyCondition {
         self.sort_key = Some(SortKeyOp::begins_with(prefix));
         self
     }
+
+    pub fn partition_key_values(&self) -> impl Iterator<Item = &KeyValue> {
+        std::iter::once(&self.partition_key).chain(&self.additional_partition_keys)
+    }
+
+    pub fn is_multi_attribute(&self) -> bool {
+        !self.additional_partition_keys.is_empty() || !self.sort_key_equalities.is_empty()
+    }
+
+    // single-attribute keys only; multi-attribute conditions are resolved by the index
+    pub fn matches(&self, key: &PrimaryKey) -> bool {
+        if key.pk != self.partition_key {
+            return false;
+        }
+        match (&self.sort_key, &key.sk) {
+            (None, _) => true,
+            (Some(op), Some(sk)) => op.matches(sk),
+            (Some(_), None) => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -213,6 +251,7 @@ mod tests {
         }
 
         #[test]
+        #[allow(clippy::useless_conversion)]
         fn begins_with_binary() {
             let op = SortKeyOp::begins_with(vec![0x04, 0x05]);
             assert!(op.matches(&KeyValue::B(vec![0x04, 0x05].into())));
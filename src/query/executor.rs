@@ -1,11 +1,14 @@
+use crate::condition::{Condition, evaluate};
 use crate::error::{TableError, TableResult};
 use crate::types::{Item, KeySchema, KeyValidationError, KeyValue, PrimaryKey};
 use crate::utils::compare_key_values;
 
 use super::condition::KeyCondition;
 
+use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
 
 #[derive(Debug, Clone)]
 pub struct QueryResult {
@@ -54,6 +57,21 @@ impl QueryOptions {
     }
 }
 
+// position of an item in query order; resuming after it continues the query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct QueryCursor {
+    sk: Option<KeyValue>,
+    storage_key: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct QueryPage {
+    pub(crate) items: Vec<Item>,
+    pub(crate) scanned_count: usize,
+    // set when the page is full and more items may follow
+    pub(crate) last: Option<QueryCursor>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct SortableItem {
     sk: Option<KeyValue>,
@@ -72,6 +90,31 @@ impl SortableItem {
             item,
         }
     }
+
+    fn cmp_cursor(&self, cursor: &QueryCursor) -> Ordering {
+        compare_positions(&self.sk, &self.storage_key, &cursor.sk, &cursor.storage_key)
+    }
+
+    fn cursor(&self) -> QueryCursor {
+        QueryCursor {
+            sk: self.sk.clone(),
+            storage_key: self.storage_key.clone(),
+        }
+    }
+}
+
+fn compare_positions(
+    a_sk: &Option<KeyValue>,
+    a_key: &str,
+    b_sk: &Option<KeyValue>,
+    b_key: &str,
+) -> Ordering {
+    match (a_sk, b_sk) {
+        (Some(a), Some(b)) => compare_key_values(a, b).then_with(|| a_key.cmp(b_key)),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a_key.cmp(b_key),
+    }
 }
 
 impl PartialOrd for SortableItem {
@@ -82,24 +125,8 @@ impl PartialOrd for SortableItem {
 
 impl Ord for SortableItem {
     fn cmp(&self, other: &Self) -> Ordering {
-        match (&self.sk, &other.sk) {
-            (Some(a), Some(b)) => {
-                let key_cmp = compare_key_values(a, b);
-                if key_cmp == Ordering::Equal {
-                    self.storage_key
-                        .cmp(&other.storage_key)
-                        .then(self.sequence.cmp(&other.sequence))
-                } else {
-                    key_cmp
-                }
-            }
-            (Some(_), None) => Ordering::Greater,
-            (None, Some(_)) => Ordering::Less,
-            (None, None) => self
-                .storage_key
-                .cmp(&other.storage_key)
-                .then(self.sequence.cmp(&other.sequence)),
-        }
+        compare_positions(&self.sk, &self.storage_key, &other.sk, &other.storage_key)
+            .then(self.sequence.cmp(&other.sequence))
     }
 }
 
@@ -112,33 +139,34 @@ impl<'a> QueryExecutor<'a> {
         Self { schema }
     }
 
-    /// TODO(performance): use a bounded heap for ascneding queries with limit
+    // with a limit only that many matches are kept, in a bounded heap
     pub fn execute(
         &self,
         items: impl Iterator<Item = (PrimaryKey, Item)>,
         condition: &KeyCondition,
         options: &QueryOptions,
     ) -> TableResult<QueryResult> {
+        if let Some(limit) = options.limit {
+            let page = self.execute_page(items, condition, options.scan_forward, None, limit);
+            return Ok(QueryResult {
+                count: page.items.len(),
+                items: page.items,
+                scanned_count: page.scanned_count,
+            });
+        }
+
         let mut scanned = 0usize;
         let mut sequence = 0usize;
 
-        let _ = options.limit.unwrap_or(64).min(1024);
         let mut matching: BTreeMap<SortableItem, ()> = BTreeMap::new();
 
         for (pk, item) in items {
             scanned += 1;
 
-            if pk.pk != condition.partition_key {
+            if !condition.matches(&pk) {
                 continue;
             }
 
-            if let Some(sk_op) = &condition.sort_key {
-                match &pk.sk {
-                    Some(sk) if sk_op.matches(sk) => {}
-                    _ => continue,
-                }
-            }
-
             let sortable = SortableItem::new(&pk, item, sequence);
             sequence += 1;
             matching.insert(sortable, ());
@@ -155,6 +183,100 @@ impl<'a> QueryExecutor<'a> {
         })
     }
 
+    // the next `page_size` matches after `after`, in query order; memory is bounded by the
+    // page size rather than the number of matches
+    pub(crate) fn execute_page(
+        &self,
+        items: impl Iterator<Item = (PrimaryKey, Item)>,
+        condition: &KeyCondition,
+        scan_forward: bool,
+        after: Option<&QueryCursor>,
+        page_size: usize,
+    ) -> QueryPage {
+        let mut scanned = 0usize;
+        let mut sequence = 0usize;
+        // forward keeps the smallest items, so the heap's top is the largest kept (and vice versa)
+        let mut smallest: BinaryHeap<SortableItem> = BinaryHeap::new();
+        let mut largest: BinaryHeap<Reverse<SortableItem>> = BinaryHeap::new();
+
+        for (pk, item) in items {
+            scanned += 1;
+
+            if page_size == 0 || !condition.matches(&pk) {
+                continue;
+            }
+
+            let sortable = SortableItem::new(&pk, item, sequence);
+            sequence += 1;
+
+            if scan_forward {
+                if after.is_some_and(|c| sortable.cmp_cursor(c) != Ordering::Greater) {
+                    continue;
+                }
+                smallest.push(sortable);
+                if smallest.len() > page_size {
+                    smallest.pop();
+                }
+            } else {
+                if after.is_some_and(|c| sortable.cmp_cursor(c) != Ordering::Less) {
+                    continue;
+                }
+                largest.push(Reverse(sortable));
+                if largest.len() > page_size {
+                    largest.pop();
+                }
+            }
+        }
+
+        let ordered: Vec<SortableItem> = if scan_forward {
+            smallest.into_sorted_vec()
+        } else {
+            largest.into_sorted_vec().into_iter().map(|r| r.0).collect()
+        };
+
+        let last = match ordered.last() {
+            Some(s) if page_size > 0 && ordered.len() == page_size => Some(s.cursor()),
+            _ => None,
+        };
+
+        QueryPage {
+            items: ordered.into_iter().map(|s| s.item).collect(),
+            scanned_count: scanned,
+            last,
+        }
+    }
+
+    // counts matches without collecting, sorting or cloning them
+    pub fn count<I: Borrow<Item>>(
+        &self,
+        items: impl Iterator<Item = (PrimaryKey, I)>,
+        condition: &KeyCondition,
+        filter: Option<&Condition>,
+    ) -> QueryResult {
+        let mut scanned = 0usize;
+        let mut count = 0usize;
+
+        for (pk, item) in items {
+            scanned += 1;
+
+            if !condition.matches(&pk) {
+                continue;
+            }
+            if let Some(filter) = filter
+                && !evaluate(filter, item.borrow()).unwrap_or(false)
+            {
+                continue;
+            }
+            count += 1;
+        }
+
+        QueryResult {
+            items: Vec::new(),
+            scanned_count: scanned,
+            count,
+        }
+    }
+
     #[inline]
     fn extract_ordered_items(
         matching: BTreeMap<SortableItem, ()>,
@@ -175,6 +297,11 @@ impl<'a> QueryExecutor<'a> {
     }
 
     pub fn validate_condition(&self, condition: &KeyCondition) -> TableResult<()> {
+        if condition.is_multi_attribute() {
+            return Err(TableError::validation_error(
+                "multi-attribute key conditions require an index with multi-attribute keys",
+            ));
+        }
         if !self
             .schema
             .partition_key
@@ -318,6 +445,83 @@ mod tests {
         assert_eq!(result.items[1].get("sk").unwrap().as_s(), Some("order#002"));
     }
 
+    #[test]
+    fn pages_resume_after_cursor() {
+        let schema = schema();
+        let executor = QueryExecutor::new(&schema);
+        let condition = KeyCondition::pk("user1");
+
+        for forward in [true, false] {
+            let mut cursor = None;
+            let mut data = Vec::new();
+            loop {
+                let page = executor.execute_page(
+                    test_items().into_iter(),
+                    &condition,
+                    forward,
+                    cursor.as_ref(),
+                    3,
+                );
+                assert!(page.items.len() <= 3);
+                data.extend(
+                    page.items
+                        .iter()
+                        .map(|i| i.get("data").unwrap().as_s().unwrap().to_string()),
+                );
+                match page.last {
+                    Some(last) => cursor = Some(last),
+                    None => break,
+                }
+            }
+
+            let mut expected = vec!["first", "second", "third", "user1 profile"];
+            if !forward {
+                expected.reverse();
+            }
+            assert_eq!(data, expected);
+        }
+    }
+
+    #[test]
+    fn short_page_has_no_cursor() {
+        let schema = schema();
+        let executor = QueryExecutor::new(&schema);
+        let page = executor.execute_page(
+            test_items().into_iter(),
+            &KeyCondition::pk("user2"),
+            true,
+            None,
+            5,
+        );
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.scanned_count, 6);
+        assert!(page.last.is_none());
+    }
+
+    #[test]
+    fn count_skips_materialization() {
+        let schema = schema();
+        let executor = QueryExecutor::new(&schema);
+        let items = test_items();
+
+        let result = executor.count(
+            items.iter().map(|(pk, item)| (pk.clone(), item)),
+            &KeyCondition::pk("user1").sk_begins_with("order"),
+            None,
+        );
+        assert!(result.items.is_empty());
+        assert_eq!(result.count, 3);
+        assert_eq!(result.scanned_count, 6);
+
+        let filter = crate::condition::attr("data").eq("second");
+        let result = executor.count(
+            items.iter().map(|(pk, item)| (pk.clone(), item)),
+            &KeyCondition::pk("user1"),
+            Some(&filter),
+        );
+        assert_eq!(result.count, 1);
+    }
+
     #[test]
     fn numeric_sort_keys() {
         let schema = KeySchema::composite("pk", KeyType::S, "sk", KeyType::N);
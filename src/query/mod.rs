@@ -1,5 +1,8 @@
 mod condition;
 mod executor;
+mod select;
 
 pub use condition::{KeyCondition, SortKeyOp};
+pub(crate) use executor::{QueryCursor, QueryPage};
 pub use executor::{QueryExecutor, QueryOptions, QueryResult};
+pub use select::{Select, project_paths};
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use crate::condition::{AttributePath, PathSegment};
+use crate::types::{AttributeValue, Item};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Select {
+    #[default]
+    AllAttributes,
+    // only meaningful for index queries; indexes return what they project either way
+    AllProjectedAttributes,
+    SpecificAttributes(Vec<AttributePath>),
+    Count,
+}
+
+impl Select {
+    pub fn specific<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<AttributePath>,
+    {
+        Self::SpecificAttributes(paths.into_iter().map(Into::into).collect())
+    }
+
+    pub fn is_count(&self) -> bool {
+        matches!(self, Self::Count)
+    }
+
+    pub fn apply(&self, item: Item) -> Item {
+        match self {
+            Self::SpecificAttributes(paths) => project_paths(&item, paths),
+            _ => item,
+        }
+    }
+}
+
+pub fn project_paths(item: &Item, paths: &[AttributePath]) -> Item {
+    let mut projected = Item::new();
+
+    for path in paths {
+        let Some(value) = path.resolve(item) else {
+            continue;
+        };
+        let Some((PathSegment::Key(root), rest)) = path.segments().split_first() else {
+            continue;
+        };
+
+        let existing = projected.remove(root);
+        let merged = merge_at(existing, rest, value.clone());
+        projected.set(root.clone(), merged);
+    }
+
+    projected
+}
+
+fn merge_at(
+    existing: Option<AttributeValue>,
+    segments: &[PathSegment],
+    value: AttributeValue,
+) -> AttributeValue {
+    match segments.split_first() {
+        None => value,
+        Some((PathSegment::Key(key), rest)) => {
+            let mut map = match existing {
+                Some(AttributeValue::M(m)) => m,
+                _ => BTreeMap::new(),
+            };
+            let child = map.remove(key);
+            map.insert(key.clone(), merge_at(child, rest, value));
+            AttributeValue::M(map)
+        }
+        // projected list elements are compacted, in the order they were requested
+        Some((PathSegment::Index(_), rest)) => {
+            let mut list = match existing {
+                Some(AttributeValue::L(l)) => l,
+                _ => Vec::new(),
+            };
+            list.push(merge_at(None, rest, value));
+            AttributeValue::L(list)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_item() -> Item {
+        let mut address = BTreeMap::new();
+        address.insert("city".to_string(), AttributeValue::S("Newton Falls".into()));
+        address.insert("zip".to_string(), AttributeValue::N("44444".into()));
+
+        Item::new()
+            .with_s("pk", "user1")
+            .with_s("name", "Alice")
+            .with_n("age", 30)
+            .with_m("address", address)
+            .with_l(
+                "tags",
+                vec![
+                    AttributeValue::S("a".into()),
+                    AttributeValue::S("b".into()),
+                    AttributeValue::S("c".into()),
+                ],
+            )
+    }
+
+    #[test]
+    fn top_level_attributes() {
+        let projected = Select::specific(["name", "missing"]).apply(test_item());
+        assert_eq!(projected.len(), 1);
+        assert_eq!(
+            projected.get("name"),
+            Some(&AttributeValue::S("Alice".into()))
+        );
+    }
+
+    #[test]
+    fn nested_map_paths() {
+        let projected = Select::SpecificAttributes(vec![
+            AttributePath::new("address").key("zip"),
+            AttributePath::new("pk"),
+        ])
+        .apply(test_item());
+
+        assert_eq!(projected.len(), 2);
+        let address = projected.get("address").unwrap().as_m().unwrap();
+        assert_eq!(address.len(), 1);
+        assert_eq!(address.get("zip"), Some(&AttributeValue::N("44444".into())));
+    }
+
+    #[test]
+    fn list_elements_are_compacted() {
+        let projected = Select::SpecificAttributes(vec![
+            AttributePath::new("tags").index(2),
+            AttributePath::new("tags").index(0),
+        ])
+        .apply(test_item());
+
+        let tags = projected.get("tags").unwrap().as_l().unwrap();
+        assert_eq!(
+            tags,
+            &vec![AttributeValue::S("c".into()), AttributeValue::S("a".into())]
+        );
+    }
+
+    #[test]
+    fn other_modes_leave_item_untouched() {
+        let item = test_item();
+        assert_eq!(Select::AllAttributes.apply(item.clone()), item);
+        assert_eq!(Select::AllProjectedAttributes.apply(item.clone()), item);
+        assert_eq!(Select::Count.apply(item.clone()), item);
+    }
+}
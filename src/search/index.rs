@@ -0,0 +1,403 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use crate::condition::AttributePath;
+use crate::index::KeyIndex;
+use crate::types::{AttributeValue, Item, PrimaryKey};
+
+use super::query::SearchQuery;
+use super::tokenizer::{SimpleTokenizer, Tokenizer};
+
+// bm25 term frequency saturation and length normalization
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+#[derive(Debug, Clone)]
+struct Document {
+    key: PrimaryKey,
+    // token count over all indexed attributes
+    length: usize,
+    terms: Vec<String>,
+}
+
+// inverted index over chosen string attributes
+#[derive(Debug)]
+pub struct TextIndex {
+    name: String,
+    attributes: Vec<AttributePath>,
+    tokenizer: Arc<dyn Tokenizer>,
+    // term -> table storage key -> token positions, ascending
+    postings: HashMap<String, BTreeMap<String, Vec<usize>>>,
+    documents: HashMap<String, Document>,
+    total_length: usize,
+}
+
+impl TextIndex {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn attributes(&self) -> &[AttributePath] {
+        &self.attributes
+    }
+
+    // indexed items
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    pub fn term_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    pub fn put(&mut self, table_key: &PrimaryKey, item: &Item) {
+        let storage_key = table_key.to_storage_key();
+        self.remove(&storage_key);
+
+        let mut positions: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        let mut position = 0;
+        for text in self.texts(item) {
+            for token in self.tokenizer.tokenize(text) {
+                positions.entry(token).or_default().push(position);
+                position += 1;
+            }
+            // keeps phrases from spanning two values
+            position += 1;
+        }
+        let length = positions.values().map(Vec::len).sum();
+        if length == 0 {
+            return;
+        }
+
+        let terms = positions.keys().cloned().collect();
+        for (term, term_positions) in positions {
+            self.postings
+                .entry(term)
+                .or_default()
+                .insert(storage_key.clone(), term_positions);
+        }
+        self.total_length += length;
+        self.documents.insert(
+            storage_key,
+            Document {
+                key: table_key.clone(),
+                length,
+                terms,
+            },
+        );
+    }
+
+    pub fn delete(&mut self, table_key: &PrimaryKey) {
+        self.remove(&table_key.to_storage_key());
+    }
+
+    pub fn clear(&mut self) {
+        self.postings.clear();
+        self.documents.clear();
+        self.total_length = 0;
+    }
+
+    // matching table keys, best first; ties go by storage key
+    pub fn search(&self, query: &SearchQuery) -> Vec<(PrimaryKey, f64)> {
+        let matches = self.matching(query);
+        let mut terms = Vec::new();
+        self.collect_terms(query, &mut terms);
+        terms.sort();
+        terms.dedup();
+
+        let mut ranked: Vec<(&str, f64)> = matches
+            .into_iter()
+            .map(|storage_key| (storage_key, self.score(storage_key, &terms)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        ranked
+            .into_iter()
+            .map(|(storage_key, score)| (self.documents[storage_key].key.clone(), score))
+            .collect()
+    }
+
+    fn remove(&mut self, storage_key: &str) {
+        let Some(document) = self.documents.remove(storage_key) else {
+            return;
+        };
+        self.total_length -= document.length;
+        for term in document.terms {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                postings.remove(storage_key);
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    // string values of the indexed attributes; string sets and lists contribute each element
+    fn texts<'a>(&self, item: &'a Item) -> Vec<&'a str> {
+        let mut texts = Vec::new();
+        for path in &self.attributes {
+            match path.resolve(item) {
+                Some(AttributeValue::S(s)) => texts.push(s.as_str()),
+                Some(AttributeValue::Ss(set)) => texts.extend(set.iter().map(String::as_str)),
+                Some(AttributeValue::L(list)) => texts.extend(list.iter().filter_map(|v| v.as_s())),
+                _ => {}
+            }
+        }
+        texts
+    }
+
+    fn matching(&self, query: &SearchQuery) -> BTreeSet<&str> {
+        match query {
+            SearchQuery::Term(text) => self.with_all(&self.tokenizer.tokenize(text)),
+            SearchQuery::Phrase(text) => {
+                let tokens = self.tokenizer.tokenize(text);
+                let mut candidates = self.with_all(&tokens);
+                candidates.retain(|storage_key| self.has_phrase(storage_key, &tokens));
+                candidates
+            }
+            SearchQuery::And(queries) => {
+                let mut queries = queries.iter();
+                let Some(first) = queries.next() else {
+                    return BTreeSet::new();
+                };
+                let mut matches = self.matching(first);
+                for query in queries {
+                    if matches.is_empty() {
+                        break;
+                    }
+                    let other = self.matching(query);
+                    matches.retain(|storage_key| other.contains(storage_key));
+                }
+                matches
+            }
+            SearchQuery::Or(queries) => queries.iter().flat_map(|q| self.matching(q)).collect(),
+        }
+    }
+
+    // documents containing every token
+    fn with_all(&self, tokens: &[String]) -> BTreeSet<&str> {
+        let mut postings = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            match self.postings.get(token) {
+                Some(p) => postings.push(p),
+                None => return BTreeSet::new(),
+            }
+        }
+        postings.sort_by_key(|p| p.len());
+
+        let Some((smallest, rest)) = postings.split_first() else {
+            return BTreeSet::new();
+        };
+        smallest
+            .keys()
+            .map(String::as_str)
+            .filter(|storage_key| rest.iter().all(|p| p.contains_key(*storage_key)))
+            .collect()
+    }
+
+    fn has_phrase(&self, storage_key: &str, tokens: &[String]) -> bool {
+        let positions: Vec<&Vec<usize>> = tokens
+            .iter()
+            .filter_map(|token| self.postings.get(token)?.get(storage_key))
+            .collect();
+        let Some((first, rest)) = positions.split_first() else {
+            return false;
+        };
+        first.iter().any(|&start| {
+            rest.iter()
+                .enumerate()
+                .all(|(i, p)| p.binary_search(&(start + i + 1)).is_ok())
+        })
+    }
+
+    fn collect_terms(&self, query: &SearchQuery, terms: &mut Vec<String>) {
+        match query {
+            SearchQuery::Term(text) | SearchQuery::Phrase(text) => {
+                terms.extend(self.tokenizer.tokenize(text))
+            }
+            SearchQuery::And(queries) | SearchQuery::Or(queries) => {
+                for query in queries {
+                    self.collect_terms(query, terms);
+                }
+            }
+        }
+    }
+
+    fn score(&self, storage_key: &str, terms: &[String]) -> f64 {
+        let documents = self.documents.len() as f64;
+        let average_length = self.total_length as f64 / documents;
+        let length = self.documents[storage_key].length as f64;
+
+        terms
+            .iter()
+            .filter_map(|term| {
+                let postings = self.postings.get(term)?;
+                let frequency = postings.get(storage_key)?.len() as f64;
+                let with_term = postings.len() as f64;
+                let idf = (1.0 + (documents - with_term + 0.5) / (with_term + 0.5)).ln();
+                let norm = K1 * (1.0 - B + B * length / average_length);
+                Some(idf * frequency * (K1 + 1.0) / (frequency + norm))
+            })
+            .sum()
+    }
+}
+
+impl KeyIndex for TextIndex {
+    fn name(&self) -> &str {
+        TextIndex::name(self)
+    }
+
+    fn put(&mut self, table_key: &PrimaryKey, item: &Item) {
+        TextIndex::put(self, table_key, item)
+    }
+
+    fn delete(&mut self, table_key: &PrimaryKey) {
+        TextIndex::delete(self, table_key)
+    }
+
+    fn clear(&mut self) {
+        TextIndex::clear(self)
+    }
+}
+
+pub struct TextIndexBuilder {
+    name: String,
+    attributes: Vec<AttributePath>,
+    tokenizer: Arc<dyn Tokenizer>,
+}
+
+impl TextIndexBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            attributes: Vec::new(),
+            tokenizer: Arc::new(SimpleTokenizer::new()),
+        }
+    }
+
+    pub fn attribute(mut self, path: impl Into<AttributePath>) -> Self {
+        self.attributes.push(path.into());
+        self
+    }
+
+    pub fn attributes<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<AttributePath>,
+    {
+        self.attributes.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn tokenizer(mut self, tokenizer: impl Tokenizer + 'static) -> Self {
+        self.tokenizer = Arc::new(tokenizer);
+        self
+    }
+
+    pub fn build(self) -> TextIndex {
+        TextIndex {
+            name: self.name,
+            attributes: self.attributes,
+            tokenizer: self.tokenizer,
+            postings: HashMap::new(),
+            documents: HashMap::new(),
+            total_length: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(id: &str, title: &str, description: &str) -> (PrimaryKey, Item) {
+        let item = Item::new()
+            .with_s("id", id)
+            .with_s("title", title)
+            .with_s("description", description);
+        (PrimaryKey::simple(id), item)
+    }
+
+    fn index() -> TextIndex {
+        let mut index = TextIndexBuilder::new("products")
+            .attributes(["title", "description"])
+            .build();
+        for (key, item) in [
+            product("p1", "Rust in Action", "systems programming with rust"),
+            product("p2", "Database Internals", "how storage engines work"),
+            product("p3", "Zero To Production", "backend development in rust"),
+            product("p4", "Key Value Stores", "a key value database in rust"),
+        ] {
+            index.put(&key, &item);
+        }
+        index
+    }
+
+    fn ids(index: &TextIndex, query: SearchQuery) -> Vec<String> {
+        index
+            .search(&query)
+            .into_iter()
+            .map(|(key, _)| key.pk.as_s().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn terms_combine_with_and_or() {
+        let index = index();
+        assert_eq!(ids(&index, SearchQuery::term("Rust")).len(), 3);
+        assert_eq!(ids(&index, SearchQuery::parse("rust database")), ["p4"]);
+
+        let mut either = ids(&index, SearchQuery::parse("storage OR production"));
+        either.sort();
+        assert_eq!(either, ["p2", "p3"]);
+        assert!(ids(&index, SearchQuery::term("python")).is_empty());
+    }
+
+    #[test]
+    fn phrases_need_adjacent_tokens() {
+        let index = index();
+        assert_eq!(
+            ids(&index, SearchQuery::phrase("key value database")),
+            ["p4"]
+        );
+        assert!(ids(&index, SearchQuery::phrase("value key")).is_empty());
+        // the title and description don't run into each other
+        assert!(ids(&index, SearchQuery::phrase("stores a")).is_empty());
+    }
+
+    #[test]
+    fn ranks_by_bm25() {
+        let index = index();
+        let ranked = index.search(&SearchQuery::parse("rust OR key"));
+        let ids: Vec<_> = ranked
+            .iter()
+            .map(|(key, _)| key.pk.as_s().unwrap().to_string())
+            .collect();
+        // p4 mentions key twice as well as rust
+        assert_eq!(ids[0], "p4");
+        // p1 mentions rust twice
+        assert_eq!(ids[1], "p1");
+        assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    fn rewrites_and_deletes_update_postings() {
+        let mut index = index();
+        let (key, item) = product("p1", "Go in Action", "concurrency with go");
+        index.put(&key, &item);
+        assert_eq!(ids(&index, SearchQuery::term("rust")).len(), 2);
+        assert_eq!(ids(&index, SearchQuery::term("go")), ["p1"]);
+
+        index.delete(&key);
+        assert!(ids(&index, SearchQuery::term("go")).is_empty());
+        assert!(!index.postings.contains_key("concurrency"));
+        assert_eq!(index.len(), 3);
+    }
+}
@@ -0,0 +1,7 @@
+mod index;
+mod query;
+mod tokenizer;
+
+pub use index::{TextIndex, TextIndexBuilder};
+pub use query::{SearchCursor, SearchHit, SearchQuery, SearchRequest, SearchResult};
+pub use tokenizer::{SimpleTokenizer, Tokenizer, stem};
@@ -0,0 +1,212 @@
+use crate::types::{Item, PrimaryKey};
+
+// query text is tokenized by the index's tokenizer when the search runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchQuery {
+    // every token of the text must appear somewhere
+    Term(String),
+    // the tokens must appear next to each other, in order
+    Phrase(String),
+    And(Vec<SearchQuery>),
+    Or(Vec<SearchQuery>),
+}
+
+impl SearchQuery {
+    pub fn term(text: impl Into<String>) -> Self {
+        Self::Term(text.into())
+    }
+
+    pub fn phrase(text: impl Into<String>) -> Self {
+        Self::Phrase(text.into())
+    }
+
+    pub fn all(queries: impl IntoIterator<Item = SearchQuery>) -> Self {
+        Self::And(queries.into_iter().collect())
+    }
+
+    pub fn any(queries: impl IntoIterator<Item = SearchQuery>) -> Self {
+        Self::Or(queries.into_iter().collect())
+    }
+
+    pub fn and(self, other: SearchQuery) -> Self {
+        match self {
+            Self::And(mut queries) => {
+                queries.push(other);
+                Self::And(queries)
+            }
+            query => Self::And(vec![query, other]),
+        }
+    }
+
+    pub fn or(self, other: SearchQuery) -> Self {
+        match self {
+            Self::Or(mut queries) => {
+                queries.push(other);
+                Self::Or(queries)
+            }
+            query => Self::Or(vec![query, other]),
+        }
+    }
+
+    // search-box syntax: words are ANDed, `OR` separates alternatives and binds looser,
+    // double quotes make a phrase. `AND` is accepted but implied
+    pub fn parse(input: &str) -> Self {
+        let mut alternatives = Vec::new();
+        let mut current = Vec::new();
+        let mut rest = input.trim_start();
+
+        while !rest.is_empty() {
+            if let Some(quoted) = rest.strip_prefix('"') {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                current.push(Self::phrase(&quoted[..end]));
+                rest = quoted.get(end + 1..).unwrap_or("");
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '"')
+                    .unwrap_or(rest.len());
+                match &rest[..end] {
+                    "OR" => alternatives.push(std::mem::take(&mut current)),
+                    "AND" => {}
+                    word => current.push(Self::term(word)),
+                }
+                rest = &rest[end..];
+            }
+            rest = rest.trim_start();
+        }
+        alternatives.push(current);
+
+        let mut alternatives: Vec<SearchQuery> = alternatives
+            .into_iter()
+            .filter(|terms| !terms.is_empty())
+            .map(|mut terms| match terms.len() {
+                1 => terms.remove(0),
+                _ => Self::And(terms),
+            })
+            .collect();
+        match alternatives.len() {
+            1 => alternatives.remove(0),
+            _ => Self::Or(alternatives),
+        }
+    }
+}
+
+impl From<&str> for SearchQuery {
+    fn from(input: &str) -> Self {
+        Self::parse(input)
+    }
+}
+
+// position of a hit in ranked order; resuming after it continues the search
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchCursor {
+    pub(crate) score: f64,
+    pub(crate) storage_key: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchRequest {
+    pub(crate) query: SearchQuery,
+    pub(crate) limit: Option<usize>,
+    pub(crate) start_after: Option<SearchCursor>,
+}
+
+impl SearchRequest {
+    pub fn new(query: impl Into<SearchQuery>) -> Self {
+        Self {
+            query: query.into(),
+            limit: None,
+            start_after: None,
+        }
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn start_after(mut self, cursor: SearchCursor) -> Self {
+        self.start_after = Some(cursor);
+        self
+    }
+}
+
+impl From<SearchQuery> for SearchRequest {
+    fn from(query: SearchQuery) -> Self {
+        Self::new(query)
+    }
+}
+
+impl From<&str> for SearchRequest {
+    fn from(input: &str) -> Self {
+        Self::new(input)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub key: PrimaryKey,
+    pub item: Item,
+    // bm25 relevance; higher is better
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchResult {
+    pub hits: Vec<SearchHit>,
+    // matches across all pages
+    pub total: usize,
+    // set when the page is full and more hits follow
+    pub last: Option<SearchCursor>,
+}
+
+impl SearchResult {
+    pub fn items(&self) -> impl Iterator<Item = &Item> {
+        self.hits.iter().map(|hit| &hit.item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_words_are_anded() {
+        assert_eq!(SearchQuery::parse("rust"), SearchQuery::term("rust"));
+        assert_eq!(
+            SearchQuery::parse("fast  rust AND db"),
+            SearchQuery::all([
+                SearchQuery::term("fast"),
+                SearchQuery::term("rust"),
+                SearchQuery::term("db"),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_or_and_phrases() {
+        assert_eq!(
+            SearchQuery::parse(r#"rust "key value" OR go"#),
+            SearchQuery::any([
+                SearchQuery::all([SearchQuery::term("rust"), SearchQuery::phrase("key value")]),
+                SearchQuery::term("go"),
+            ])
+        );
+        // an unterminated quote runs to the end
+        assert_eq!(
+            SearchQuery::parse(r#"OR "in memory"#),
+            SearchQuery::phrase("in memory")
+        );
+        assert_eq!(SearchQuery::parse("   "), SearchQuery::Or(Vec::new()));
+    }
+
+    #[test]
+    fn combinators_flatten() {
+        let query = SearchQuery::term("a")
+            .and(SearchQuery::term("b"))
+            .and(SearchQuery::term("c"));
+        assert!(matches!(query, SearchQuery::And(ref q) if q.len() == 3));
+
+        let query = SearchQuery::term("a").or(SearchQuery::term("b"));
+        assert!(matches!(query, SearchQuery::Or(ref q) if q.len() == 2));
+    }
+}
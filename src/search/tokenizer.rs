@@ -0,0 +1,120 @@
+use std::fmt;
+
+// turns attribute text and query text into terms; both sides must use the same tokenizer
+pub trait Tokenizer: fmt::Debug + Send + Sync {
+    fn tokenize(&self, text: &str) -> Vec<String>;
+}
+
+// lowercases and splits on anything that isn't alphanumeric
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimpleTokenizer {
+    stemming: bool,
+}
+
+impl SimpleTokenizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // folds common english suffixes so "indexes", "indexed" and "indexing" match "index"
+    pub fn with_stemming(mut self) -> Self {
+        self.stemming = true;
+        self
+    }
+}
+
+impl Tokenizer for SimpleTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let word = word.to_lowercase();
+                if self.stemming { stem(&word) } else { word }
+            })
+            .collect()
+    }
+}
+
+// light suffix stripping rather than a full porter stemmer; short words are left alone
+pub fn stem(word: &str) -> String {
+    let len = word.chars().count();
+    if len <= 3 || !word.is_ascii() {
+        return word.to_string();
+    }
+
+    if let Some(base) = word.strip_suffix("ies") {
+        return format!("{}y", base);
+    }
+    if let Some(base) = word.strip_suffix("sses") {
+        return format!("{}ss", base);
+    }
+    for suffix in ["ing", "ed"] {
+        if let Some(base) = word.strip_suffix(suffix)
+            && base.len() >= 3
+            && base.bytes().any(is_vowel)
+        {
+            return undouble(base).to_string();
+        }
+    }
+    if let Some(base) = word.strip_suffix("es")
+        && (base.ends_with('x') || base.ends_with("ch") || base.ends_with("sh"))
+    {
+        return base.to_string();
+    }
+    if word.ends_with('s') && !word.ends_with("ss") && !word.ends_with("us") {
+        return word[..word.len() - 1].to_string();
+    }
+    word.to_string()
+}
+
+fn is_vowel(b: u8) -> bool {
+    matches!(b, b'a' | b'e' | b'i' | b'o' | b'u')
+}
+
+// "stopp" -> "stop", but "fall" and "miss" keep their doubled letter
+fn undouble(base: &str) -> &str {
+    let bytes = base.as_bytes();
+    match bytes {
+        [.., a, b] if a == b && !matches!(a, b'l' | b's' | b'z') && !is_vowel(*a) => {
+            &base[..base.len() - 1]
+        }
+        _ => base,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_and_lowercases() {
+        let tokens = SimpleTokenizer::new().tokenize("Fast, in-memory KEY/value store!");
+        assert_eq!(tokens, ["fast", "in", "memory", "key", "value", "store"]);
+        assert!(SimpleTokenizer::new().tokenize(" -- ").is_empty());
+    }
+
+    #[test]
+    fn stems_common_suffixes() {
+        let cases = [
+            ("indexes", "index"),
+            ("indexed", "index"),
+            ("indexing", "index"),
+            ("queries", "query"),
+            ("stopped", "stop"),
+            ("falling", "fall"),
+            ("classes", "class"),
+            ("status", "status"),
+            ("bed", "bed"),
+            ("red", "red"),
+            ("tables", "table"),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(stem(word), expected, "{}", word);
+        }
+
+        let tokens = SimpleTokenizer::new()
+            .with_stemming()
+            .tokenize("Indexing Tables");
+        assert_eq!(tokens, ["index", "table"]);
+    }
+}
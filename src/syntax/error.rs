@@ -0,0 +1,15 @@
+// what went wrong, at a byte offset into the input; each front end wraps it in its own error
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SyntaxError {
+    pub(crate) position: usize,
+    pub(crate) message: String,
+}
+
+impl SyntaxError {
+    pub(crate) fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
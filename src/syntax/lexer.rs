@@ -0,0 +1,372 @@
+use crate::condition::CompareOp;
+
+use super::error::SyntaxError;
+
+// the language being read; each one only lexes the tokens it has a use for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Dialect {
+    // DynamoDB condition, key, update and projection expressions
+    Expression,
+    // PartiQL statements
+    Partiql,
+}
+
+impl Dialect {
+    fn input(self) -> &'static str {
+        match self {
+            Self::Expression => "expression",
+            Self::Partiql => "statement",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    // bare identifiers double as keywords, matched case-insensitively
+    Ident(String),
+    // "double quoted" identifiers are never keywords; PartiQL only
+    Quoted(String),
+    // 'single quoted' strings; PartiQL only
+    Str(String),
+    // `#name`, looked up in the expression attribute names; expressions only
+    Name(String),
+    // `:value`, looked up in the expression attribute values; expressions only
+    Value(String),
+    // expressions only write unsigned integers
+    Number(String),
+    Param,
+    Star,
+    Comma,
+    Dot,
+    Colon,
+    Plus,
+    Minus,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    // << and >> around PartiQL set literals
+    LBag,
+    RBag,
+    Compare(CompareOp),
+    Eof,
+}
+
+impl Token {
+    pub(crate) fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Self::Ident(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    pub(crate) fn describe(&self, dialect: Dialect) -> String {
+        let symbol = match self {
+            Self::Ident(word) | Self::Name(word) | Self::Value(word) => {
+                return format!("'{}'", word);
+            }
+            Self::Quoted(word) => return format!("\"{}\"", word),
+            Self::Str(_) => return "a string".to_string(),
+            Self::Number(n) => return n.clone(),
+            Self::Compare(_) => return "a comparison".to_string(),
+            Self::Eof => return format!("the end of the {}", dialect.input()),
+            Self::Param => "?",
+            Self::Star => "*",
+            Self::Comma => ",",
+            Self::Dot => ".",
+            Self::Colon => ":",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::LParen => "(",
+            Self::RParen => ")",
+            Self::LBrace => "{",
+            Self::RBrace => "}",
+            Self::LBracket => "[",
+            Self::RBracket => "]",
+            Self::LBag => "<<",
+            Self::RBag => ">>",
+        };
+        format!("'{}'", symbol)
+    }
+}
+
+fn is_word(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+// tokens paired with their byte offsets; always ends with `Eof`
+pub(crate) fn tokenize(input: &str, dialect: Dialect) -> Result<Vec<(Token, usize)>, SyntaxError> {
+    let partiql = dialect == Dialect::Partiql;
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let token = match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'\'' if partiql => {
+                let (text, end) = quoted(input, i, b'\'')?;
+                i = end;
+                Token::Str(text)
+            }
+            b'"' if partiql => {
+                let (text, end) = quoted(input, i, b'"')?;
+                i = end;
+                Token::Quoted(text)
+            }
+            b'#' | b':' if !partiql => {
+                i += 1;
+                while i < bytes.len() && is_word(bytes[i]) {
+                    i += 1;
+                }
+                if i == start + 1 {
+                    return Err(SyntaxError::new(
+                        start,
+                        format!("'{}' must be followed by a name", c as char),
+                    ));
+                }
+                let word = input[start..i].to_string();
+                if c == b'#' {
+                    Token::Name(word)
+                } else {
+                    Token::Value(word)
+                }
+            }
+            b'0'..=b'9' => {
+                i = number_end(bytes, i, partiql);
+                Token::Number(input[start..i].to_string())
+            }
+            b'-' | b'+' if partiql && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                i = number_end(bytes, i + 1, partiql);
+                Token::Number(input[start..i].to_string())
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && is_word(bytes[i]) {
+                    i += 1;
+                }
+                Token::Ident(input[start..i].to_string())
+            }
+            b'<' => {
+                let (token, len) = match bytes.get(i + 1) {
+                    Some(b'<') if partiql => (Token::LBag, 2),
+                    Some(b'=') => (Token::Compare(CompareOp::Le), 2),
+                    Some(b'>') => (Token::Compare(CompareOp::Ne), 2),
+                    _ => (Token::Compare(CompareOp::Lt), 1),
+                };
+                i += len;
+                token
+            }
+            b'>' => {
+                let (token, len) = match bytes.get(i + 1) {
+                    Some(b'>') if partiql => (Token::RBag, 2),
+                    Some(b'=') => (Token::Compare(CompareOp::Ge), 2),
+                    _ => (Token::Compare(CompareOp::Gt), 1),
+                };
+                i += len;
+                token
+            }
+            b'!' if partiql && bytes.get(i + 1) == Some(&b'=') => {
+                i += 2;
+                Token::Compare(CompareOp::Ne)
+            }
+            _ => {
+                i += 1;
+                match c {
+                    b'?' => Token::Param,
+                    b'*' => Token::Star,
+                    b',' => Token::Comma,
+                    b'.' => Token::Dot,
+                    b':' => Token::Colon,
+                    b'+' => Token::Plus,
+                    b'-' => Token::Minus,
+                    b'(' => Token::LParen,
+                    b')' => Token::RParen,
+                    b'{' => Token::LBrace,
+                    b'}' => Token::RBrace,
+                    b'[' => Token::LBracket,
+                    b']' => Token::RBracket,
+                    b'=' => Token::Compare(CompareOp::Eq),
+                    _ => {
+                        let c = input[start..].chars().next().unwrap_or('?');
+                        return Err(SyntaxError::new(
+                            start,
+                            format!("unexpected character '{}'", c),
+                        ));
+                    }
+                }
+            }
+        };
+        tokens.push((token, start));
+    }
+    tokens.push((Token::Eof, input.len()));
+    Ok(tokens)
+}
+
+// a doubled quote inside stands for one quote
+fn quoted(input: &str, start: usize, quote: u8) -> Result<(String, usize), SyntaxError> {
+    let bytes = input.as_bytes();
+    let mut text = String::new();
+    let mut i = start + 1;
+    let mut run = i;
+    loop {
+        match bytes.get(i) {
+            None => return Err(SyntaxError::new(start, "unterminated quote")),
+            Some(&b) if b == quote => {
+                text.push_str(&input[run..i]);
+                if bytes.get(i + 1) == Some(&quote) {
+                    text.push(quote as char);
+                    i += 2;
+                    run = i;
+                } else {
+                    return Ok((text, i + 1));
+                }
+            }
+            Some(_) => i += 1,
+        }
+    }
+}
+
+// decimals and exponents are PartiQL only
+fn number_end(bytes: &[u8], mut i: usize, partiql: bool) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    if !partiql {
+        return i;
+    }
+    if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        let digits = match bytes.get(i + 1) {
+            Some(b'+' | b'-') => i + 2,
+            _ => i + 1,
+        };
+        if bytes.get(digits).is_some_and(u8::is_ascii_digit) {
+            i = digits;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str, dialect: Dialect) -> Vec<Token> {
+        tokenize(input, dialect)
+            .unwrap()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    #[test]
+    fn expression_tokens_and_positions() {
+        let tokens = tokenize("#a.b[12] <> :v AND size(x)>=:n", Dialect::Expression).unwrap();
+        let kinds: Vec<Token> = tokens.iter().map(|(t, _)| t.clone()).collect();
+        assert_eq!(
+            kinds,
+            [
+                Token::Name("#a".into()),
+                Token::Dot,
+                Token::Ident("b".into()),
+                Token::LBracket,
+                Token::Number("12".into()),
+                Token::RBracket,
+                Token::Compare(CompareOp::Ne),
+                Token::Value(":v".into()),
+                Token::Ident("AND".into()),
+                Token::Ident("size".into()),
+                Token::LParen,
+                Token::Ident("x".into()),
+                Token::RParen,
+                Token::Compare(CompareOp::Ge),
+                Token::Value(":n".into()),
+                Token::Eof,
+            ]
+        );
+        let positions: Vec<usize> = tokens.iter().map(|(_, p)| *p).collect();
+        assert_eq!(
+            positions,
+            [0, 2, 3, 4, 5, 7, 9, 12, 15, 19, 23, 24, 25, 26, 28, 30]
+        );
+    }
+
+    #[test]
+    fn partiql_tokens_and_positions() {
+        let tokens = tokenize("SELECT * FROM \"t\" WHERE a >= -1.5e3", Dialect::Partiql).unwrap();
+        let positions: Vec<usize> = tokens.iter().map(|(_, p)| *p).collect();
+        assert_eq!(positions, [0, 7, 9, 14, 18, 24, 26, 29, 35]);
+        assert_eq!(tokens[3].0, Token::Quoted("t".into()));
+        assert_eq!(tokens[6].0, Token::Compare(CompareOp::Ge));
+        assert_eq!(tokens[7].0, Token::Number("-1.5e3".into()));
+    }
+
+    #[test]
+    fn quotes_bags_and_operators() {
+        assert_eq!(
+            kinds("'it''s' <<1>> <> != <=", Dialect::Partiql),
+            [
+                Token::Str("it's".into()),
+                Token::LBag,
+                Token::Number("1".into()),
+                Token::RBag,
+                Token::Compare(CompareOp::Ne),
+                Token::Compare(CompareOp::Ne),
+                Token::Compare(CompareOp::Le),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn dialects_differ() {
+        assert_eq!(
+            kinds("a-1.5", Dialect::Expression),
+            [
+                Token::Ident("a".into()),
+                Token::Minus,
+                Token::Number("1".into()),
+                Token::Dot,
+                Token::Number("5".into()),
+                Token::Eof,
+            ]
+        );
+        assert_eq!(
+            kinds("a<<b", Dialect::Expression)[1..3],
+            [Token::Compare(CompareOp::Lt), Token::Compare(CompareOp::Lt)]
+        );
+        assert_eq!(kinds(":", Dialect::Partiql)[0], Token::Colon);
+        assert!(tokenize("'a'", Dialect::Expression).is_err());
+    }
+
+    #[test]
+    fn errors_carry_positions() {
+        assert_eq!(
+            tokenize("a = : b", Dialect::Expression).unwrap_err(),
+            SyntaxError::new(4, "':' must be followed by a name")
+        );
+        assert_eq!(
+            tokenize("a != :b", Dialect::Expression)
+                .unwrap_err()
+                .position,
+            2
+        );
+        assert_eq!(
+            tokenize("a = 'open", Dialect::Partiql).unwrap_err(),
+            SyntaxError::new(4, "unterminated quote")
+        );
+        assert_eq!(tokenize("a = #", Dialect::Partiql).unwrap_err().position, 4);
+    }
+}
@@ -0,0 +1,7 @@
+mod error;
+mod lexer;
+mod tokens;
+
+pub(crate) use error::SyntaxError;
+pub(crate) use lexer::{Dialect, Token};
+pub(crate) use tokens::Tokens;
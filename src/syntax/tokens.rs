@@ -0,0 +1,170 @@
+use std::num::IntErrorKind;
+
+use crate::condition::AttributePath;
+
+use super::error::SyntaxError;
+use super::lexer::{Dialect, Token, tokenize};
+
+// a cursor over the tokens of one input, shared by the recursive descent parsers
+pub(crate) struct Tokens {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    dialect: Dialect,
+}
+
+impl Tokens {
+    pub(crate) fn new(input: &str, dialect: Dialect) -> Result<Self, SyntaxError> {
+        Ok(Self {
+            tokens: tokenize(input, dialect)?,
+            pos: 0,
+            dialect,
+        })
+    }
+
+    pub(crate) fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    // the token after the next one
+    pub(crate) fn peek_second(&self) -> &Token {
+        let i = (self.pos + 1).min(self.tokens.len() - 1);
+        &self.tokens[i].0
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    pub(crate) fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    pub(crate) fn accept(&mut self, token: &Token) -> bool {
+        let found = self.peek() == token;
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    pub(crate) fn accept_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_keyword(keyword);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    pub(crate) fn expect(&mut self, token: Token, what: &str) -> Result<(), SyntaxError> {
+        if self.accept(&token) {
+            Ok(())
+        } else {
+            Err(self.unexpected(what))
+        }
+    }
+
+    pub(crate) fn expect_keyword(&mut self, keyword: &str) -> Result<(), SyntaxError> {
+        if self.accept_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(keyword))
+        }
+    }
+
+    pub(crate) fn expect_end(&self) -> Result<(), SyntaxError> {
+        if self.peek() == &Token::Eof {
+            Ok(())
+        } else {
+            Err(self.unexpected(&Token::Eof.describe(self.dialect)))
+        }
+    }
+
+    pub(crate) fn unexpected(&self, expected: &str) -> SyntaxError {
+        SyntaxError::new(
+            self.position(),
+            format!(
+                "expected {}, found {}",
+                expected,
+                self.peek().describe(self.dialect)
+            ),
+        )
+    }
+
+    // a document path: a name, then any run of `.name` and `[n]`, plus `['key']` in PartiQL;
+    // `name` reads each name, as the dialects spell them differently
+    pub(crate) fn path<E: From<SyntaxError>>(
+        &mut self,
+        mut name: impl FnMut(&mut Self) -> Result<String, E>,
+    ) -> Result<AttributePath, E> {
+        let mut path = AttributePath::new(name(self)?);
+        loop {
+            if self.accept(&Token::Dot) {
+                path = path.key(name(self)?);
+            } else if self.accept(&Token::LBracket) {
+                path = match self.peek().clone() {
+                    Token::Number(n) => match n.parse() {
+                        Ok(index) => path.index(index),
+                        Err(e) if *e.kind() == IntErrorKind::PosOverflow => {
+                            return Err(SyntaxError::new(
+                                self.position(),
+                                format!("list index {} is too large", n),
+                            )
+                            .into());
+                        }
+                        Err(_) => return Err(self.unexpected("a list index").into()),
+                    },
+                    Token::Str(key) => path.key(key),
+                    _ if self.dialect == Dialect::Partiql => {
+                        return Err(self.unexpected("a list index or quoted key").into());
+                    }
+                    _ => return Err(self.unexpected("a list index").into()),
+                };
+                self.advance();
+                self.expect(Token::RBracket, "']'")?;
+            } else {
+                return Ok(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bare_name(tokens: &mut Tokens) -> Result<String, SyntaxError> {
+        match tokens.advance() {
+            Token::Ident(word) => Ok(word),
+            _ => Err(SyntaxError::new(0, "not a name")),
+        }
+    }
+
+    #[test]
+    fn paths() {
+        let mut tokens = Tokens::new("a.b[2]['c'] x", Dialect::Partiql).unwrap();
+        assert_eq!(
+            tokens.path(bare_name).unwrap(),
+            AttributePath::new("a").key("b").index(2).key("c")
+        );
+        assert_eq!(tokens.peek(), &Token::Ident("x".into()));
+        assert_eq!(
+            tokens.expect_end().unwrap_err(),
+            SyntaxError::new(12, "expected the end of the statement, found 'x'")
+        );
+
+        let mut tokens = Tokens::new("a[99999999999999999999]", Dialect::Expression).unwrap();
+        assert_eq!(
+            tokens.path(bare_name).unwrap_err(),
+            SyntaxError::new(2, "list index 99999999999999999999 is too large")
+        );
+        let mut tokens = Tokens::new("a[-1]", Dialect::Partiql).unwrap();
+        assert_eq!(
+            tokens.path(bare_name).unwrap_err(),
+            SyntaxError::new(2, "expected a list index, found -1")
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use super::core::Table;
+use super::request::{QueryRequest, ScanRequest};
+use crate::aggregate::{AggregateResult, Aggregation, Aggregator};
+use crate::condition::evaluate;
+use crate::error::{TableError, TableResult};
+use crate::query::Select;
+
+// aggregations stream their source and ignore its select; request filters and limits
+// apply as they would for the plain read
+impl Table {
+    pub fn aggregate_query(
+        &self,
+        request: impl Into<QueryRequest>,
+        aggregation: &Aggregation,
+    ) -> TableResult<AggregateResult> {
+        let mut request = request.into();
+        request.select = Select::AllAttributes;
+        let mut aggregator = Aggregator::new(aggregation)?;
+        for item in self.query_iter(request)? {
+            aggregator.add(&item?)?;
+        }
+        Ok(aggregator.finish())
+    }
+
+    // over what the index projects
+    pub fn aggregate_gsi(
+        &self,
+        index_name: &str,
+        request: impl Into<QueryRequest>,
+        aggregation: &Aggregation,
+    ) -> TableResult<AggregateResult> {
+        let request = request.into();
+        let mut aggregator = Aggregator::new(aggregation)?;
+        let gsi = self.gsi_view(index_name)?;
+        if !gsi.is_active() {
+            return Err(TableError::index_not_active(index_name));
+        }
+
+        for item in gsi.matching(&request.key_condition, &request.options)? {
+            if let Some(ref filter) = request.filter
+                && !evaluate(filter, item).unwrap_or(false)
+            {
+                continue;
+            }
+            aggregator.add(item)?;
+        }
+        Ok(aggregator.finish())
+    }
+
+    pub fn aggregate_scan(
+        &self,
+        mut request: ScanRequest,
+        aggregation: &Aggregation,
+    ) -> TableResult<AggregateResult> {
+        request.select = Select::AllAttributes;
+        let mut aggregator = Aggregator::new(aggregation)?;
+        for item in self.scan_iter(request)? {
+            aggregator.add(&item?)?;
+        }
+        Ok(aggregator.finish())
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::index::GlobalSecondaryIndex;
+use crate::types::{Item, PrimaryKey};
+
+// time source for the consistency simulation, as an offset from an arbitrary origin
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+// only moves when told to; clones share the same time
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PendingWrite {
+    due: Duration,
+    key: PrimaryKey,
+    // what eventually consistent readers see until the write is due
+    previous: Option<Item>,
+    // None for deletes
+    item: Option<Item>,
+}
+
+impl PendingWrite {
+    // the key written and what it was set to, None for deletes
+    pub(crate) fn change(&self) -> (&PrimaryKey, Option<&Item>) {
+        (&self.key, self.item.as_ref())
+    }
+
+    pub(crate) fn apply_to(&self, gsi: &mut GlobalSecondaryIndex) {
+        match &self.item {
+            Some(item) => {
+                gsi.put(self.key.clone(), item);
+            }
+            None => {
+                gsi.delete(&self.key);
+            }
+        }
+    }
+}
+
+// opt-in lag between a write and its visibility in gsis and eventually consistent reads
+#[derive(Debug)]
+pub struct EventualConsistency {
+    delay: Duration,
+    clock: Arc<dyn Clock>,
+    // in write order, so also in due order
+    pending: VecDeque<PendingWrite>,
+}
+
+impl EventualConsistency {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            clock: Arc::new(SystemClock::new()),
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub(crate) fn record(&mut self, key: PrimaryKey, previous: Option<Item>, item: Option<Item>) {
+        self.pending.push_back(PendingWrite {
+            due: self.clock.now() + self.delay,
+            key,
+            previous,
+            item,
+        });
+    }
+
+    pub(crate) fn due(&self) -> impl Iterator<Item = &PendingWrite> {
+        let now = self.clock.now();
+        self.pending
+            .iter()
+            .take_while(move |write| write.due <= now)
+    }
+
+    pub(crate) fn take_due(&mut self) -> Vec<PendingWrite> {
+        let count = self.due().count();
+        self.pending.drain(..count).collect()
+    }
+
+    pub(crate) fn take_all(&mut self) -> Vec<PendingWrite> {
+        self.pending.drain(..).collect()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    // keys with a write still being propagated, due or not
+    pub(crate) fn pending_keys(&self) -> impl Iterator<Item = &PrimaryKey> {
+        self.pending.iter().map(|write| &write.key)
+    }
+
+    // the version eventually consistent readers still see, if it differs from the current one
+    pub(crate) fn stale_version(&self, key: &PrimaryKey) -> Option<Option<&Item>> {
+        let now = self.clock.now();
+        self.pending
+            .iter()
+            .find(|write| write.due > now && &write.key == key)
+            .map(|write| write.previous.as_ref())
+    }
+
+    // overlays the stale versions onto the current items
+    pub(crate) fn stale_view(
+        &self,
+        current: impl Iterator<Item = (PrimaryKey, Item)>,
+    ) -> Vec<(PrimaryKey, Item)> {
+        let now = self.clock.now();
+        let mut stale: HashMap<String, &PendingWrite> = HashMap::new();
+        for write in self.pending.iter().filter(|write| write.due > now) {
+            stale.entry(write.key.to_storage_key()).or_insert(write);
+        }
+
+        let mut items: Vec<_> = current
+            .filter(|(pk, _)| !stale.contains_key(&pk.to_storage_key()))
+            .collect();
+        items.extend(
+            stale
+                .into_values()
+                .filter_map(|write| Some((write.key.clone(), write.previous.clone()?))),
+        );
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulation(clock: &ManualClock) -> EventualConsistency {
+        EventualConsistency::new(Duration::from_millis(100)).with_clock(clock.clone())
+    }
+
+    #[test]
+    fn manual_clock_clones_share_time() {
+        let clock = ManualClock::new();
+        let other = clock.clone();
+        clock.advance(Duration::from_millis(5));
+        other.advance(Duration::from_millis(5));
+        assert_eq!(clock.now(), Duration::from_millis(10));
+
+        clock.set(Duration::from_secs(1));
+        assert_eq!(other.now(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn writes_come_due_after_the_delay() {
+        let clock = ManualClock::new();
+        let mut sim = simulation(&clock);
+        sim.record(PrimaryKey::simple("a"), None, Some(Item::new()));
+        clock.advance(Duration::from_millis(50));
+        sim.record(PrimaryKey::simple("b"), None, Some(Item::new()));
+
+        assert_eq!(sim.due().count(), 0);
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(sim.take_due().len(), 1);
+        assert_eq!(sim.pending_count(), 1);
+
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(sim.take_due().len(), 1);
+        assert_eq!(sim.pending_count(), 0);
+    }
+
+    #[test]
+    fn stale_version_is_the_oldest_pending_one() {
+        let clock = ManualClock::new();
+        let mut sim = simulation(&clock);
+        let key = PrimaryKey::simple("a");
+        let v1 = Item::new().with_s("pk", "a").with_n("v", 1);
+        let v2 = Item::new().with_s("pk", "a").with_n("v", 2);
+
+        assert_eq!(sim.stale_version(&key), None);
+        sim.record(key.clone(), None, Some(v1.clone()));
+        clock.advance(Duration::from_millis(60));
+        sim.record(key.clone(), Some(v1.clone()), Some(v2.clone()));
+
+        assert_eq!(sim.stale_version(&key), Some(None));
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(sim.stale_version(&key), Some(Some(&v1)));
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(sim.stale_version(&key), None);
+    }
+
+    #[test]
+    fn stale_view_restores_deleted_and_hides_new_items() {
+        let clock = ManualClock::new();
+        let mut sim = simulation(&clock);
+        let old = Item::new().with_s("pk", "old");
+        let new = Item::new().with_s("pk", "new");
+        sim.record(PrimaryKey::simple("old"), Some(old.clone()), None);
+        sim.record(PrimaryKey::simple("new"), None, Some(new.clone()));
+
+        let view = sim.stale_view(std::iter::once((PrimaryKey::simple("new"), new.clone())));
+        assert_eq!(view, vec![(PrimaryKey::simple("old"), old)]);
+
+        clock.advance(Duration::from_millis(100));
+        let view = sim.stale_view(std::iter::once((PrimaryKey::simple("new"), new.clone())));
+        assert_eq!(view, vec![(PrimaryKey::simple("new"), new)]);
+    }
+}
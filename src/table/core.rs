@@ -1,5 +1,7 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet, VecDeque};
 
+use super::consistency::{EventualConsistency, PendingWrite};
+use super::description::TableDescription;
 use super::request::{
     DeleteRequest, GetRequest, PutRequest, QueryRequest, ScanRequest, UpdateRequest,
 };
@@ -9,8 +11,15 @@ use crate::batch::{
 };
 use crate::condition::{Condition, evaluate};
 use crate::error::{TableError, TableResult, TransactionCancelReason};
-use crate::index::{GlobalSecondaryIndex, GsiBuilder, LocalSecondaryIndex, LsiBuilder};
-use crate::query::{KeyCondition, QueryExecutor, QueryOptions, QueryResult};
+use crate::geo::{GeoIndex, GeoIndexBuilder};
+use crate::index::{
+    GlobalSecondaryIndex, GsiBuilder, GsiView, IndexStatus, KeyIndex, KeyIndexes,
+    LocalSecondaryIndex, LsiBuilder, Projection,
+};
+use crate::query::{
+    KeyCondition, QueryCursor, QueryExecutor, QueryOptions, QueryPage, QueryResult, Select,
+};
+use crate::search::{TextIndex, TextIndexBuilder};
 use crate::storage::{MemoryStorage, Storage};
 use crate::transaction::{
     TransactGetRequest, TransactGetResult, TransactWriteItem, TransactWriteRequest,
@@ -18,9 +27,10 @@ use crate::transaction::{
 };
 use crate::types::{
     AttributeValue, Item, KeySchema, KeyValidationError, PrimaryKey, ReturnValue, WriteResult,
-    decode, encode,
+    decode, encode, encode_key_component,
 };
 use crate::update::{UpdateExecutor, UpdateExpression};
+use crate::vector::{VectorIndex, VectorIndexBuilder};
 
 #[derive(Debug)]
 pub struct Table {
@@ -29,6 +39,14 @@ pub struct Table {
     storage: MemoryStorage,
     gsis: BTreeMap<String, GlobalSecondaryIndex>,
     lsis: BTreeMap<String, LocalSecondaryIndex>,
+    // text, geo and vector indexes
+    key_indexes: KeyIndexes,
+    // storage keys still to be indexed, per CREATING gsi
+    backfills: BTreeMap<String, VecDeque<String>>,
+    // set while a batch or transaction that was checked as a whole is being applied
+    defer_unique_checks: bool,
+    // when set, gsi updates and eventually consistent reads lag behind writes
+    consistency: Option<EventualConsistency>,
 }
 
 impl Table {
@@ -39,6 +57,10 @@ impl Table {
             storage: MemoryStorage::new(),
             gsis: BTreeMap::new(),
             lsis: BTreeMap::new(),
+            key_indexes: KeyIndexes::new(),
+            backfills: BTreeMap::new(),
+            defer_unique_checks: false,
+            consistency: None,
         }
     }
 
@@ -54,6 +76,16 @@ impl Table {
     pub fn is_empty(&self) -> bool {
         self.storage.is_empty()
     }
+    pub fn describe(&self) -> TableDescription {
+        TableDescription {
+            name: self.name.clone(),
+            key_schema: self.schema.clone(),
+            item_count: self.storage.len(),
+            size_bytes: self.storage.total_value_bytes(),
+            global_secondary_indexes: self.gsis.values().map(|gsi| gsi.describe()).collect(),
+            local_secondary_indexes: self.lsis.values().map(|lsi| lsi.describe()).collect(),
+        }
+    }
     pub fn clear(&mut self) {
         self.storage.clear();
         for gsi in self.gsis.values_mut() {
@@ -62,19 +94,131 @@ impl Table {
         for lsi in self.lsis.values_mut() {
             lsi.clear();
         }
+        self.key_indexes.clear();
+        if let Some(consistency) = self.consistency.as_mut() {
+            consistency.clear();
+        }
+        // nothing left to backfill
+        for name in std::mem::take(&mut self.backfills).into_keys() {
+            if let Some(gsi) = self.gsis.get_mut(&name) {
+                gsi.set_status(IndexStatus::Active);
+            }
+        }
     }
 
     // index management
-    pub fn add_gsi(&mut self, builder: GsiBuilder) {
-        let gsi = builder.build(self.schema.clone());
+    // fails for a unique index when existing items already share an index key
+    pub fn add_gsi(&mut self, builder: GsiBuilder) -> TableResult<()> {
+        let mut gsi = builder.build(self.schema.clone())?;
         let name = gsi.name().to_string();
 
-        let mut gsi = gsi;
         for (pk, item) in self.iter_with_keys() {
             gsi.put(pk, &item);
         }
+        if gsi.is_unique() {
+            let duplicates: Vec<PrimaryKey> = gsi.duplicate_keys().into_iter().flatten().collect();
+            if !duplicates.is_empty() {
+                return Err(TableError::unique_violation(name, duplicates));
+            }
+        }
+
+        self.backfills.remove(&name);
+        self.gsis.insert(name, gsi);
+        Ok(())
+    }
+
+    // registers the index as CREATING; existing items are indexed by backfill_step.
+    // unique indexes are built up front so duplicates are caught before the index is used
+    pub fn add_gsi_online(&mut self, builder: GsiBuilder) -> TableResult<()> {
+        if builder.is_unique() {
+            return self.add_gsi(builder);
+        }
+
+        let mut gsi = builder.build(self.schema.clone())?;
+        let name = gsi.name().to_string();
+
+        let pending: VecDeque<String> = self.storage.keys().map(String::from).collect();
+        if pending.is_empty() {
+            self.backfills.remove(&name);
+        } else {
+            gsi.set_status(IndexStatus::Creating);
+            self.backfills.insert(name.clone(), pending);
+        }
 
         self.gsis.insert(name, gsi);
+        Ok(())
+    }
+
+    // indexes up to max_items pending items and returns how many were processed.
+    // writes that land mid-backfill go straight to the index; re-reading each
+    // pending key from storage keeps that safe
+    pub fn backfill_step(&mut self, max_items: usize) -> TableResult<usize> {
+        let mut processed = 0;
+        let names: Vec<String> = self.backfills.keys().cloned().collect();
+
+        for name in names {
+            while processed < max_items {
+                let Some(storage_key) = self
+                    .backfills
+                    .get_mut(&name)
+                    .and_then(|pending| pending.pop_front())
+                else {
+                    break;
+                };
+                processed += 1;
+
+                let Some(item) = self.get_item_by_storage_key(&storage_key)? else {
+                    continue; // deleted since the index was created
+                };
+                if let Some(pk) = item.extract_key(&self.schema)
+                    && let Some(gsi) = self.gsis.get_mut(&name)
+                {
+                    gsi.put(pk, &item);
+                }
+            }
+
+            if self.backfills.get(&name).is_some_and(|p| p.is_empty()) {
+                self.backfills.remove(&name);
+                if let Some(gsi) = self.gsis.get_mut(&name) {
+                    gsi.set_status(IndexStatus::Active);
+                }
+            }
+            if processed >= max_items {
+                break;
+            }
+        }
+
+        Ok(processed)
+    }
+
+    pub fn is_backfilling(&self) -> bool {
+        !self.backfills.is_empty()
+    }
+
+    pub fn drop_gsi(&mut self, name: &str) -> TableResult<()> {
+        self.backfills.remove(name);
+        self.gsis
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| TableError::index_not_found(name))
+    }
+
+    pub fn update_gsi_projection(&mut self, name: &str, projection: Projection) -> TableResult<()> {
+        let mut gsi = self
+            .gsis
+            .remove(name)
+            .ok_or_else(|| TableError::index_not_found(name))?;
+
+        gsi.reset_projection(projection);
+        for (pk, item) in self.iter_with_keys() {
+            gsi.put(pk, &item);
+        }
+        // fully rebuilt, so any pending backfill is moot
+        self.backfills.remove(name);
+        gsi.set_status(IndexStatus::Active);
+
+        self.gsis.insert(name.to_string(), gsi);
+        Ok(())
     }
 
     pub fn gsi(&self, name: &str) -> Option<&GlobalSecondaryIndex> {
@@ -85,6 +229,36 @@ impl Table {
         self.gsis.keys().map(|s| s.as_str())
     }
 
+    pub(crate) fn unique_gsis(&self) -> impl Iterator<Item = &GlobalSecondaryIndex> {
+        self.gsis.values().filter(|gsi| gsi.is_unique())
+    }
+
+    // eventual consistency simulation
+    pub fn simulate_eventual_consistency(&mut self, consistency: EventualConsistency) {
+        self.propagate();
+        self.consistency = Some(consistency);
+    }
+
+    // catches everything up before going back to immediate consistency
+    pub fn disable_eventual_consistency(&mut self) {
+        self.propagate();
+        self.consistency = None;
+    }
+
+    pub fn eventual_consistency(&self) -> Option<&EventualConsistency> {
+        self.consistency.as_ref()
+    }
+
+    // applies every pending write, due or not, and returns how many there were
+    pub fn propagate(&mut self) -> usize {
+        let writes = match self.consistency.as_mut() {
+            Some(consistency) => consistency.take_all(),
+            None => return 0,
+        };
+        self.apply_pending_writes(&writes);
+        writes.len()
+    }
+
     pub fn add_lsi(&mut self, builder: LsiBuilder) {
         let lsi = builder.build(self.schema.clone());
         let name = lsi.name().to_string();
@@ -105,6 +279,87 @@ impl Table {
         self.lsis.keys().map(|s| s.as_str())
     }
 
+    pub fn add_text_index(&mut self, builder: TextIndexBuilder) {
+        self.add_key_index(builder.build());
+    }
+
+    pub fn drop_text_index(&mut self, name: &str) -> TableResult<()> {
+        self.drop_key_index::<TextIndex>(name)
+    }
+
+    pub fn text_index(&self, name: &str) -> Option<&TextIndex> {
+        self.key_indexes.get(name)
+    }
+
+    pub fn text_index_names(&self) -> impl Iterator<Item = &str> {
+        self.key_indexes.names::<TextIndex>()
+    }
+
+    pub fn add_geo_index(&mut self, builder: GeoIndexBuilder) -> TableResult<()> {
+        self.add_key_index(builder.build()?);
+        Ok(())
+    }
+
+    pub fn drop_geo_index(&mut self, name: &str) -> TableResult<()> {
+        self.drop_key_index::<GeoIndex>(name)
+    }
+
+    pub fn geo_index(&self, name: &str) -> Option<&GeoIndex> {
+        self.key_indexes.get(name)
+    }
+
+    pub fn geo_index_names(&self) -> impl Iterator<Item = &str> {
+        self.key_indexes.names::<GeoIndex>()
+    }
+
+    pub fn add_vector_index(&mut self, builder: VectorIndexBuilder) -> TableResult<()> {
+        self.add_key_index(builder.build()?);
+        Ok(())
+    }
+
+    pub fn drop_vector_index(&mut self, name: &str) -> TableResult<()> {
+        self.drop_key_index::<VectorIndex>(name)
+    }
+
+    pub fn vector_index(&self, name: &str) -> Option<&VectorIndex> {
+        self.key_indexes.get(name)
+    }
+
+    pub fn vector_index_names(&self) -> impl Iterator<Item = &str> {
+        self.key_indexes.names::<VectorIndex>()
+    }
+
+    // replaces any index of the same kind and name
+    fn add_key_index(&mut self, mut index: impl KeyIndex) {
+        for (pk, item) in self.iter_with_keys() {
+            index.put(&pk, &item);
+        }
+        self.key_indexes.insert(index);
+    }
+
+    fn drop_key_index<T: KeyIndex>(&mut self, name: &str) -> TableResult<()> {
+        if self.key_indexes.remove::<T>(name) {
+            Ok(())
+        } else {
+            Err(TableError::index_not_found(name))
+        }
+    }
+
+    // key indexes hold table keys only; keys whose item is gone are skipped
+    pub(crate) fn key_index_hits<D, H>(
+        &self,
+        found: impl IntoIterator<Item = (PrimaryKey, D)>,
+        hit: impl Fn(PrimaryKey, Item, D) -> H,
+    ) -> TableResult<Vec<H>> {
+        let mut hits = Vec::new();
+        for (key, data) in found {
+            if let Some(item) = self.get_item(&key)? {
+                hits.push(hit(key, item, data));
+            }
+        }
+        Ok(hits)
+    }
+
     // public API operations
     pub fn put(&mut self, request: impl Into<PutRequest>) -> TableResult<WriteResult> {
         let request = request.into();
@@ -118,6 +373,15 @@ impl Table {
 
     pub fn get(&self, request: impl Into<GetRequest>) -> TableResult<Option<Item>> {
         let request = request.into();
+        if !request.consistent_read
+            && let Some(stale) = self
+                .consistency
+                .as_ref()
+                .and_then(|consistency| consistency.stale_version(&request.key))
+        {
+            return Ok(stale.cloned());
+        }
+
         let storage_key = request.key.to_storage_key();
         let item = self.get_item_by_storage_key(&storage_key)?;
 
@@ -145,7 +409,13 @@ impl Table {
 
     pub fn query(&mut self, request: impl Into<QueryRequest>) -> TableResult<QueryResult> {
         let request = request.into();
-        self.query_internal(request.key_condition, request.filter, request.options)
+        self.query_internal(
+            request.key_condition,
+            request.filter,
+            request.options,
+            request.select,
+            request.consistent_read,
+        )
     }
 
     pub fn query_gsi(
@@ -154,23 +424,22 @@ impl Table {
         request: impl Into<QueryRequest>,
     ) -> TableResult<QueryResult> {
         let request = request.into();
-        let gsi = self
-            .gsis
-            .get(index_name)
-            .ok_or_else(|| TableError::index_not_found(index_name))?;
-
-        let mut result = gsi.query_with_options(request.key_condition, request.options)?;
+        // gsis only offer eventually consistent reads, whatever the request asks for
+        let gsi = self.gsi_view(index_name)?;
+        if !gsi.is_active() {
+            return Err(TableError::index_not_active(index_name));
+        }
 
-        if let Some(filter) = request.filter {
-            let filtered: Vec<Item> = result
-                .items
-                .into_iter()
-                .filter(|item| evaluate(&filter, item).unwrap_or(false))
-                .collect();
-            result.count = filtered.len();
-            result.items = filtered;
+        if request.select.is_count() && request.options.limit.is_none() {
+            return gsi.count(&request.key_condition, request.filter.as_ref());
         }
-        Ok(result)
+
+        let result = gsi.query_with_options(request.key_condition, request.options)?;
+        Ok(Self::finish_query(
+            result,
+            request.filter.as_ref(),
+            &request.select,
+        ))
     }
 
     pub fn query_lsi(
@@ -184,28 +453,33 @@ impl Table {
             .get(index_name)
             .ok_or_else(|| TableError::index_not_found(index_name))?;
 
-        let mut result = lsi.query_with_options(request.key_condition, request.options)?;
-
-        if let Some(filter) = request.filter {
-            let filtered: Vec<Item> = result
-                .items
-                .into_iter()
-                .filter(|item| evaluate(&filter, item).unwrap_or(false))
-                .collect();
-            result.count = filtered.len();
-            result.items = filtered;
+        if request.select.is_count() && request.options.limit.is_none() {
+            return lsi.count(&request.key_condition, request.filter.as_ref());
         }
-        Ok(result)
+
+        let result = lsi.query_with_options(request.key_condition, request.options)?;
+        Ok(Self::finish_query(
+            result,
+            request.filter.as_ref(),
+            &request.select,
+        ))
     }
 
     pub fn scan(&self, request: ScanRequest) -> TableResult<Vec<Item>> {
-        let mut items = Vec::new();
+        self.scan_with_counts(request).map(|result| result.items)
+    }
+
+    pub fn scan_with_counts(&self, request: ScanRequest) -> TableResult<QueryResult> {
+        Self::validate_table_select(&request.select)?;
+
+        let mut result = QueryResult::empty();
         let limit = request.limit.unwrap_or(usize::MAX);
 
         for (_, item) in self.iter_with_keys() {
-            if items.len() >= limit {
+            if result.count >= limit {
                 break;
             }
+            result.scanned_count += 1;
 
             if let Some(ref filter) = request.filter
                 && !evaluate(filter, &item).unwrap_or(false)
@@ -213,10 +487,13 @@ impl Table {
                 continue;
             }
 
-            items.push(item);
+            result.count += 1;
+            if !request.select.is_count() {
+                result.items.push(request.select.apply(item));
+            }
         }
 
-        Ok(items)
+        Ok(result)
     }
 
     // convenience methods
@@ -261,13 +538,15 @@ impl Table {
         if let Err(failure) = validation {
             return Err(self.convert_failure_to_error(failure));
         }
+        self.check_unique_transaction(&request.items)?;
 
         // apply all operations
-        for item in request.items {
-            self.apply_transact_write_item(item)?;
-        }
-
-        Ok(())
+        self.with_deferred_unique_checks(|table| {
+            for item in request.items {
+                table.apply_transact_write_item(item)?;
+            }
+            Ok(())
+        })
     }
 
     pub fn transact_get(
@@ -298,11 +577,24 @@ impl Table {
             }
         }
 
+        // puts land before deletes
+        let writes: Vec<(PrimaryKey, Option<&Item>)> = puts
+            .iter()
+            .filter_map(|item| Some((item.extract_key(&self.schema)?, Some(item))))
+            .chain(deletes.iter().map(|key| (key.clone(), None)))
+            .collect();
+        if let Some(conflict) = self.find_unique_conflict(&writes) {
+            return Err(conflict.into());
+        }
+
         let schema = self.schema.clone();
         let executor = BatchExecutor::new();
-        let mut write_result = executor.execute_put(puts, &schema, |item| self.put_item(item))?;
-        let delete_result =
-            executor.execute_delete(deletes, |key| self.delete_item(key).map(|_| ()))?;
+        let (mut write_result, delete_result) = self.with_deferred_unique_checks(|table| {
+            let puts = executor.execute_put(puts, &schema, |item| table.put_item(item))?;
+            let deletes =
+                executor.execute_delete(deletes, |key| table.delete_item(key).map(|_| ()))?;
+            TableResult::Ok((puts, deletes))
+        })?;
 
         // merge results
         write_result.processed_count += delete_result.processed_count;
@@ -360,11 +652,14 @@ impl Table {
                 return Err(TableError::ConditionFailed);
             }
         }
+        if !self.defer_unique_checks {
+            self.check_unique_write(&pk, &item)?;
+        }
 
         let was_update = old_item.is_some();
         let encoded = self.encode_item(&item)?;
         self.storage.put(&storage_key, encoded)?;
-        self.update_indexes_on_put(&pk, &item);
+        self.update_indexes_on_put(&pk, &item, old_item.as_ref());
 
         let attributes = match return_value {
             ReturnValue::None => None,
@@ -395,11 +690,14 @@ impl Table {
         if self.storage.exists(&storage_key)? {
             return Err(TableError::ItemAlreadyExists);
         }
+        if !self.defer_unique_checks {
+            self.check_unique_write(&pk, &item)?;
+        }
 
         let encoded = self.encode_item(&item)?;
 
         self.storage.put(&storage_key, encoded)?;
-        self.update_indexes_on_put(&pk, &item);
+        self.update_indexes_on_put(&pk, &item, None);
 
         let attributes = match return_value {
             ReturnValue::None => None,
@@ -433,8 +731,8 @@ impl Table {
 
         self.storage.delete(&storage_key)?;
 
-        if was_update {
-            self.update_indexes_on_delete(key);
+        if let Some(old_item) = &old_item {
+            self.update_indexes_on_delete(key, old_item);
         }
 
         let attributes = match return_value {
@@ -480,11 +778,14 @@ impl Table {
                 "cannot modify key attributes".to_string(),
             ));
         }
+        if !self.defer_unique_checks {
+            self.check_unique_write(key, &new_item)?;
+        }
 
         // save updated item
         let encoded = self.encode_item(&new_item)?;
         self.storage.put(&storage_key, encoded)?;
-        self.update_indexes_on_put(key, &new_item);
+        self.update_indexes_on_put(key, &new_item, Some(&old_item));
 
         let attributes = match return_value {
             ReturnValue::AllNew => Some(new_item),
@@ -503,24 +804,63 @@ impl Table {
         key_condition: KeyCondition,
         filter: Option<Condition>,
         options: QueryOptions,
+        select: Select,
+        consistent_read: bool,
     ) -> TableResult<QueryResult> {
+        Self::validate_table_select(&select)?;
+
         let executor = QueryExecutor::new(&self.schema);
         executor.validate_condition(&key_condition)?;
 
-        let items = self.iter_with_keys();
-        let mut result = executor.execute(items, &key_condition, &options)?;
+        let items = self.items_for_read(consistent_read);
+        if select.is_count() && options.limit.is_none() {
+            return Ok(executor.count(items, &key_condition, filter.as_ref()));
+        }
+
+        let result = executor.execute(items, &key_condition, &options)?;
+        Ok(Self::finish_query(result, filter.as_ref(), &select))
+    }
+
+    fn validate_table_select(select: &Select) -> TableResult<()> {
+        if matches!(select, Select::AllProjectedAttributes) {
+            return Err(TableError::validation_error(
+                "ALL_PROJECTED_ATTRIBUTES can only be used when querying an index",
+            ));
+        }
+        Ok(())
+    }
 
+    fn finish_query(
+        mut result: QueryResult,
+        filter: Option<&Condition>,
+        select: &Select,
+    ) -> QueryResult {
         if let Some(filter) = filter {
-            let filtered: Vec<Item> = result
+            result
                 .items
-                .into_iter()
-                .filter(|item| evaluate(&filter, item).unwrap_or(false))
-                .collect();
-            result.count = filtered.len();
-            result.items = filtered;
+                .retain(|item| evaluate(filter, item).unwrap_or(false));
+            result.count = result.items.len();
         }
 
-        Ok(result)
+        match select {
+            Select::Count => result.items.clear(),
+            Select::SpecificAttributes(_) => {
+                result.items = result
+                    .items
+                    .into_iter()
+                    .map(|item| select.apply(item))
+                    .collect();
+            }
+            Select::AllAttributes | Select::AllProjectedAttributes => {}
+        }
+        result
+    }
+
+    fn with_deferred_unique_checks<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.defer_unique_checks = true;
+        let result = f(self);
+        self.defer_unique_checks = false;
+        result
     }
 
     fn apply_transact_write_item(&mut self, item: TransactWriteItem) -> TableResult<()> {
@@ -593,7 +933,7 @@ impl Table {
         }
     }
 
-    fn iter_with_keys(&self) -> impl Iterator<Item = (PrimaryKey, Item)> + '_ {
+    pub(crate) fn iter_with_keys(&self) -> impl Iterator<Item = (PrimaryKey, Item)> + '_ {
         self.storage.iter().filter_map(|(_, value)| {
             let item = self.decode_item(value).ok()?;
             let pk = item.extract_key(&self.schema)?;
@@ -601,22 +941,157 @@ impl Table {
         })
     }
 
-    fn update_indexes_on_put(&mut self, pk: &PrimaryKey, item: &Item) {
+    // every stored item, in storage order
+    pub(crate) fn decoded_items(&self) -> impl Iterator<Item = TableResult<Item>> + '_ {
+        self.storage
+            .iter()
+            .map(|(_, value)| self.decode_item(value))
+    }
+
+    // under the simulation, base table reads fall back to the versions still being propagated
+    pub(crate) fn items_for_read(
+        &self,
+        consistent_read: bool,
+    ) -> Box<dyn Iterator<Item = (PrimaryKey, Item)> + '_> {
+        match &self.consistency {
+            Some(consistency) if !consistent_read => {
+                Box::new(consistency.stale_view(self.iter_with_keys()).into_iter())
+            }
+            _ => Box::new(self.iter_with_keys()),
+        }
+    }
+
+    // the next `page_size` matches after `after`, in query order; only the items under the
+    // partition's storage key prefix are read, and only one page of them is held
+    pub(crate) fn query_page(
+        &self,
+        condition: &KeyCondition,
+        scan_forward: bool,
+        consistent_read: bool,
+        after: Option<&QueryCursor>,
+        page_size: usize,
+    ) -> TableResult<QueryPage> {
+        let mut prefix = encode_key_component(&condition.partition_key).into_owned();
+        if self.schema.has_sort_key() {
+            prefix.push('#');
+        }
+        let stale = self.consistency.as_ref().filter(|_| !consistent_read);
+
+        // versions eventual readers still see for items deleted since
+        let mut deleted = Vec::new();
+        if let Some(consistency) = stale {
+            let mut seen = HashSet::new();
+            for pk in consistency.pending_keys() {
+                let storage_key = pk.to_storage_key();
+                if condition.matches(pk)
+                    && seen.insert(storage_key.clone())
+                    && !self.storage.exists(&storage_key)?
+                    && let Some(Some(item)) = consistency.stale_version(pk)
+                {
+                    deleted.push((pk.clone(), item.clone()));
+                }
+            }
+        }
+
+        let mut error = None;
+        let current = self
+            .storage
+            .iter()
+            .filter(|(storage_key, _)| storage_key.starts_with(&prefix))
+            .filter_map(|(_, value)| {
+                let item = match self.decode_item(value) {
+                    Ok(item) => item,
+                    Err(e) => {
+                        error.get_or_insert(e);
+                        return None;
+                    }
+                };
+                let pk = item.extract_key(&self.schema)?;
+                match stale.and_then(|consistency| consistency.stale_version(&pk)) {
+                    Some(previous) => Some((pk, previous?.clone())),
+                    None => Some((pk, item)),
+                }
+            });
+        let page = QueryExecutor::new(&self.schema).execute_page(
+            current.chain(deleted),
+            condition,
+            scan_forward,
+            after,
+            page_size,
+        );
+        match error {
+            Some(e) => Err(e),
+            None => Ok(page),
+        }
+    }
+
+    // the index as readers see it right now; writes that came due since the last write are
+    // laid over it, as applying them needs `&mut self`
+    pub(crate) fn gsi_view(&self, name: &str) -> TableResult<GsiView<'_>> {
+        let gsi = self
+            .gsis
+            .get(name)
+            .ok_or_else(|| TableError::index_not_found(name))?;
+        let overlay = match &self.consistency {
+            Some(consistency) if !gsi.is_unique() => {
+                let mut due = consistency.due().peekable();
+                due.peek()
+                    .is_some()
+                    .then(|| gsi.overlay(due.map(PendingWrite::change)))
+            }
+            _ => None,
+        };
+        Ok(GsiView::new(gsi, overlay))
+    }
+
+    fn apply_pending_writes(&mut self, writes: &[PendingWrite]) {
+        for gsi in self.gsis.values_mut().filter(|gsi| !gsi.is_unique()) {
+            for write in writes {
+                write.apply_to(gsi);
+            }
+        }
+    }
+
+    fn apply_due_writes(&mut self) {
+        if let Some(consistency) = self.consistency.as_mut() {
+            let writes = consistency.take_due();
+            self.apply_pending_writes(&writes);
+        }
+    }
+
+    // unique indexes back a constraint, so they never lag
+    fn update_indexes_on_put(&mut self, pk: &PrimaryKey, item: &Item, previous: Option<&Item>) {
+        self.apply_due_writes();
+        let lagging = self.consistency.is_some();
         for gsi in self.gsis.values_mut() {
-            gsi.put(pk.clone(), item);
+            if !lagging || gsi.is_unique() {
+                gsi.put(pk.clone(), item);
+            }
         }
         for lsi in self.lsis.values_mut() {
             lsi.put(pk, item);
         }
+        self.key_indexes.put(pk, item);
+        if let Some(consistency) = self.consistency.as_mut() {
+            consistency.record(pk.clone(), previous.cloned(), Some(item.clone()));
+        }
     }
 
-    fn update_indexes_on_delete(&mut self, pk: &PrimaryKey) {
+    fn update_indexes_on_delete(&mut self, pk: &PrimaryKey, previous: &Item) {
+        self.apply_due_writes();
+        let lagging = self.consistency.is_some();
         for gsi in self.gsis.values_mut() {
-            gsi.delete(pk);
+            if !lagging || gsi.is_unique() {
+                gsi.delete(pk);
+            }
         }
         for lsi in self.lsis.values_mut() {
             lsi.delete(pk);
         }
+        self.key_indexes.delete(pk);
+        if let Some(consistency) = self.consistency.as_mut() {
+            consistency.record(pk.clone(), Some(previous.clone()), None);
+        }
     }
 }
 
@@ -626,6 +1101,9 @@ pub struct TableBuilder {
     initial_capacity: Option<usize>,
     gsi_builders: Vec<GsiBuilder>,
     lsi_builders: Vec<LsiBuilder>,
+    // holds the first index that failed to build
+    key_indexes: TableResult<KeyIndexes>,
+    consistency: Option<EventualConsistency>,
 }
 
 impl TableBuilder {
@@ -636,6 +1114,8 @@ impl TableBuilder {
             initial_capacity: None,
             gsi_builders: Vec::new(),
             lsi_builders: Vec::new(),
+            key_indexes: Ok(KeyIndexes::new()),
+            consistency: None,
         }
     }
 
@@ -654,18 +1134,46 @@ impl TableBuilder {
         self
     }
 
-    pub fn build(self) -> Table {
+    pub fn with_text_index(self, builder: TextIndexBuilder) -> Self {
+        self.with_key_index(Ok(builder.build()))
+    }
+
+    pub fn with_geo_index(self, builder: GeoIndexBuilder) -> Self {
+        self.with_key_index(builder.build())
+    }
+
+    pub fn with_vector_index(self, builder: VectorIndexBuilder) -> Self {
+        self.with_key_index(builder.build())
+    }
+
+    fn with_key_index(mut self, index: TableResult<impl KeyIndex>) -> Self {
+        self.key_indexes = self.key_indexes.and_then(|mut indexes| {
+            indexes.insert(index?);
+            Ok(indexes)
+        });
+        self
+    }
+
+    pub fn with_eventual_consistency(mut self, consistency: EventualConsistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    pub fn build(self) -> TableResult<Table> {
         let mut table = Table::new(self.name, self.schema);
         if let Some(cap) = self.initial_capacity {
             table.storage = MemoryStorage::with_capacity(cap);
         }
         for gsi_builder in self.gsi_builders {
-            table.add_gsi(gsi_builder);
+            table.add_gsi(gsi_builder)?;
         }
         for lsi_builder in self.lsi_builders {
             table.add_lsi(lsi_builder);
         }
-        table
+        // the table is empty, so there is nothing to backfill
+        table.key_indexes = self.key_indexes?;
+        table.consistency = self.consistency;
+        Ok(table)
     }
 }
 
@@ -747,6 +1255,66 @@ mod tests {
         }
     }
 
+    mod describe {
+        use super::*;
+        use crate::index::Projection;
+
+        #[test]
+        fn empty_table() {
+            let table = simple_table();
+            let description = table.describe();
+            assert_eq!(description.name, "users");
+            assert_eq!(description.key_schema.pk_name(), "user_id");
+            assert_eq!(description.item_count, 0);
+            assert_eq!(description.size_bytes, 0);
+            assert!(description.global_secondary_indexes.is_empty());
+            assert!(description.local_secondary_indexes.is_empty());
+        }
+
+        #[test]
+        fn counts_items_and_indexes() {
+            let mut table = TableBuilder::new(
+                "orders",
+                KeySchema::composite("user_id", KeyType::S, "order_id", KeyType::S),
+            )
+            .with_gsi(
+                GsiBuilder::new("by-status", KeySchema::simple("status", KeyType::S)).keys_only(),
+            )
+            .with_lsi(LsiBuilder::new("by-date", "date", KeyType::S))
+            .build()
+            .unwrap();
+
+            for i in 0..4 {
+                let mut item = Item::new()
+                    .with_s("user_id", "user1")
+                    .with_s("order_id", format!("order{}", i))
+                    .with_s("status", "open")
+                    .with_s("notes", "x".repeat(100));
+                if i % 2 == 0 {
+                    item = item.with_s("date", format!("2026-01-0{}", i + 1));
+                }
+                table.put_item(item).unwrap();
+            }
+
+            let description = table.describe();
+            assert_eq!(description.item_count, 4);
+            assert!(description.size_bytes > 400);
+
+            let gsi = description.gsi("by-status").unwrap();
+            assert_eq!(gsi.item_count, 4);
+            assert_eq!(gsi.key_schema.pk_name(), "status");
+            assert_eq!(gsi.projection, Projection::KeysOnly);
+            assert_eq!(gsi.status, IndexStatus::Active);
+            // keys-only entries are smaller than the base items
+            assert!(gsi.size_bytes > 0 && gsi.size_bytes < description.size_bytes);
+
+            let lsi = description.lsi("by-date").unwrap();
+            assert_eq!(lsi.item_count, 2);
+            assert_eq!(lsi.key_schema.sk_name(), Some("date"));
+            assert!(description.gsi("missing").is_none());
+        }
+    }
+
     mod iter_with_keys {
         use super::*;
 
@@ -774,4 +1342,67 @@ mod tests {
             assert!(items.is_empty());
         }
     }
+
+    mod corrupt_items {
+        use super::*;
+        use crate::aggregate::Aggregation;
+
+        #[test]
+        fn iterators_yield_errors() {
+            let mut table = composite_table();
+            for order in ["o1", "o2", "o3"] {
+                table
+                    .put_item(
+                        Item::new()
+                            .with_s("user_id", "u1")
+                            .with_s("order_id", order),
+                    )
+                    .unwrap();
+            }
+            let key = PrimaryKey::composite("u1", "o2");
+            table
+                .storage
+                .put(&key.to_storage_key(), vec![0xff])
+                .unwrap();
+
+            let results: Vec<_> = table
+                .query_iter(KeyCondition::pk("u1"))
+                .unwrap()
+                .page_size(1)
+                .collect();
+            assert_eq!(results.len(), 1);
+            assert!(results[0].is_err());
+
+            let results: Vec<_> = table.scan_iter(ScanRequest::new()).unwrap().collect();
+            assert_eq!(results.len(), 3);
+            assert_eq!(results.iter().filter(|item| item.is_err()).count(), 1);
+        }
+
+        #[test]
+        fn aggregations_fail() {
+            let mut table = composite_table();
+            for order in ["o1", "o2"] {
+                table
+                    .put_item(
+                        Item::new()
+                            .with_s("user_id", "u1")
+                            .with_s("order_id", order),
+                    )
+                    .unwrap();
+            }
+            let key = PrimaryKey::composite("u1", "o2");
+            table
+                .storage
+                .put(&key.to_storage_key(), vec![0xff])
+                .unwrap();
+
+            let count = Aggregation::new().count();
+            assert!(
+                table
+                    .aggregate_query(KeyCondition::pk("u1"), &count)
+                    .is_err()
+            );
+            assert!(table.aggregate_scan(ScanRequest::new(), &count).is_err());
+        }
+    }
 }
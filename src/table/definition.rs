@@ -0,0 +1,573 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::core::TableBuilder;
+use crate::error::{TableError, TableResult};
+use crate::index::{GsiBuilder, LsiBuilder, MAX_KEY_ATTRIBUTES, Projection};
+use crate::json::{JsonValue, parse};
+use crate::types::{KeyAttribute, KeySchema, KeyType};
+
+impl TableBuilder {
+    // CreateTable request shape; billing and throughput settings are ignored
+    pub fn from_json(input: &str) -> TableResult<Self> {
+        Self::from_json_value(&parse(input)?)
+    }
+
+    pub fn from_json_value(value: &JsonValue) -> TableResult<Self> {
+        let root = object(value, "table definition")?;
+        let name = required_str(root, "TableName", "table definition")?;
+
+        let definitions = attribute_definitions(root)?;
+        let mut used = BTreeSet::new();
+
+        let schema = key_schema(
+            required(root, "KeySchema", "table definition")?,
+            "KeySchema",
+            &definitions,
+            &mut used,
+        )?;
+
+        let mut builder = TableBuilder::new(name, schema.clone());
+        let mut index_names = BTreeSet::new();
+
+        for (i, index) in optional_array(root, "GlobalSecondaryIndexes")?
+            .iter()
+            .enumerate()
+        {
+            let context = format!("GlobalSecondaryIndexes[{}]", i);
+            let index = object(index, &context)?;
+            let index_name = index_name(index, &context, &mut index_names)?;
+            let context = format!("GlobalSecondaryIndexes[{}] ({})", i, index_name);
+
+            let gsi = gsi_builder(
+                index_name,
+                required(index, "KeySchema", &context)?,
+                &format!("{}.KeySchema", context),
+                &definitions,
+                &mut used,
+            )?;
+            let projection = projection(index, &context)?;
+            builder = builder.with_gsi(gsi.projection(projection));
+        }
+
+        for (i, index) in optional_array(root, "LocalSecondaryIndexes")?
+            .iter()
+            .enumerate()
+        {
+            let context = format!("LocalSecondaryIndexes[{}]", i);
+            let index = object(index, &context)?;
+            let index_name = index_name(index, &context, &mut index_names)?;
+            let context = format!("LocalSecondaryIndexes[{}] ({})", i, index_name);
+
+            if !schema.has_sort_key() {
+                return Err(invalid(format!(
+                    "{}: local secondary indexes require a table with a sort key",
+                    context
+                )));
+            }
+
+            let index_schema = key_schema(
+                required(index, "KeySchema", &context)?,
+                &format!("{}.KeySchema", context),
+                &definitions,
+                &mut used,
+            )?;
+            if index_schema.pk_name() != schema.pk_name() {
+                return Err(invalid(format!(
+                    "{}: HASH key must be the table partition key '{}', found '{}'",
+                    context,
+                    schema.pk_name(),
+                    index_schema.pk_name()
+                )));
+            }
+            let Some(sort_key) = index_schema.sort_key else {
+                return Err(invalid(format!(
+                    "{}: KeySchema requires a RANGE key",
+                    context
+                )));
+            };
+
+            let projection = projection(index, &context)?;
+            builder = builder.with_lsi(
+                LsiBuilder::new(index_name, sort_key.name, sort_key.key_type)
+                    .projection(projection),
+            );
+        }
+
+        if let Some(unused) = definitions.keys().find(|name| !used.contains(*name)) {
+            return Err(invalid(format!(
+                "AttributeDefinitions: '{}' is defined but not used in any key schema",
+                unused
+            )));
+        }
+
+        Ok(builder)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> TableError {
+    TableError::validation_error(format!("invalid table definition: {}", message.into()))
+}
+
+fn object<'a>(value: &'a JsonValue, context: &str) -> TableResult<&'a BTreeMap<String, JsonValue>> {
+    value.as_object().ok_or_else(|| {
+        invalid(format!(
+            "{}: expected object, found {}",
+            context,
+            value.type_name()
+        ))
+    })
+}
+
+fn required<'a>(
+    map: &'a BTreeMap<String, JsonValue>,
+    field: &str,
+    context: &str,
+) -> TableResult<&'a JsonValue> {
+    map.get(field)
+        .ok_or_else(|| invalid(format!("{}: missing {}", context, field)))
+}
+
+fn required_str<'a>(
+    map: &'a BTreeMap<String, JsonValue>,
+    field: &str,
+    context: &str,
+) -> TableResult<&'a str> {
+    let value = required(map, field, context)?;
+    value.as_str().ok_or_else(|| {
+        invalid(format!(
+            "{}: {} must be a string, found {}",
+            context,
+            field,
+            value.type_name()
+        ))
+    })
+}
+
+fn array<'a>(value: &'a JsonValue, context: &str) -> TableResult<&'a Vec<JsonValue>> {
+    value.as_array().ok_or_else(|| {
+        invalid(format!(
+            "{}: expected array, found {}",
+            context,
+            value.type_name()
+        ))
+    })
+}
+
+fn optional_array<'a>(
+    map: &'a BTreeMap<String, JsonValue>,
+    field: &str,
+) -> TableResult<&'a [JsonValue]> {
+    match map.get(field) {
+        Some(value) => array(value, field).map(Vec::as_slice),
+        None => Ok(&[]),
+    }
+}
+
+fn attribute_definitions(
+    root: &BTreeMap<String, JsonValue>,
+) -> TableResult<BTreeMap<String, KeyType>> {
+    let entries = array(
+        required(root, "AttributeDefinitions", "table definition")?,
+        "AttributeDefinitions",
+    )?;
+
+    let mut definitions = BTreeMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let context = format!("AttributeDefinitions[{}]", i);
+        let entry = object(entry, &context)?;
+        let name = required_str(entry, "AttributeName", &context)?;
+        let key_type = match required_str(entry, "AttributeType", &context)? {
+            "S" => KeyType::S,
+            "N" => KeyType::N,
+            "B" => KeyType::B,
+            other => {
+                return Err(invalid(format!(
+                    "{}: AttributeType must be S, N or B, found '{}'",
+                    context, other
+                )));
+            }
+        };
+        if definitions.insert(name.to_string(), key_type).is_some() {
+            return Err(invalid(format!(
+                "{}: attribute '{}' is defined more than once",
+                context, name
+            )));
+        }
+    }
+
+    Ok(definitions)
+}
+
+fn key_schema(
+    value: &JsonValue,
+    context: &str,
+    definitions: &BTreeMap<String, KeyType>,
+    used: &mut BTreeSet<String>,
+) -> TableResult<KeySchema> {
+    let (hash, range) = key_attributes(value, context, definitions, used, 1)?;
+    let pk = &hash[0];
+    Ok(match range.first() {
+        Some(sk) => KeySchema::composite(&pk.name, pk.key_type, &sk.name, sk.key_type),
+        None => KeySchema::simple(&pk.name, pk.key_type),
+    })
+}
+
+// GSIs may list up to MAX_KEY_ATTRIBUTES HASH entries followed by as many RANGE entries
+fn gsi_builder(
+    name: &str,
+    value: &JsonValue,
+    context: &str,
+    definitions: &BTreeMap<String, KeyType>,
+    used: &mut BTreeSet<String>,
+) -> TableResult<GsiBuilder> {
+    let (hash, range) = key_attributes(value, context, definitions, used, MAX_KEY_ATTRIBUTES)?;
+    let pk = &hash[0];
+    let mut builder = GsiBuilder::new(name, KeySchema::simple(&pk.name, pk.key_type));
+    for attribute in &hash[1..] {
+        builder = builder.partition_key_attribute(&attribute.name, attribute.key_type);
+    }
+    for attribute in &range {
+        builder = builder.sort_key_attribute(&attribute.name, attribute.key_type);
+    }
+    Ok(builder)
+}
+
+fn key_attributes(
+    value: &JsonValue,
+    context: &str,
+    definitions: &BTreeMap<String, KeyType>,
+    used: &mut BTreeSet<String>,
+    max_per_role: usize,
+) -> TableResult<(Vec<KeyAttribute>, Vec<KeyAttribute>)> {
+    let entries = array(value, context)?;
+    if entries.is_empty() || entries.len() > 2 * max_per_role {
+        return Err(invalid(format!(
+            "{}: expected {} elements, found {}",
+            context,
+            if max_per_role == 1 {
+                "1 or 2".to_string()
+            } else {
+                format!("1 to {}", 2 * max_per_role)
+            },
+            entries.len()
+        )));
+    }
+
+    let mut hash: Vec<KeyAttribute> = Vec::new();
+    let mut range: Vec<KeyAttribute> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let entry_context = format!("{}[{}]", context, i);
+        let entry = object(entry, &entry_context)?;
+        let name = required_str(entry, "AttributeName", &entry_context)?;
+        let role = required_str(entry, "KeyType", &entry_context)?;
+
+        let expected = if hash.is_empty() {
+            "HASH"
+        } else if !range.is_empty() || hash.len() == max_per_role {
+            "RANGE"
+        } else {
+            "HASH or RANGE"
+        };
+        if !expected.split(" or ").any(|allowed| allowed == role) {
+            return Err(invalid(format!(
+                "{}: KeyType must be {}, found '{}'",
+                entry_context, expected, role
+            )));
+        }
+
+        let Some(key_type) = definitions.get(name) else {
+            return Err(invalid(format!(
+                "{}: attribute '{}' is not declared in AttributeDefinitions",
+                entry_context, name
+            )));
+        };
+        if hash.iter().chain(&range).any(|a| a.name == name) {
+            return Err(invalid(format!(
+                "{}: key attributes must be different, '{}' appears more than once",
+                context, name
+            )));
+        }
+        used.insert(name.to_string());
+
+        let attribute = KeyAttribute::new(name, *key_type);
+        if role == "HASH" {
+            hash.push(attribute);
+        } else {
+            range.push(attribute);
+        }
+    }
+
+    Ok((hash, range))
+}
+
+fn index_name<'a>(
+    index: &'a BTreeMap<String, JsonValue>,
+    context: &str,
+    seen: &mut BTreeSet<String>,
+) -> TableResult<&'a str> {
+    let name = required_str(index, "IndexName", context)?;
+    if !seen.insert(name.to_string()) {
+        return Err(invalid(format!(
+            "{}: duplicate index name '{}'",
+            context, name
+        )));
+    }
+    Ok(name)
+}
+
+fn projection(index: &BTreeMap<String, JsonValue>, context: &str) -> TableResult<Projection> {
+    let projection = required(index, "Projection", context)?;
+    let context = format!("{}.Projection", context);
+    let projection = object(projection, &context)?;
+    let non_key = projection.get("NonKeyAttributes");
+
+    match required_str(projection, "ProjectionType", &context)? {
+        "ALL" | "KEYS_ONLY" if non_key.is_some() => Err(invalid(format!(
+            "{}: NonKeyAttributes is only allowed with INCLUDE",
+            context
+        ))),
+        "ALL" => Ok(Projection::All),
+        "KEYS_ONLY" => Ok(Projection::KeysOnly),
+        "INCLUDE" => {
+            let Some(non_key) = non_key else {
+                return Err(invalid(format!(
+                    "{}: INCLUDE requires NonKeyAttributes",
+                    context
+                )));
+            };
+            let attrs = array(non_key, &format!("{}.NonKeyAttributes", context))?
+                .iter()
+                .map(|v| {
+                    v.as_str().ok_or_else(|| {
+                        invalid(format!(
+                            "{}.NonKeyAttributes: expected string, found {}",
+                            context,
+                            v.type_name()
+                        ))
+                    })
+                })
+                .collect::<TableResult<Vec<_>>>()?;
+            Ok(Projection::include(attrs))
+        }
+        other => Err(invalid(format!(
+            "{}: ProjectionType must be ALL, KEYS_ONLY or INCLUDE, found '{}'",
+            context, other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: &str = r#"{
+        "TableName": "orders",
+        "BillingMode": "PAY_PER_REQUEST",
+        "AttributeDefinitions": [
+            {"AttributeName": "customer", "AttributeType": "S"},
+            {"AttributeName": "order_id", "AttributeType": "N"},
+            {"AttributeName": "status", "AttributeType": "S"},
+            {"AttributeName": "created", "AttributeType": "N"}
+        ],
+        "KeySchema": [
+            {"AttributeName": "customer", "KeyType": "HASH"},
+            {"AttributeName": "order_id", "KeyType": "RANGE"}
+        ],
+        "GlobalSecondaryIndexes": [{
+            "IndexName": "by_status",
+            "KeySchema": [
+                {"AttributeName": "status", "KeyType": "HASH"},
+                {"AttributeName": "created", "KeyType": "RANGE"}
+            ],
+            "Projection": {"ProjectionType": "INCLUDE", "NonKeyAttributes": ["total"]}
+        }],
+        "LocalSecondaryIndexes": [{
+            "IndexName": "by_created",
+            "KeySchema": [
+                {"AttributeName": "customer", "KeyType": "HASH"},
+                {"AttributeName": "created", "KeyType": "RANGE"}
+            ],
+            "Projection": {"ProjectionType": "KEYS_ONLY"}
+        }]
+    }"#;
+
+    fn error_message(input: &str) -> String {
+        match TableBuilder::from_json(input) {
+            Ok(_) => panic!("expected definition to be rejected"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn with_lsi_key(range: &str, range_type: &str) -> String {
+        format!(
+            r#"{{
+                "TableName": "t",
+                "AttributeDefinitions": [
+                    {{"AttributeName": "pk", "AttributeType": "S"}},
+                    {{"AttributeName": "sk", "AttributeType": "S"}},
+                    {{"AttributeName": "{range}", "AttributeType": "{range_type}"}}
+                ],
+                "KeySchema": [
+                    {{"AttributeName": "pk", "KeyType": "HASH"}},
+                    {{"AttributeName": "sk", "KeyType": "RANGE"}}
+                ],
+                "LocalSecondaryIndexes": [{{
+                    "IndexName": "lsi",
+                    "KeySchema": [
+                        {{"AttributeName": "pk", "KeyType": "HASH"}},
+                        {{"AttributeName": "other", "KeyType": "RANGE"}}
+                    ],
+                    "Projection": {{"ProjectionType": "ALL"}}
+                }}]
+            }}"#
+        )
+    }
+
+    #[test]
+    fn builds_table_with_indexes() {
+        let table = TableBuilder::from_json(ORDERS).unwrap().build().unwrap();
+        let description = table.describe();
+
+        assert_eq!(description.name, "orders");
+        assert_eq!(description.key_schema.pk_name(), "customer");
+        assert_eq!(description.key_schema.sk_name(), Some("order_id"));
+
+        let gsi = description.gsi("by_status").unwrap();
+        assert_eq!(gsi.key_schema.pk_name(), "status");
+        assert_eq!(gsi.projection, Projection::include(["total"]));
+
+        let lsi = description.lsi("by_created").unwrap();
+        assert_eq!(lsi.key_schema.sk_name(), Some("created"));
+        assert_eq!(lsi.projection, Projection::KeysOnly);
+    }
+
+    #[test]
+    fn lsi_key_must_be_declared() {
+        assert_eq!(
+            error_message(&with_lsi_key("created", "N")),
+            "validation error: invalid table definition: LocalSecondaryIndexes[0] (lsi).KeySchema[1]: \
+             attribute 'other' is not declared in AttributeDefinitions"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        assert!(error_message(r#"{"AttributeDefinitions": []}"#).contains("missing TableName"));
+        assert!(
+            error_message(&with_lsi_key("other", "X"))
+                .contains("AttributeDefinitions[2]: AttributeType must be S, N or B, found 'X'")
+        );
+        assert!(
+            error_message(&ORDERS.replace(r#""KeyType": "RANGE""#, r#""KeyType": "SORT""#))
+                .contains("KeySchema[1]: KeyType must be RANGE, found 'SORT'")
+        );
+        assert!(
+            error_message(&ORDERS.replace(
+                r#""ProjectionType": "KEYS_ONLY""#,
+                r#""ProjectionType": "INCLUDE""#
+            ))
+            .contains("(by_created).Projection: INCLUDE requires NonKeyAttributes")
+        );
+        assert!(
+            error_message(&ORDERS.replace(
+                r#""IndexName": "by_created""#,
+                r#""IndexName": "by_status""#
+            ))
+            .contains("duplicate index name 'by_status'")
+        );
+        assert!(
+            error_message(&ORDERS.replace(
+                r#""status", "AttributeType""#,
+                r#""state", "AttributeType""#
+            ))
+            .contains("attribute 'status' is not declared")
+        );
+        assert!(error_message("{").starts_with("encoding error: invalid json"));
+    }
+
+    #[test]
+    fn missing_projection_names_its_index() {
+        let input = ORDERS.replace(
+            r#",
+            "Projection": {"ProjectionType": "INCLUDE", "NonKeyAttributes": ["total"]}"#,
+            "",
+        );
+        assert_eq!(
+            error_message(&input),
+            "validation error: invalid table definition: GlobalSecondaryIndexes[0] (by_status): \
+             missing Projection"
+        );
+    }
+
+    #[test]
+    fn rejects_unused_definitions() {
+        let input = r#"{
+            "TableName": "t",
+            "AttributeDefinitions": [
+                {"AttributeName": "pk", "AttributeType": "S"},
+                {"AttributeName": "extra", "AttributeType": "N"}
+            ],
+            "KeySchema": [{"AttributeName": "pk", "KeyType": "HASH"}]
+        }"#;
+        assert!(error_message(input).contains("'extra' is defined but not used"));
+    }
+
+    #[test]
+    fn lsi_requires_composite_table_key() {
+        let input = r#"{
+            "TableName": "t",
+            "AttributeDefinitions": [
+                {"AttributeName": "pk", "AttributeType": "S"},
+                {"AttributeName": "sk", "AttributeType": "S"}
+            ],
+            "KeySchema": [{"AttributeName": "pk", "KeyType": "HASH"}],
+            "LocalSecondaryIndexes": [{
+                "IndexName": "lsi",
+                "KeySchema": [
+                    {"AttributeName": "pk", "KeyType": "HASH"},
+                    {"AttributeName": "sk", "KeyType": "RANGE"}
+                ],
+                "Projection": {"ProjectionType": "ALL"}
+            }]
+        }"#;
+        assert!(error_message(input).contains("require a table with a sort key"));
+    }
+
+    #[test]
+    fn gsi_with_multi_attribute_keys() {
+        let input = r#"{
+            "TableName": "t",
+            "AttributeDefinitions": [
+                {"AttributeName": "pk", "AttributeType": "S"},
+                {"AttributeName": "tenant", "AttributeType": "S"},
+                {"AttributeName": "region", "AttributeType": "S"},
+                {"AttributeName": "created", "AttributeType": "N"}
+            ],
+            "KeySchema": [{"AttributeName": "pk", "KeyType": "HASH"}],
+            "GlobalSecondaryIndexes": [{
+                "IndexName": "by_tenant",
+                "KeySchema": [
+                    {"AttributeName": "tenant", "KeyType": "HASH"},
+                    {"AttributeName": "region", "KeyType": "HASH"},
+                    {"AttributeName": "created", "KeyType": "RANGE"}
+                ],
+                "Projection": {"ProjectionType": "ALL"}
+            }]
+        }"#;
+        let table = TableBuilder::from_json(input).unwrap().build().unwrap();
+        let gsi = table.gsi("by_tenant").unwrap();
+        let partition: Vec<_> = gsi.partition_key_attributes().map(|a| &a.name).collect();
+        assert_eq!(partition, ["tenant", "region"]);
+        assert_eq!(gsi.schema().sk_name(), Some("created"));
+
+        let reordered = input.replace(
+            r#"{"AttributeName": "region", "KeyType": "HASH"},
+                    {"AttributeName": "created", "KeyType": "RANGE"}"#,
+            r#"{"AttributeName": "created", "KeyType": "RANGE"},
+                    {"AttributeName": "region", "KeyType": "HASH"}"#,
+        );
+        assert!(
+            error_message(&reordered).contains("KeySchema[2]: KeyType must be RANGE, found 'HASH'")
+        );
+    }
+}
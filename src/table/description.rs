@@ -0,0 +1,24 @@
+use crate::index::IndexDescription;
+use crate::types::KeySchema;
+
+#[derive(Debug, Clone)]
+pub struct TableDescription {
+    pub name: String,
+    pub key_schema: KeySchema,
+    pub item_count: usize,
+    pub size_bytes: usize,
+    pub global_secondary_indexes: Vec<IndexDescription>,
+    pub local_secondary_indexes: Vec<IndexDescription>,
+}
+
+impl TableDescription {
+    pub fn gsi(&self, name: &str) -> Option<&IndexDescription> {
+        self.global_secondary_indexes
+            .iter()
+            .find(|d| d.name == name)
+    }
+
+    pub fn lsi(&self, name: &str) -> Option<&IndexDescription> {
+        self.local_secondary_indexes.iter().find(|d| d.name == name)
+    }
+}
@@ -0,0 +1,48 @@
+use super::core::Table;
+use crate::error::{TableError, TableResult};
+use crate::geo::{BoundingBox, GeoHit, GeoIndex, GeoPoint};
+use crate::types::PrimaryKey;
+
+impl Table {
+    // items within `meters` of `center`, nearest first
+    pub fn query_radius(
+        &self,
+        index_name: &str,
+        center: GeoPoint,
+        meters: f64,
+    ) -> TableResult<Vec<GeoHit>> {
+        let index = self.geo_index_for_query(index_name)?;
+        if !center.is_valid() {
+            return Err(TableError::validation_error(format!(
+                "invalid coordinates ({}, {})",
+                center.lat, center.lng
+            )));
+        }
+        self.geo_hits(index.within_radius(center, meters.max(0.0)))
+    }
+
+    // items inside the box, nearest its center first
+    pub fn query_bbox(&self, index_name: &str, area: BoundingBox) -> TableResult<Vec<GeoHit>> {
+        let index = self.geo_index_for_query(index_name)?;
+        if !area.south_west.is_valid()
+            || !area.north_east.is_valid()
+            || area.south_west.lat > area.north_east.lat
+        {
+            return Err(TableError::validation_error("invalid bounding box"));
+        }
+        self.geo_hits(index.within_box(&area))
+    }
+
+    fn geo_index_for_query(&self, index_name: &str) -> TableResult<&GeoIndex> {
+        self.geo_index(index_name)
+            .ok_or_else(|| TableError::index_not_found(index_name))
+    }
+
+    fn geo_hits(&self, found: Vec<(PrimaryKey, f64)>) -> TableResult<Vec<GeoHit>> {
+        self.key_index_hits(found, |key, item, distance| GeoHit {
+            key,
+            item,
+            distance,
+        })
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+
+use super::core::Table;
+use super::request::{QueryRequest, ScanRequest};
+use crate::condition::{Condition, evaluate};
+use crate::error::{TableError, TableResult};
+use crate::query::{KeyCondition, QueryCursor, QueryExecutor, Select};
+use crate::types::Item;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+// yields query results in key order, holding at most one page of items at a time; each page
+// is read from storage just past where the previous one ended
+pub struct QueryIter<'a> {
+    table: &'a Table,
+    key_condition: KeyCondition,
+    scan_forward: bool,
+    filter: Option<Condition>,
+    select: Select,
+    consistent_read: bool,
+    // matches still allowed by the request limit, counted before the filter like `query`
+    remaining: Option<usize>,
+    page_size: usize,
+    // the last item of the previous page
+    last: Option<QueryCursor>,
+    buffer: VecDeque<Item>,
+    exhausted: bool,
+}
+
+impl<'a> QueryIter<'a> {
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    fn fetch_page(&mut self) -> TableResult<()> {
+        let page_size = match self.remaining {
+            Some(remaining) => remaining.min(self.page_size),
+            None => self.page_size,
+        };
+        let page = self.table.query_page(
+            &self.key_condition,
+            self.scan_forward,
+            self.consistent_read,
+            self.last.as_ref(),
+            page_size,
+        )?;
+
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= page.items.len();
+        }
+        self.buffer.extend(page.items);
+        self.last = page.last;
+        self.exhausted = self.last.is_none() || self.remaining == Some(0);
+        Ok(())
+    }
+}
+
+// a failed page read ends the iteration after its error
+impl Iterator for QueryIter<'_> {
+    type Item = TableResult<Item>;
+
+    fn next(&mut self) -> Option<TableResult<Item>> {
+        loop {
+            while let Some(item) = self.buffer.pop_front() {
+                if let Some(ref filter) = self.filter
+                    && !evaluate(filter, &item).unwrap_or(false)
+                {
+                    continue;
+                }
+                return Some(Ok(self.select.apply(item)));
+            }
+
+            if self.exhausted {
+                return None;
+            }
+            if let Err(e) = self.fetch_page() {
+                self.exhausted = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl Table {
+    pub fn query_iter(&self, request: impl Into<QueryRequest>) -> TableResult<QueryIter<'_>> {
+        let request = request.into();
+        validate_iter_select(&request.select)?;
+        QueryExecutor::new(self.schema()).validate_condition(&request.key_condition)?;
+
+        Ok(QueryIter {
+            table: self,
+            key_condition: request.key_condition,
+            scan_forward: request.options.scan_forward,
+            filter: request.filter,
+            select: request.select,
+            consistent_read: request.consistent_read,
+            remaining: request.options.limit,
+            page_size: DEFAULT_PAGE_SIZE,
+            last: None,
+            buffer: VecDeque::new(),
+            exhausted: false,
+        })
+    }
+
+    // storage order, like `scan`; the limit applies after the filter, and items that fail to
+    // decode come through as errors
+    pub fn scan_iter(
+        &self,
+        request: ScanRequest,
+    ) -> TableResult<impl Iterator<Item = TableResult<Item>> + '_> {
+        validate_iter_select(&request.select)?;
+
+        let ScanRequest {
+            filter,
+            limit,
+            select,
+        } = request;
+
+        Ok(self
+            .decoded_items()
+            .filter(move |item| match (item, &filter) {
+                (Ok(item), Some(filter)) => evaluate(filter, item).unwrap_or(false),
+                _ => true,
+            })
+            .take(limit.unwrap_or(usize::MAX))
+            .map(move |item| item.map(|item| select.apply(item))))
+    }
+}
+
+fn validate_iter_select(select: &Select) -> TableResult<()> {
+    match select {
+        Select::Count => Err(TableError::validation_error(
+            "COUNT is not supported by iterators; use query or scan_with_counts",
+        )),
+        Select::AllProjectedAttributes => Err(TableError::validation_error(
+            "ALL_PROJECTED_ATTRIBUTES can only be used when querying an index",
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::attr;
+    use crate::types::{KeySchema, KeyType, KeyValue, PrimaryKey};
+
+    fn table() -> Table {
+        let mut table = Table::new(
+            "t",
+            KeySchema::composite("pk", KeyType::S, "sk", KeyType::N),
+        );
+        for i in 0..25 {
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("pk", "a")
+                        .with_n("sk", i)
+                        .with_n("v", i % 3),
+                )
+                .unwrap();
+        }
+        table
+            .put_item(Item::new().with_s("pk", "b").with_n("sk", 1))
+            .unwrap();
+        table
+    }
+
+    fn sort_keys(items: impl Iterator<Item = TableResult<Item>>) -> Vec<i64> {
+        items
+            .map(|item| item.unwrap())
+            .map(|item| item.get("sk").unwrap().as_n().unwrap().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn pages_through_partition_in_order() {
+        let table = table();
+        let forward = table
+            .query_iter(KeyCondition::pk("a"))
+            .unwrap()
+            .page_size(4);
+        assert_eq!(sort_keys(forward), (0..25).collect::<Vec<_>>());
+
+        let reverse = table
+            .query_iter(QueryRequest::new(KeyCondition::pk("a")).reverse())
+            .unwrap()
+            .page_size(7);
+        assert_eq!(sort_keys(reverse), (0..25).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn limit_counts_before_filter() {
+        let table = table();
+        let request = QueryRequest::new(KeyCondition::pk("a"))
+            .filter(attr("v").eq(0))
+            .limit(10);
+        let items = table.query_iter(request).unwrap().page_size(3);
+        assert_eq!(sort_keys(items), vec![0, 3, 6, 9]);
+    }
+
+    #[test]
+    fn scan_limit_counts_after_filter() {
+        let table = table();
+        let request = ScanRequest::new().filter(attr("v").eq(1)).limit(3);
+        assert_eq!(table.scan_iter(request).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn eventual_reads_see_stale_versions() {
+        use crate::table::{EventualConsistency, ManualClock};
+        use std::time::Duration;
+
+        let mut table = table();
+        let clock = ManualClock::new();
+        table.simulate_eventual_consistency(
+            EventualConsistency::new(Duration::from_millis(10)).with_clock(clock.clone()),
+        );
+        table
+            .delete(PrimaryKey::composite("a", KeyValue::N("3".into())))
+            .unwrap();
+        table
+            .put_item(Item::new().with_s("pk", "a").with_n("sk", 100))
+            .unwrap();
+
+        let request = QueryRequest::new(KeyCondition::pk("a")).consistent_read(false);
+        let stale = sort_keys(table.query_iter(request.clone()).unwrap().page_size(4));
+        assert_eq!(stale, (0..25).collect::<Vec<_>>());
+
+        let current = sort_keys(table.query_iter(KeyCondition::pk("a")).unwrap());
+        assert!(!current.contains(&3) && current.contains(&100));
+
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(
+            sort_keys(table.query_iter(request).unwrap().page_size(4)),
+            current
+        );
+    }
+
+    #[test]
+    fn rejects_count() {
+        let table = table();
+        let request = QueryRequest::new(KeyCondition::pk("a")).select(Select::Count);
+        assert!(table.query_iter(request).is_err());
+        assert!(
+            table
+                .scan_iter(ScanRequest::new().select(Select::Count))
+                .is_err()
+        );
+    }
+}
@@ -1,7 +1,22 @@
+mod aggregate;
+mod consistency;
 mod core;
+mod definition;
+mod description;
+mod geo;
+mod iter;
+mod partiql;
 mod request;
+mod search;
+mod transfer;
+mod unique;
+mod vector;
 
+pub use consistency::{Clock, EventualConsistency, ManualClock, SystemClock};
 pub use core::{Table, TableBuilder};
+pub use description::TableDescription;
+pub use iter::QueryIter;
 pub use request::{
     DeleteRequest, GetRequest, PutRequest, QueryRequest, ScanRequest, UpdateRequest,
 };
+pub use transfer::{ImportError, ImportResult};
@@ -0,0 +1,129 @@
+use super::core::Table;
+use super::request::{DeleteRequest, PutRequest, QueryRequest, ScanRequest, UpdateRequest};
+use crate::condition::{AttributePath, evaluate};
+use crate::error::{TableError, TableResult};
+use crate::index::GlobalSecondaryIndex;
+use crate::partiql::{KeyLayout, Plan, Statement, plan};
+use crate::types::{AttributeValue, Item, KeyAttribute, KeyType};
+
+impl Table {
+    // parses `statement` and works out how it would run, without running it
+    pub fn plan_statement(&self, statement: &str, params: &[AttributeValue]) -> TableResult<Plan> {
+        let statement = Statement::parse(statement)?;
+        if statement.table() != self.name() {
+            return Err(TableError::validation_error(format!(
+                "statement targets table '{}', not '{}'",
+                statement.table(),
+                self.name()
+            )));
+        }
+
+        let schema = self.schema();
+        let table_keys = KeyLayout {
+            partition: vec![(
+                AttributePath::new(&schema.partition_key.name),
+                schema.partition_key.key_type,
+            )],
+            sort: schema
+                .sort_key
+                .iter()
+                .map(|sk| (AttributePath::new(&sk.name), sk.key_type))
+                .collect(),
+        };
+        let index_keys = match &statement {
+            Statement::Select {
+                index: Some(index), ..
+            } => {
+                let gsi = self
+                    .gsi(index)
+                    .ok_or_else(|| TableError::index_not_found(index))?;
+                Some(KeyLayout {
+                    partition: gsi_key_paths(gsi, gsi.partition_key_attributes()),
+                    sort: gsi_key_paths(gsi, gsi.sort_key_attributes()),
+                })
+            }
+            _ => None,
+        };
+        Ok(plan(statement, params, &table_keys, index_keys.as_ref())?)
+    }
+
+    // runs a PartiQL statement; reads return the matching items, writes return none
+    pub fn execute_statement(
+        &mut self,
+        statement: &str,
+        params: &[AttributeValue],
+    ) -> TableResult<Vec<Item>> {
+        match self.plan_statement(statement, params)? {
+            Plan::Query {
+                index,
+                key_condition,
+                filter,
+                select,
+            } => {
+                let request = QueryRequest::new(key_condition)
+                    .filter_if(filter)
+                    .select(select);
+                let result = match index {
+                    Some(index) => self.query_gsi(&index, request)?,
+                    None => self.query(request)?,
+                };
+                Ok(result.items)
+            }
+            Plan::Scan {
+                index: None,
+                filter,
+                select,
+            } => self.scan(ScanRequest::new().filter_if(filter).select(select)),
+            Plan::Scan {
+                index: Some(index),
+                filter,
+                select,
+            } => {
+                let gsi = self.gsi_view(&index)?;
+                if !gsi.is_active() {
+                    return Err(TableError::index_not_active(&index));
+                }
+                Ok(gsi
+                    .scan()
+                    .into_iter()
+                    .filter(|item| match &filter {
+                        Some(filter) => evaluate(filter, item).unwrap_or(false),
+                        None => true,
+                    })
+                    .map(|item| select.apply(item.clone()))
+                    .collect())
+            }
+            Plan::Insert { item } => {
+                self.put(PutRequest::new(item).if_not_exists())?;
+                Ok(Vec::new())
+            }
+            Plan::Update {
+                key,
+                expression,
+                condition,
+            } => {
+                self.update(UpdateRequest::new(key, expression).condition(condition))?;
+                Ok(Vec::new())
+            }
+            Plan::Delete { key, condition } => {
+                self.delete(DeleteRequest::new(key).condition_if(condition))?;
+                Ok(Vec::new())
+            }
+        }
+    }
+}
+
+fn gsi_key_paths<'a>(
+    gsi: &GlobalSecondaryIndex,
+    attributes: impl Iterator<Item = &'a KeyAttribute>,
+) -> Vec<(AttributePath, KeyType)> {
+    attributes
+        .map(|attr| {
+            let path = gsi
+                .key_path(&attr.name)
+                .cloned()
+                .unwrap_or_else(|| AttributePath::new(&attr.name));
+            (path, attr.key_type)
+        })
+        .collect()
+}
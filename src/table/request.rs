@@ -1,5 +1,5 @@
 use crate::condition::Condition;
-use crate::query::{KeyCondition, QueryOptions};
+use crate::query::{KeyCondition, QueryOptions, Select};
 use crate::types::{Item, PrimaryKey, ReturnValue};
 use crate::update::UpdateExpression;
 
@@ -126,6 +126,7 @@ impl From<PrimaryKey> for DeleteRequest {
 pub struct GetRequest {
     pub(crate) key: PrimaryKey,
     pub(crate) projection: Option<Vec<String>>,
+    pub(crate) consistent_read: bool,
 }
 
 impl GetRequest {
@@ -133,9 +134,16 @@ impl GetRequest {
         Self {
             key: key.into(),
             projection: None,
+            consistent_read: true,
         }
     }
 
+    // only makes a difference while the table simulates eventual consistency
+    pub fn consistent_read(mut self, consistent_read: bool) -> Self {
+        self.consistent_read = consistent_read;
+        self
+    }
+
     pub fn project<I, S>(mut self, attrs: I) -> Self
     where
         I: IntoIterator<Item = S>,
@@ -157,6 +165,8 @@ pub struct QueryRequest {
     pub(crate) key_condition: KeyCondition,
     pub(crate) filter: Option<Condition>,
     pub(crate) options: QueryOptions,
+    pub(crate) select: Select,
+    pub(crate) consistent_read: bool,
 }
 
 impl QueryRequest {
@@ -165,6 +175,8 @@ impl QueryRequest {
             key_condition,
             filter: None,
             options: QueryOptions::new(),
+            select: Select::AllAttributes,
+            consistent_read: true,
         }
     }
 
@@ -178,6 +190,12 @@ impl QueryRequest {
         self
     }
 
+    // ignored by gsi queries, which are always eventually consistent
+    pub fn consistent_read(mut self, consistent_read: bool) -> Self {
+        self.consistent_read = consistent_read;
+        self
+    }
+
     pub fn limit(mut self, limit: usize) -> Self {
         self.options = self.options.with_limit(limit);
         self
@@ -197,6 +215,11 @@ impl QueryRequest {
         self.options = options;
         self
     }
+
+    pub fn select(mut self, select: Select) -> Self {
+        self.select = select;
+        self
+    }
 }
 
 impl From<KeyCondition> for QueryRequest {
@@ -209,6 +232,7 @@ impl From<KeyCondition> for QueryRequest {
 pub struct ScanRequest {
     pub(crate) filter: Option<Condition>,
     pub(crate) limit: Option<usize>,
+    pub(crate) select: Select,
 }
 
 impl ScanRequest {
@@ -216,6 +240,7 @@ impl ScanRequest {
         Self {
             filter: None,
             limit: None,
+            select: Select::AllAttributes,
         }
     }
 
@@ -233,6 +258,11 @@ impl ScanRequest {
         self.limit = Some(limit);
         self
     }
+
+    pub fn select(mut self, select: Select) -> Self {
+        self.select = select;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -297,6 +327,7 @@ mod tests {
         assert!(req.filter.is_none());
         assert!(req.options.limit.is_none());
         assert!(req.options.scan_forward);
+        assert!(req.consistent_read);
 
         let req = QueryRequest::new(cond.clone())
             .filter(attr("status").eq("active"))
@@ -306,6 +337,13 @@ mod tests {
         assert!(req.options.limit.is_some());
         assert_eq!(req.options.limit, Some(10));
         assert!(!req.options.scan_forward);
+        assert_eq!(req.select, Select::AllAttributes);
+
+        let req = QueryRequest::new(cond)
+            .select(Select::Count)
+            .consistent_read(false);
+        assert!(req.select.is_count());
+        assert!(!req.consistent_read);
     }
 
     #[test]
@@ -316,9 +354,11 @@ mod tests {
 
         let req = ScanRequest::new()
             .filter(attr("status").eq("active"))
-            .limit(5);
+            .limit(5)
+            .select(Select::specific(["status"]));
         assert!(req.limit.is_some());
         assert_eq!(req.limit, Some(5));
         assert!(req.filter.is_some());
+        assert!(matches!(req.select, Select::SpecificAttributes(_)));
     }
 }
@@ -0,0 +1,51 @@
+use super::core::Table;
+use crate::error::{TableError, TableResult};
+use crate::search::{SearchCursor, SearchHit, SearchRequest, SearchResult};
+
+impl Table {
+    // ranked full-text search; pages continue from `SearchResult::last`
+    pub fn search(
+        &self,
+        index_name: &str,
+        request: impl Into<SearchRequest>,
+    ) -> TableResult<SearchResult> {
+        let request = request.into();
+        let index = self
+            .text_index(index_name)
+            .ok_or_else(|| TableError::index_not_found(index_name))?;
+
+        let ranked = index.search(&request.query);
+        let total = ranked.len();
+        let limit = request.limit.unwrap_or(usize::MAX);
+
+        let mut result = SearchResult {
+            total,
+            ..SearchResult::default()
+        };
+        let mut remaining = ranked
+            .into_iter()
+            .filter(|(key, score)| {
+                request.start_after.as_ref().is_none_or(|cursor| {
+                    // best first, then by storage key
+                    *score < cursor.score
+                        || (*score == cursor.score && key.to_storage_key() > cursor.storage_key)
+                })
+            })
+            .peekable();
+
+        result.hits = self.key_index_hits(remaining.by_ref().take(limit), |key, item, score| {
+            SearchHit { key, item, score }
+        })?;
+
+        if remaining.peek().is_some()
+            && let Some(hit) = result.hits.last()
+        {
+            result.last = Some(SearchCursor {
+                score: hit.score,
+                storage_key: hit.key.to_storage_key(),
+            });
+        }
+
+        Ok(result)
+    }
+}
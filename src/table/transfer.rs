@@ -0,0 +1,295 @@
+use std::io::{BufRead, Write};
+
+use super::core::Table;
+use crate::batch::{BatchWriteItem, MAX_BATCH_WRITE_ITEMS};
+use crate::condition::AttributePath;
+use crate::csv::{CsvMapping, CsvReader, escape_field, format_cell};
+use crate::error::{TableError, TableResult};
+use crate::json::{JsonError, item_from_json, item_to_json, parse};
+use crate::types::Item;
+use crate::utils::compare_key_values;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportError {
+    // 1-based line number in the input
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportResult {
+    pub imported: usize,
+    pub errors: Vec<ImportError>,
+}
+
+impl ImportResult {
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl Table {
+    // one `{"Item": {...}}` object per line, ordered by primary key
+    pub fn export_json_lines<W: Write>(&self, mut writer: W) -> TableResult<usize> {
+        let mut items: Vec<_> = self
+            .scan_all()?
+            .into_iter()
+            .filter_map(|item| Some((item.extract_key(self.schema())?, item)))
+            .collect();
+        // keys compare the way queries order them, so N 9 comes before N 10
+        items.sort_by(|(a, _), (b, _)| {
+            compare_key_values(&a.pk, &b.pk).then_with(|| match (&a.sk, &b.sk) {
+                (Some(a), Some(b)) => compare_key_values(a, b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            })
+        });
+
+        for (_, item) in &items {
+            writeln!(writer, "{{\"Item\":{}}}", item_to_json(item))?;
+        }
+        writer.flush()?;
+
+        Ok(items.len())
+    }
+
+    // bad lines are reported and skipped; only reader failures abort the import
+    pub fn import_json_lines<R: BufRead>(&mut self, reader: R) -> TableResult<ImportResult> {
+        let mut result = ImportResult::default();
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            // as with csv, a file saved by an editor may start with a byte order mark
+            let line = match i {
+                0 => line.strip_prefix('\u{FEFF}').unwrap_or(&line),
+                _ => &line,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let outcome = exported_item(line)
+                .map_err(|e| e.to_string())
+                .and_then(|item| self.put_item(item).map_err(|e| e.to_string()));
+
+            match outcome {
+                Ok(()) => result.imported += 1,
+                Err(message) => result.errors.push(ImportError {
+                    line: i + 1,
+                    message,
+                }),
+            }
+        }
+
+        Ok(result)
+    }
+
+    // the first row is the header; columns are matched by name and unmapped columns are ignored
+    pub fn import_csv<R: BufRead>(
+        &mut self,
+        reader: R,
+        mapping: &CsvMapping,
+    ) -> TableResult<ImportResult> {
+        let mut result = ImportResult::default();
+        let mut records = CsvReader::new(reader);
+
+        let Some(header) = records.read_record()? else {
+            return Ok(result);
+        };
+        let positions = self.resolve_csv_columns(&header.fields, mapping)?;
+
+        let mut pending = Vec::new();
+        for record in records {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    let line = e.line().ok_or_else(|| TableError::from(e.clone()))?;
+                    result.errors.push(ImportError {
+                        line,
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            if record.fields.len() != header.fields.len() {
+                result.errors.push(ImportError {
+                    line: record.line,
+                    message: format!(
+                        "expected {} fields, found {}",
+                        header.fields.len(),
+                        record.fields.len()
+                    ),
+                });
+                continue;
+            }
+
+            match self.csv_row_to_item(&record.fields, &positions, mapping) {
+                Ok(item) => pending.push((record.line, item)),
+                Err(message) => result.errors.push(ImportError {
+                    line: record.line,
+                    message,
+                }),
+            }
+
+            if pending.len() == MAX_BATCH_WRITE_ITEMS {
+                self.flush_csv_rows(&mut pending, &mut result)?;
+            }
+        }
+        self.flush_csv_rows(&mut pending, &mut result)?;
+
+        result.errors.sort_by_key(|e| e.line);
+        Ok(result)
+    }
+
+    pub fn export_csv<W, I, P>(&self, mut writer: W, columns: I) -> TableResult<usize>
+    where
+        W: Write,
+        I: IntoIterator<Item = P>,
+        P: Into<AttributePath>,
+    {
+        let columns: Vec<AttributePath> = columns.into_iter().map(Into::into).collect();
+        let header: Vec<_> = columns
+            .iter()
+            .map(|path| escape_field(&path.to_string()))
+            .collect();
+        writeln!(writer, "{}", header.join(","))?;
+
+        let mut items = self.scan_all()?;
+        items.sort_by_cached_key(|item| {
+            item.extract_key(self.schema())
+                .map(|pk| pk.to_storage_key())
+        });
+
+        for item in &items {
+            let row: Vec<_> = columns
+                .iter()
+                .map(|path| {
+                    path.resolve(item)
+                        .map(|value| escape_field(&format_cell(value)))
+                        .unwrap_or_default()
+                })
+                .collect();
+            writeln!(writer, "{}", row.join(","))?;
+        }
+        writer.flush()?;
+
+        Ok(items.len())
+    }
+
+    fn resolve_csv_columns(
+        &self,
+        header: &[String],
+        mapping: &CsvMapping,
+    ) -> TableResult<Vec<usize>> {
+        let schema = self.schema();
+        let mut key_names: Vec<&str> = mapping
+            .key_columns()
+            .map(|c| c.attribute.as_str())
+            .collect();
+        key_names.sort_unstable();
+        let mut expected: Vec<&str> = std::iter::once(schema.pk_name())
+            .chain(schema.sk_name())
+            .collect();
+        expected.sort_unstable();
+        if key_names != expected {
+            return Err(TableError::validation_error(format!(
+                "csv key columns must map to {}",
+                expected.join(", ")
+            )));
+        }
+
+        mapping
+            .columns()
+            .iter()
+            .map(|c| {
+                header
+                    .iter()
+                    .position(|h| h.trim() == c.column)
+                    .ok_or_else(|| {
+                        TableError::validation_error(format!("missing csv column: {}", c.column))
+                    })
+            })
+            .collect()
+    }
+
+    fn csv_row_to_item(
+        &self,
+        fields: &[String],
+        positions: &[usize],
+        mapping: &CsvMapping,
+    ) -> Result<Item, String> {
+        let mut item = Item::new();
+        for (column, &pos) in mapping.columns().iter().zip(positions) {
+            let raw = &fields[pos];
+            // empty cells are absent attributes; empty keys are left for validate_key
+            if raw.is_empty() {
+                continue;
+            }
+            let value = column
+                .column_type
+                .parse(raw)
+                .map_err(|e| format!("column {}: {}", column.column, e))?;
+            item.set(column.attribute.clone(), value);
+        }
+
+        item.validate_key(self.schema())
+            .map_err(|e| TableError::from(e).to_string())?;
+        Ok(item)
+    }
+
+    fn flush_csv_rows(
+        &mut self,
+        pending: &mut Vec<(usize, Item)>,
+        result: &mut ImportResult,
+    ) -> TableResult<()> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let rows = std::mem::take(pending);
+        let items: Vec<Item> = rows.iter().map(|(_, item)| item.clone()).collect();
+        let written = match self.batch_write(items) {
+            Ok(written) => written,
+            // the batch is all or nothing; row by row, only the conflicting rows fail
+            Err(e) if e.is_unique_violation() => {
+                for (line, item) in rows {
+                    match self.put_item(item) {
+                        Ok(_) => result.imported += 1,
+                        Err(e) => result.errors.push(ImportError {
+                            line,
+                            message: e.to_string(),
+                        }),
+                    }
+                }
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        result.imported += written.processed_count;
+
+        for unprocessed in written.unprocessed_items {
+            let BatchWriteItem::Put { item } = unprocessed else {
+                continue;
+            };
+            if let Some((line, _)) = rows.iter().find(|(_, row)| *row == item) {
+                result.errors.push(ImportError {
+                    line: *line,
+                    message: "item could not be written".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// a line of an export: the item inside its `{"Item": {...}}` wrapper, or a bare item
+fn exported_item(line: &str) -> Result<Item, JsonError> {
+    let value = parse(line)?;
+    let wrapped = value
+        .as_object()
+        .filter(|object| object.len() == 1)
+        .and_then(|object| object.get("Item"))
+        .filter(|inner| inner.as_object().is_some());
+    item_from_json(wrapped.unwrap_or(&value))
+}
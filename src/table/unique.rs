@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::core::Table;
+use crate::error::{TableError, TableResult, TransactionCancelReason};
+use crate::index::IndexEntryKey;
+use crate::transaction::TransactWriteItem;
+use crate::types::{Item, PrimaryKey};
+use crate::update::UpdateExecutor;
+
+#[derive(Debug)]
+pub(crate) struct UniqueConflict {
+    pub index: String,
+    // which write hit the conflict
+    pub position: usize,
+    pub keys: Vec<PrimaryKey>,
+}
+
+impl From<UniqueConflict> for TableError {
+    fn from(conflict: UniqueConflict) -> Self {
+        TableError::unique_violation(conflict.index, conflict.keys)
+    }
+}
+
+impl Table {
+    // `writes` land together as (key, new item) pairs, None for deletes; a later write to a
+    // key replaces an earlier one, and items being rewritten don't conflict with their old selves
+    pub(crate) fn find_unique_conflict(
+        &self,
+        writes: &[(PrimaryKey, Option<&Item>)],
+    ) -> Option<UniqueConflict> {
+        let mut unique = self.unique_gsis().peekable();
+        unique.peek()?;
+
+        let mut last_write: HashMap<String, usize> = HashMap::new();
+        for (position, (key, _)) in writes.iter().enumerate() {
+            last_write.insert(key.to_storage_key(), position);
+        }
+
+        for gsi in unique {
+            let mut claimed: BTreeMap<IndexEntryKey, &PrimaryKey> = BTreeMap::new();
+            for (position, (key, item)) in writes.iter().enumerate() {
+                if last_write.get(&key.to_storage_key()) != Some(&position) {
+                    continue;
+                }
+                let Some(index_key) = item.and_then(|item| gsi.index_key_for(item)) else {
+                    continue;
+                };
+
+                let conflict = gsi
+                    .holders(&index_key)
+                    .find(|holder| !last_write.contains_key(&holder.to_storage_key()))
+                    .or_else(|| claimed.insert(IndexEntryKey::new(&index_key, ""), key));
+                if let Some(other) = conflict {
+                    return Some(UniqueConflict {
+                        index: gsi.name().to_string(),
+                        position,
+                        keys: vec![other.clone(), key.clone()],
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn check_unique_write(&self, key: &PrimaryKey, item: &Item) -> TableResult<()> {
+        match self.find_unique_conflict(&[(key.clone(), Some(item))]) {
+            Some(conflict) => Err(conflict.into()),
+            None => Ok(()),
+        }
+    }
+
+    // runs after transaction validation, so updates are known to apply cleanly
+    pub(crate) fn check_unique_transaction(&self, items: &[TransactWriteItem]) -> TableResult<()> {
+        if self.unique_gsis().next().is_none() {
+            return Ok(());
+        }
+
+        let mut positions = Vec::new();
+        let mut writes: Vec<(PrimaryKey, Option<Item>)> = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let write = match item {
+                TransactWriteItem::Put { item, .. } => match item.extract_key(self.schema()) {
+                    Some(key) => (key, Some(item.clone())),
+                    None => continue,
+                },
+                TransactWriteItem::Update {
+                    key, expression, ..
+                } => {
+                    let Some(current) = self.get_item(key)? else {
+                        continue;
+                    };
+                    let updated = UpdateExecutor::new().execute(current, expression)?;
+                    (key.clone(), Some(updated))
+                }
+                TransactWriteItem::Delete { key, .. } => (key.clone(), None),
+                TransactWriteItem::ConditionCheck { .. } => continue,
+            };
+            positions.push(index);
+            writes.push(write);
+        }
+
+        let writes: Vec<_> = writes
+            .iter()
+            .map(|(key, item)| (key.clone(), item.as_ref()))
+            .collect();
+        match self.find_unique_conflict(&writes) {
+            Some(conflict) => Err(TableError::transaction_canceled(vec![
+                TransactionCancelReason::UniqueViolation {
+                    index: positions[conflict.position],
+                    index_name: conflict.index,
+                },
+            ])),
+            None => Ok(()),
+        }
+    }
+}
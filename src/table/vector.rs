@@ -0,0 +1,60 @@
+use super::core::Table;
+use crate::condition::{Condition, evaluate};
+use crate::error::{TableError, TableResult};
+use crate::types::PrimaryKey;
+use crate::vector::{VectorHit, VectorIndex};
+
+impl Table {
+    // the k items closest to `vector`, closest first
+    pub fn knn(&self, index_name: &str, vector: &[f32], k: usize) -> TableResult<Vec<VectorHit>> {
+        let index = self.vector_index_for_query(index_name, vector)?;
+        self.vector_hits(index.nearest(vector, k, |_| true))
+    }
+
+    // like `knn`, but only items matching `filter` are candidates
+    pub fn knn_where(
+        &self,
+        index_name: &str,
+        vector: &[f32],
+        k: usize,
+        filter: &Condition,
+    ) -> TableResult<Vec<VectorHit>> {
+        let index = self.vector_index_for_query(index_name, vector)?;
+        let found = index.nearest(vector, k, |key| {
+            matches!(self.get_item(key), Ok(Some(item)) if evaluate(filter, &item).unwrap_or(false))
+        });
+        self.vector_hits(found)
+    }
+
+    fn vector_index_for_query(
+        &self,
+        index_name: &str,
+        vector: &[f32],
+    ) -> TableResult<&VectorIndex> {
+        let index = self
+            .vector_index(index_name)
+            .ok_or_else(|| TableError::index_not_found(index_name))?;
+        if vector.len() != index.dimensions() {
+            return Err(TableError::validation_error(format!(
+                "index '{}' holds {}-dimensional vectors, got {}",
+                index_name,
+                index.dimensions(),
+                vector.len()
+            )));
+        }
+        if !vector.iter().all(|v| v.is_finite()) {
+            return Err(TableError::validation_error(
+                "query vector has non-finite components",
+            ));
+        }
+        Ok(index)
+    }
+
+    fn vector_hits(&self, found: Vec<(PrimaryKey, f32)>) -> TableResult<Vec<VectorHit>> {
+        self.key_index_hits(found, |key, item, distance| VectorHit {
+            key,
+            item,
+            distance,
+        })
+    }
+}
@@ -0,0 +1,754 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use super::{AttributeValue, Item};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    TypeMismatch {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    InvalidNumber {
+        value: String,
+        target: &'static str,
+    },
+    MissingAttribute {
+        name: String,
+    },
+    // a value with no attribute form, such as NaN or an empty set
+    InvalidValue {
+        message: String,
+    },
+    // error inside a named attribute, map entry or list element
+    At {
+        path: String,
+        source: Box<ConversionError>,
+    },
+}
+
+impl ConversionError {
+    pub fn type_mismatch(expected: &'static str, actual: &AttributeValue) -> Self {
+        Self::TypeMismatch {
+            expected,
+            actual: actual.type_name(),
+        }
+    }
+
+    pub fn missing(name: impl Into<String>) -> Self {
+        Self::MissingAttribute { name: name.into() }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::InvalidValue {
+            message: message.into(),
+        }
+    }
+
+    pub fn at(self, segment: impl fmt::Display) -> Self {
+        match self {
+            Self::At { path, source } => Self::At {
+                path: join_path(&segment.to_string(), &path),
+                source,
+            },
+            Self::MissingAttribute { name } => Self::MissingAttribute {
+                name: join_path(&segment.to_string(), &name),
+            },
+            other => Self::At {
+                path: segment.to_string(),
+                source: Box::new(other),
+            },
+        }
+    }
+
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Self::MissingAttribute { .. })
+    }
+}
+
+fn join_path(parent: &str, child: &str) -> String {
+    if child.starts_with('[') {
+        format!("{}{}", parent, child)
+    } else {
+        format!("{}.{}", parent, child)
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, actual } => {
+                write!(f, "expected {}, got {}", expected, actual)
+            }
+            Self::InvalidNumber { value, target } => {
+                write!(f, "cannot convert number {} to {}", value, target)
+            }
+            Self::MissingAttribute { name } => write!(f, "missing attribute: {}", name),
+            Self::InvalidValue { message } => write!(f, "{}", message),
+            Self::At { path, source } => write!(f, "{}: {}", path, source),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+pub trait ToAttributeValue {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError>;
+
+    // a struct field left out of its item rather than stored; only Option and sets use it
+    fn is_absent(&self) -> bool {
+        false
+    }
+}
+
+pub trait FromAttributeValue: Sized {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError>;
+
+    // what an absent attribute converts to; only Option and sets accept it
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+impl<T: ToAttributeValue + ?Sized> ToAttributeValue for &T {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        (**self).to_attribute_value()
+    }
+
+    fn is_absent(&self) -> bool {
+        (**self).is_absent()
+    }
+}
+
+impl ToAttributeValue for AttributeValue {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        Ok(self.clone())
+    }
+}
+
+impl FromAttributeValue for AttributeValue {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToAttributeValue for str {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        Ok(AttributeValue::S(self.to_string()))
+    }
+}
+
+impl ToAttributeValue for String {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        Ok(AttributeValue::S(self.clone()))
+    }
+}
+
+impl FromAttributeValue for String {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        value
+            .as_s()
+            .map(str::to_string)
+            .ok_or_else(|| ConversionError::type_mismatch("S", value))
+    }
+}
+
+impl ToAttributeValue for bool {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        Ok(AttributeValue::Bool(*self))
+    }
+}
+
+impl FromAttributeValue for bool {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        value
+            .as_bool()
+            .ok_or_else(|| ConversionError::type_mismatch("BOOL", value))
+    }
+}
+
+// u8 is left out on purpose so that Vec<u8> maps to binary rather than a list
+macro_rules! impl_number {
+    ($($t:ty),*) => {
+        $(
+            impl ToAttributeValue for $t {
+                fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+                    number_string(self).map(AttributeValue::N)
+                }
+            }
+
+            impl FromAttributeValue for $t {
+                fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+                    let n = value
+                        .as_n()
+                        .ok_or_else(|| ConversionError::type_mismatch("N", value))?;
+                    parse_number(n)
+                }
+            }
+        )*
+    };
+}
+impl_number!(
+    i8, i16, i32, i64, i128, isize, u16, u32, u64, u128, usize, f32, f64
+);
+
+// NaN and the infinities have no N form
+fn number_string<T: ToString>(n: &T) -> Result<String, ConversionError> {
+    let n = n.to_string();
+    match n.as_str() {
+        "NaN" | "inf" | "-inf" => Err(ConversionError::invalid(format!(
+            "number {} is not finite",
+            n
+        ))),
+        _ => Ok(n),
+    }
+}
+
+fn parse_number<T: FromStr>(n: &str) -> Result<T, ConversionError> {
+    n.parse().map_err(|_| ConversionError::InvalidNumber {
+        value: n.to_string(),
+        target: std::any::type_name::<T>(),
+    })
+}
+
+impl ToAttributeValue for Vec<u8> {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        Ok(AttributeValue::B(self.clone()))
+    }
+}
+
+impl FromAttributeValue for Vec<u8> {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        value
+            .as_b()
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| ConversionError::type_mismatch("B", value))
+    }
+}
+
+impl<T: ToAttributeValue> ToAttributeValue for Option<T> {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        match self {
+            Some(v) => v.to_attribute_value(),
+            None => Ok(AttributeValue::Null),
+        }
+    }
+
+    fn is_absent(&self) -> bool {
+        self.as_ref().is_none_or(T::is_absent)
+    }
+}
+
+impl<T: FromAttributeValue> FromAttributeValue for Option<T> {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        match value {
+            AttributeValue::Null => Ok(None),
+            v => T::from_attribute_value(v).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToAttributeValue> ToAttributeValue for Vec<T> {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        self.iter()
+            .enumerate()
+            .map(|(i, v)| v.to_attribute_value().map_err(|e| e.at(format!("[{}]", i))))
+            .collect::<Result<_, _>>()
+            .map(AttributeValue::L)
+    }
+}
+
+impl<T: FromAttributeValue> FromAttributeValue for Vec<T> {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        let list = value
+            .as_l()
+            .ok_or_else(|| ConversionError::type_mismatch("L", value))?;
+        list.iter()
+            .enumerate()
+            .map(|(i, v)| T::from_attribute_value(v).map_err(|e| e.at(format!("[{}]", i))))
+            .collect()
+    }
+}
+
+impl<T: ToAttributeValue> ToAttributeValue for BTreeMap<String, T> {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        self.iter()
+            .map(|(k, v)| {
+                v.to_attribute_value()
+                    .map(|v| (k.clone(), v))
+                    .map_err(|e| e.at(k))
+            })
+            .collect::<Result<_, _>>()
+            .map(AttributeValue::M)
+    }
+}
+
+impl<T: FromAttributeValue> FromAttributeValue for BTreeMap<String, T> {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        map_entries(value)
+    }
+}
+
+impl<T: ToAttributeValue> ToAttributeValue for HashMap<String, T> {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        self.iter()
+            .map(|(k, v)| {
+                v.to_attribute_value()
+                    .map(|v| (k.clone(), v))
+                    .map_err(|e| e.at(k))
+            })
+            .collect::<Result<_, _>>()
+            .map(AttributeValue::M)
+    }
+}
+
+impl<T: FromAttributeValue> FromAttributeValue for HashMap<String, T> {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        map_entries(value)
+    }
+}
+
+fn map_entries<T, C>(value: &AttributeValue) -> Result<C, ConversionError>
+where
+    T: FromAttributeValue,
+    C: FromIterator<(String, T)>,
+{
+    let map = value
+        .as_m()
+        .ok_or_else(|| ConversionError::type_mismatch("M", value))?;
+    map.iter()
+        .map(|(k, v)| {
+            T::from_attribute_value(v)
+                .map(|v| (k.clone(), v))
+                .map_err(|e| e.at(k))
+        })
+        .collect()
+}
+
+impl ToAttributeValue for Item {
+    fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+        Ok(AttributeValue::M(self.clone().into_inner()))
+    }
+}
+
+impl FromAttributeValue for Item {
+    fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+        value
+            .as_m()
+            .map(|m| Item::from(m.clone()))
+            .ok_or_else(|| ConversionError::type_mismatch("M", value))
+    }
+}
+
+// string, number and binary sets
+macro_rules! impl_set {
+    ($set:ident, $elem:ty, $variant:ident, $name:literal, $to:expr, $from:expr) => {
+        impl ToAttributeValue for $set<$elem> {
+            fn to_attribute_value(&self) -> Result<AttributeValue, ConversionError> {
+                if self.is_empty() {
+                    return Err(ConversionError::invalid(concat!(
+                        $name,
+                        " must not be empty"
+                    )));
+                }
+                self.iter()
+                    .map($to)
+                    .collect::<Result<_, _>>()
+                    .map(AttributeValue::$variant)
+            }
+
+            // DynamoDB has no empty sets, so an item leaves one out
+            fn is_absent(&self) -> bool {
+                self.is_empty()
+            }
+        }
+
+        impl FromAttributeValue for $set<$elem> {
+            fn from_attribute_value(value: &AttributeValue) -> Result<Self, ConversionError> {
+                match value {
+                    AttributeValue::$variant(set) => set.iter().map($from).collect(),
+                    other => Err(ConversionError::type_mismatch($name, other)),
+                }
+            }
+
+            fn from_missing() -> Option<Self> {
+                Some(Self::new())
+            }
+        }
+    };
+}
+
+impl_set!(
+    BTreeSet,
+    String,
+    Ss,
+    "SS",
+    |s: &String| Ok(s.clone()),
+    |s: &String| Ok(s.clone())
+);
+impl_set!(
+    HashSet,
+    String,
+    Ss,
+    "SS",
+    |s: &String| Ok(s.clone()),
+    |s: &String| Ok(s.clone())
+);
+impl_set!(
+    BTreeSet,
+    Vec<u8>,
+    Bs,
+    "BS",
+    |b: &Vec<u8>| Ok(b.clone()),
+    |b: &Vec<u8>| Ok(b.clone())
+);
+impl_set!(
+    HashSet,
+    Vec<u8>,
+    Bs,
+    "BS",
+    |b: &Vec<u8>| Ok(b.clone()),
+    |b: &Vec<u8>| Ok(b.clone())
+);
+
+macro_rules! impl_number_set {
+    ($($t:ty),*) => {
+        $(
+            impl_set!(BTreeSet, $t, Ns, "NS", number_string, |n: &String| parse_number(n));
+            impl_set!(HashSet, $t, Ns, "NS", number_string, |n: &String| parse_number(n));
+        )*
+    };
+}
+impl_number_set!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+// maps a struct's fields to item attributes:
+//
+//     impl_item!(User { id => "user_id", name, tags });
+//
+// generates ToAttributeValue/FromAttributeValue plus TryFrom conversions to and from Item
+#[macro_export]
+macro_rules! impl_item {
+    ($ty:ident { $($field:ident $(=> $attr:literal)?),* $(,)? }) => {
+        impl $crate::types::ToAttributeValue for $ty {
+            fn to_attribute_value(
+                &self,
+            ) -> ::std::result::Result<$crate::types::AttributeValue, $crate::types::ConversionError>
+            {
+                $crate::types::Item::try_from(self)
+                    .map(|item| $crate::types::AttributeValue::M(item.into_inner()))
+            }
+        }
+
+        impl $crate::types::FromAttributeValue for $ty {
+            fn from_attribute_value(
+                value: &$crate::types::AttributeValue,
+            ) -> ::std::result::Result<Self, $crate::types::ConversionError> {
+                let map = value.as_m().ok_or_else(|| {
+                    $crate::types::ConversionError::type_mismatch("M", value)
+                })?;
+                ::std::result::Result::Ok(Self {
+                    $(
+                        $field: {
+                            let name = $crate::impl_item!(@name $field $($attr)?);
+                            match map.get(name) {
+                                ::std::option::Option::Some(v) => {
+                                    $crate::types::FromAttributeValue::from_attribute_value(v)
+                                        .map_err(|e| e.at(name))?
+                                }
+                                ::std::option::Option::None => {
+                                    $crate::types::FromAttributeValue::from_missing()
+                                        .ok_or_else(|| $crate::types::ConversionError::missing(name))?
+                                }
+                            }
+                        },
+                    )*
+                })
+            }
+        }
+
+        impl ::std::convert::TryFrom<&$ty> for $crate::types::Item {
+            type Error = $crate::types::ConversionError;
+
+            fn try_from(value: &$ty) -> ::std::result::Result<Self, Self::Error> {
+                let mut item = $crate::types::Item::new();
+                $(
+                    let name = $crate::impl_item!(@name $field $($attr)?);
+                    // absent options and empty sets are left out rather than stored
+                    if !$crate::types::ToAttributeValue::is_absent(&value.$field) {
+                        let attribute =
+                            $crate::types::ToAttributeValue::to_attribute_value(&value.$field)
+                                .map_err(|e| e.at(name))?;
+                        if !attribute.is_null() {
+                            item.set(name, attribute);
+                        }
+                    }
+                )*
+                ::std::result::Result::Ok(item)
+            }
+        }
+
+        impl ::std::convert::TryFrom<$ty> for $crate::types::Item {
+            type Error = $crate::types::ConversionError;
+
+            fn try_from(value: $ty) -> ::std::result::Result<Self, Self::Error> {
+                Self::try_from(&value)
+            }
+        }
+
+        impl ::std::convert::TryFrom<&$crate::types::Item> for $ty {
+            type Error = $crate::types::ConversionError;
+
+            fn try_from(item: &$crate::types::Item) -> ::std::result::Result<Self, Self::Error> {
+                $crate::types::FromAttributeValue::from_attribute_value(
+                    &$crate::types::AttributeValue::M(item.clone().into_inner()),
+                )
+            }
+        }
+
+        impl ::std::convert::TryFrom<$crate::types::Item> for $ty {
+            type Error = $crate::types::ConversionError;
+
+            fn try_from(item: $crate::types::Item) -> ::std::result::Result<Self, Self::Error> {
+                Self::try_from(&item)
+            }
+        }
+    };
+    (@name $field:ident) => {
+        stringify!($field)
+    };
+    (@name $field:ident $attr:literal) => {
+        $attr
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::AttributePath;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Address {
+        city: String,
+        zip: Option<u32>,
+    }
+    crate::impl_item!(Address { city, zip });
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct User {
+        id: String,
+        age: u64,
+        score: f64,
+        active: bool,
+        nickname: Option<String>,
+        tags: BTreeSet<String>,
+        avatar: Vec<u8>,
+        address: Address,
+        history: Vec<Address>,
+        counters: HashMap<String, i64>,
+    }
+    crate::impl_item!(User {
+        id => "user_id",
+        age,
+        score,
+        active,
+        nickname,
+        tags,
+        avatar,
+        address,
+        history,
+        counters,
+    });
+
+    fn user() -> User {
+        User {
+            id: "u1".into(),
+            age: 30,
+            score: 4.5,
+            active: true,
+            nickname: None,
+            tags: ["admin".to_string(), "beta".to_string()].into(),
+            avatar: vec![1, 2, 3],
+            address: Address {
+                city: "Paris".into(),
+                zip: Some(75001),
+            },
+            history: vec![Address {
+                city: "Lyon".into(),
+                zip: None,
+            }],
+            counters: [("logins".to_string(), 7)].into(),
+        }
+    }
+
+    mod scalars {
+        use super::*;
+
+        #[test]
+        fn roundtrip() {
+            assert_eq!(
+                i32::from_attribute_value(&(-5i32).to_attribute_value().unwrap()),
+                Ok(-5)
+            );
+            assert_eq!(
+                String::from_attribute_value(&"hi".to_attribute_value().unwrap()),
+                Ok("hi".to_string())
+            );
+            assert_eq!(
+                Vec::<u8>::from_attribute_value(&vec![0u8, 255].to_attribute_value().unwrap()),
+                Ok(vec![0, 255])
+            );
+            assert_eq!(
+                Option::<bool>::from_attribute_value(&AttributeValue::Null),
+                Ok(None)
+            );
+        }
+
+        #[test]
+        fn typed_errors() {
+            assert_eq!(
+                i8::from_attribute_value(&AttributeValue::N("300".into())),
+                Err(ConversionError::InvalidNumber {
+                    value: "300".into(),
+                    target: "i8",
+                })
+            );
+            assert_eq!(
+                bool::from_attribute_value(&AttributeValue::S("true".into())),
+                Err(ConversionError::TypeMismatch {
+                    expected: "BOOL",
+                    actual: "S",
+                })
+            );
+        }
+
+        #[test]
+        fn sets() {
+            let set: HashSet<String> = ["a".to_string()].into();
+            assert_eq!(
+                set.to_attribute_value(),
+                Ok(AttributeValue::Ss(["a".to_string()].into()))
+            );
+
+            let numbers: BTreeSet<u8> = [1, 2].into();
+            let value = numbers.to_attribute_value().unwrap();
+            assert!(matches!(value, AttributeValue::Ns(_)));
+            assert_eq!(BTreeSet::<u8>::from_attribute_value(&value), Ok(numbers));
+
+            let blobs: HashSet<Vec<u8>> = [vec![1], vec![2, 3]].into();
+            let value = blobs.to_attribute_value().unwrap();
+            assert!(matches!(value, AttributeValue::Bs(_)));
+            assert_eq!(HashSet::<Vec<u8>>::from_attribute_value(&value), Ok(blobs));
+        }
+
+        #[test]
+        fn values_without_an_attribute_form() {
+            assert_eq!(
+                f64::NAN.to_attribute_value(),
+                Err(ConversionError::invalid("number NaN is not finite"))
+            );
+            assert_eq!(
+                f32::NEG_INFINITY.to_attribute_value(),
+                Err(ConversionError::invalid("number -inf is not finite"))
+            );
+            assert_eq!(
+                BTreeSet::<String>::new().to_attribute_value(),
+                Err(ConversionError::invalid("SS must not be empty"))
+            );
+            assert_eq!(
+                HashSet::<u32>::new().to_attribute_value(),
+                Err(ConversionError::invalid("NS must not be empty"))
+            );
+            assert_eq!(
+                vec![1.0, f64::INFINITY].to_attribute_value().unwrap_err(),
+                ConversionError::invalid("number inf is not finite").at("[1]")
+            );
+        }
+    }
+
+    mod impl_item {
+        use super::*;
+
+        #[test]
+        fn struct_roundtrip() {
+            let item = Item::try_from(user()).unwrap();
+            assert_eq!(item.get("user_id"), Some(&AttributeValue::S("u1".into())));
+            assert!(!item.contains("id"));
+            // None fields are omitted
+            assert!(!item.contains("nickname"));
+
+            assert_eq!(User::try_from(&item), Ok(user()));
+        }
+
+        #[test]
+        fn missing_and_mismatched_fields() {
+            let item = Item::try_from(user()).unwrap().with_s("age", "thirty");
+            let err = User::try_from(&item).unwrap_err();
+            assert_eq!(err.to_string(), "age: expected N, got S");
+
+            let mut item = Item::try_from(user()).unwrap();
+            item.remove("score");
+            assert_eq!(
+                User::try_from(item).unwrap_err(),
+                ConversionError::missing("score")
+            );
+
+            let mut history = Item::try_from(user()).unwrap();
+            history.set(
+                "history",
+                AttributeValue::L(vec![AttributeValue::M(Default::default())]),
+            );
+            assert_eq!(
+                User::try_from(history).unwrap_err().to_string(),
+                "missing attribute: history[0].city"
+            );
+        }
+
+        #[test]
+        fn invalid_fields() {
+            let mut invalid = user();
+            invalid.score = f64::NAN;
+            assert_eq!(
+                Item::try_from(&invalid).unwrap_err().to_string(),
+                "score: number NaN is not finite"
+            );
+        }
+
+        #[test]
+        fn empty_sets_are_left_out() {
+            let mut user = user();
+            user.tags.clear();
+            let item = Item::try_from(&user).unwrap();
+            assert!(!item.contains("tags"));
+            assert_eq!(User::try_from(&item), Ok(user));
+
+            assert_eq!(HashSet::<u32>::from_missing(), Some(HashSet::new()));
+            assert_eq!(Option::<BTreeSet<String>>::from_missing(), Some(None));
+        }
+
+        #[test]
+        fn get_as() {
+            let item = Item::try_from(user()).unwrap();
+            assert_eq!(item.get_as::<u64>("age"), Ok(30));
+            assert_eq!(
+                item.get_as::<String>(AttributePath::new("address").key("city")),
+                Ok("Paris".to_string())
+            );
+            assert_eq!(item.get_as::<Option<String>>("nickname"), Ok(None));
+            assert_eq!(
+                item.get_as::<String>("nickname"),
+                Err(ConversionError::missing("nickname"))
+            );
+            assert_eq!(
+                item.get_as::<String>("age").unwrap_err().to_string(),
+                "age: expected S, got N"
+            );
+        }
+    }
+}
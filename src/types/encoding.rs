@@ -291,6 +291,47 @@ impl<'a> Decoder<'a> {
     }
 }
 
+// size of the encoding without producing it
+pub fn encoded_len(value: &AttributeValue) -> usize {
+    let body = match value {
+        AttributeValue::S(s) => bytes_len(s.len()),
+        AttributeValue::N(n) => bytes_len(n.len()),
+        AttributeValue::B(b) => bytes_len(b.len()),
+        AttributeValue::Bool(_) => 1,
+        AttributeValue::Null => 0,
+        AttributeValue::M(m) => return encoded_map_len(m),
+        AttributeValue::L(l) => len_prefix(l.len()) + l.iter().map(encoded_len).sum::<usize>(),
+        AttributeValue::Ss(ss) => {
+            len_prefix(ss.len()) + ss.iter().map(|s| bytes_len(s.len())).sum::<usize>()
+        }
+        AttributeValue::Ns(ns) => {
+            len_prefix(ns.len()) + ns.iter().map(|n| bytes_len(n.len())).sum::<usize>()
+        }
+        AttributeValue::Bs(bs) => {
+            len_prefix(bs.len()) + bs.iter().map(|b| bytes_len(b.len())).sum::<usize>()
+        }
+    };
+    1 + body
+}
+
+pub(crate) fn encoded_map_len(map: &BTreeMap<String, AttributeValue>) -> usize {
+    1 + len_prefix(map.len())
+        + map
+            .iter()
+            .map(|(k, v)| bytes_len(k.len()) + encoded_len(v))
+            .sum::<usize>()
+}
+
+#[inline]
+fn len_prefix(len: usize) -> usize {
+    if len < 128 { 1 } else { 4 }
+}
+
+#[inline]
+fn bytes_len(len: usize) -> usize {
+    len_prefix(len) + len
+}
+
 pub fn encode(value: &AttributeValue) -> Vec<u8> {
     Encoder::new().encode(value)
 }
@@ -324,6 +365,35 @@ mod tests {
         assert!(decode(&[255]).is_err());
     }
 
+    #[test]
+    fn encoded_len_matches_encoding() {
+        let mut map = BTreeMap::new();
+        map.insert("name".to_string(), AttributeValue::S("x".repeat(300)));
+        map.insert(
+            "tags".to_string(),
+            AttributeValue::Ss(["a", "b"].into_iter().map(String::from).collect()),
+        );
+        map.insert(
+            "nested".to_string(),
+            AttributeValue::L(vec![
+                AttributeValue::N("1".into()),
+                AttributeValue::Bool(true),
+                AttributeValue::Null,
+                AttributeValue::Bs([vec![1u8, 2], vec![3]].into_iter().collect()),
+            ]),
+        );
+        let values = [
+            AttributeValue::S("hello".into()),
+            AttributeValue::B(vec![0; 200]),
+            AttributeValue::Ns(["1", "2.5"].into_iter().map(String::from).collect()),
+            AttributeValue::M(map),
+        ];
+
+        for value in &values {
+            assert_eq!(encoded_len(value), encode(value).len(), "{:?}", value);
+        }
+    }
+
     mod roundtrip {
         use super::*;
 
@@ -1,5 +1,9 @@
 use std::collections::BTreeMap;
 
+use crate::condition::AttributePath;
+
+use super::convert::{ConversionError, FromAttributeValue};
+use super::encoding::encoded_map_len;
 use super::{AttributeValue, KeySchema, KeyType, KeyValue, PrimaryKey};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -54,6 +58,21 @@ impl Item {
         self.attributes
     }
 
+    pub fn get_as<T: FromAttributeValue>(
+        &self,
+        path: impl Into<AttributePath>,
+    ) -> Result<T, ConversionError> {
+        let path = path.into();
+        match path.resolve(self) {
+            Some(value) => T::from_attribute_value(value).map_err(|e| e.at(&path)),
+            None => T::from_missing().ok_or_else(|| ConversionError::missing(path.to_string())),
+        }
+    }
+
+    pub fn encoded_size(&self) -> usize {
+        encoded_map_len(&self.attributes)
+    }
+
     pub fn extract_key(&self, schema: &KeySchema) -> Option<PrimaryKey> {
         let pk_attr = self.get(schema.pk_name())?;
         let pk = KeyValue::from_attribute_with_type(pk_attr, schema.partition_key.key_type)?;
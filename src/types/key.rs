@@ -145,6 +145,69 @@ pub fn encode_key_component(key: &KeyValue) -> Cow<'_, str> {
     }
 }
 
+// byte encoding whose ordering matches key ordering for values of the same type;
+// numbers are normalized, so "1.50" and "1.5" encode the same
+pub fn encode_ordered_key(key: &KeyValue) -> Vec<u8> {
+    match key {
+        KeyValue::S(s) => s.as_bytes().to_vec(),
+        KeyValue::B(b) => b.clone(),
+        KeyValue::N(n) => encode_ordered_number(n).unwrap_or_else(|| {
+            // unparseable numbers sort after every real one
+            let mut out = vec![ORDERED_NAN];
+            out.extend_from_slice(n.as_bytes());
+            out
+        }),
+    }
+}
+
+const ORDERED_NEGATIVE: u8 = 0x01;
+const ORDERED_ZERO: u8 = 0x02;
+const ORDERED_POSITIVE: u8 = 0x03;
+const ORDERED_NAN: u8 = 0x04;
+
+fn encode_ordered_number(n: &str) -> Option<Vec<u8>> {
+    let (negative, rest) = match n.as_bytes().first()? {
+        b'-' => (true, &n[1..]),
+        b'+' => (false, &n[1..]),
+        _ => (false, n),
+    };
+    let (mantissa, exp) = match rest.find(['e', 'E']) {
+        Some(i) => (&rest[..i], rest[i + 1..].parse::<i32>().ok()?),
+        None => (rest, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits: Vec<u8> = int.bytes().chain(frac.bytes()).collect();
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    let leading = digits.iter().take_while(|&&d| d == b'0').count();
+    if leading == digits.len() {
+        return Some(vec![ORDERED_ZERO]);
+    }
+    let trailing = digits.iter().rev().take_while(|&&d| d == b'0').count();
+    let significant = &digits[leading..digits.len() - trailing];
+
+    // value is 0.<significant> * 10^exponent
+    let exponent = (int.len() as i64 - leading as i64 + exp as i64)
+        .clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+    let exponent = (exponent as u32 ^ 0x8000_0000).to_be_bytes();
+
+    let mut out = Vec::with_capacity(significant.len() + 6);
+    if negative {
+        // larger magnitudes sort first, and a shorter digit run must sort after its extensions
+        out.push(ORDERED_NEGATIVE);
+        out.extend(exponent.iter().map(|b| !b));
+        out.extend(significant.iter().map(|d| !d));
+        out.push(0xFF);
+    } else {
+        out.push(ORDERED_POSITIVE);
+        out.extend_from_slice(&exponent);
+        out.extend_from_slice(significant);
+    }
+    Some(out)
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum KeyType {
     S,
@@ -248,6 +311,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn key_type_maching() {
         assert!(KeyType::S.matches(&KeyValue::S("hello".into())));
         assert!(!KeyType::S.matches(&KeyValue::N("123".into())));
@@ -279,6 +343,56 @@ mod tests {
         );
     }
 
+    mod ordered_key {
+        use super::*;
+
+        fn n(value: &str) -> Vec<u8> {
+            encode_ordered_key(&KeyValue::N(value.into()))
+        }
+
+        #[test]
+        fn numbers_sort_numerically() {
+            let sorted = [
+                "-1e10",
+                "-123",
+                "-12",
+                "-1.5",
+                "-0.05",
+                "0",
+                "0.001",
+                "0.05",
+                "1",
+                "1.5",
+                "9",
+                "10",
+                "12",
+                "100",
+                "123",
+                "99999999999999999999999999999999999999",
+                "1e40",
+            ];
+            for pair in sorted.windows(2) {
+                assert!(n(pair[0]) < n(pair[1]), "{} < {}", pair[0], pair[1]);
+            }
+        }
+
+        #[test]
+        fn equivalent_numbers_encode_equal() {
+            assert_eq!(n("1.50"), n("1.5"));
+            assert_eq!(n("0015"), n("15"));
+            assert_eq!(n("1.5e1"), n("15"));
+            assert_eq!(n("-0"), n("0.000"));
+        }
+
+        #[test]
+        fn strings_and_binary_keep_byte_order() {
+            let a = encode_ordered_key(&KeyValue::S("apple".into()));
+            let b = encode_ordered_key(&KeyValue::S("apples".into()));
+            assert!(a < b);
+            assert_eq!(encode_ordered_key(&KeyValue::B(vec![1, 2])), vec![1, 2]);
+        }
+    }
+
     mod storage_key {
         use super::*;
 
@@ -1,11 +1,16 @@
 mod attributes;
+mod convert;
 mod encoding;
 mod item;
 mod key;
 mod returns;
 
 pub use attributes::AttributeValue;
-pub use encoding::{DecodeError, Decoder, Encoder, decode, encode};
+pub use convert::{ConversionError, FromAttributeValue, ToAttributeValue};
+pub use encoding::{DecodeError, Decoder, Encoder, decode, encode, encoded_len};
 pub use item::{Item, KeyValidationError};
-pub use key::{KeyAttribute, KeySchema, KeyType, KeyValue, PrimaryKey, encode_key_component};
+pub use key::{
+    KeyAttribute, KeySchema, KeyType, KeyValue, PrimaryKey, encode_key_component,
+    encode_ordered_key,
+};
 pub use returns::{ReturnValue, WriteResult};
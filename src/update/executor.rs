@@ -189,6 +189,7 @@ impl UpdateExecutor {
         Ok(())
     }
 
+    #[allow(clippy::collapsible_match)]
     fn remove_at_path(
         mut current: AttributeValue,
         segments: &[PathSegment],
@@ -0,0 +1,364 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::metric::Metric;
+
+// levels are drawn from a fixed seed so an index built from the same writes has the same shape
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+const MAX_LEVEL: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HnswConfig {
+    pub(crate) m: usize,
+    pub(crate) ef_construction: usize,
+    pub(crate) ef_search: usize,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+        }
+    }
+}
+
+impl HnswConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // links per node above the bottom layer, at least 2; the bottom layer keeps twice as many
+    pub fn m(mut self, m: usize) -> Self {
+        self.m = m;
+        self
+    }
+
+    // candidates considered when linking a new node
+    pub fn ef_construction(mut self, ef: usize) -> Self {
+        self.ef_construction = ef.max(1);
+        self
+    }
+
+    // candidates considered per query; raised to k when smaller
+    pub fn ef_search(mut self, ef: usize) -> Self {
+        self.ef_search = ef.max(1);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored {
+    distance: f32,
+    node: usize,
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+#[derive(Debug)]
+struct Node {
+    storage_key: String,
+    vector: Vec<f32>,
+    // neighbours per layer, bottom layer first
+    links: Vec<Vec<usize>>,
+    // removed nodes stay in the graph as waypoints until the next rebuild
+    deleted: bool,
+}
+
+// hierarchical navigable small world graph over vectors keyed by table storage key
+#[derive(Debug)]
+pub(crate) struct Hnsw {
+    config: HnswConfig,
+    metric: Metric,
+    nodes: Vec<Node>,
+    ids: HashMap<String, usize>,
+    entry: Option<usize>,
+    deleted: usize,
+    rng: u64,
+}
+
+impl Hnsw {
+    pub(crate) fn new(config: HnswConfig, metric: Metric) -> Self {
+        Self {
+            config,
+            metric,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            entry: None,
+            deleted: 0,
+            rng: SEED,
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.nodes.clear();
+        self.ids.clear();
+        self.entry = None;
+        self.deleted = 0;
+        self.rng = SEED;
+    }
+
+    pub(crate) fn insert(&mut self, storage_key: String, vector: Vec<f32>) {
+        self.remove(&storage_key);
+
+        let level = self.random_level();
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            storage_key: storage_key.clone(),
+            vector,
+            links: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.ids.insert(storage_key, id);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(id);
+            return;
+        };
+        let top = self.nodes[entry].links.len() - 1;
+        let query = self.nodes[id].vector.clone();
+
+        let mut entries = vec![entry];
+        for layer in (level + 1..=top).rev() {
+            entries = vec![self.search_layer(&query, &entries, 1, layer, |_| true)[0].node];
+        }
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(
+                &query,
+                &entries,
+                self.config.ef_construction,
+                layer,
+                |node| !node.deleted,
+            );
+            let neighbours: Vec<usize> = found.iter().take(self.config.m).map(|s| s.node).collect();
+            let max_links = self.max_links(layer);
+            for &neighbour in &neighbours {
+                self.nodes[neighbour].links[layer].push(id);
+                if self.nodes[neighbour].links[layer].len() > max_links {
+                    self.prune(neighbour, layer, max_links);
+                }
+            }
+            self.nodes[id].links[layer] = neighbours;
+            if !found.is_empty() {
+                entries = found.into_iter().map(|s| s.node).collect();
+            }
+        }
+        if level > top {
+            self.entry = Some(id);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, storage_key: &str) -> bool {
+        let Some(id) = self.ids.remove(storage_key) else {
+            return false;
+        };
+        self.nodes[id].deleted = true;
+        self.deleted += 1;
+        // once most of the graph is tombstones, searches mostly walk dead nodes
+        if self.deleted > self.ids.len() {
+            self.rebuild();
+        }
+        true
+    }
+
+    // up to k live nodes that pass `accept`, closest first
+    pub(crate) fn search(
+        &self,
+        query: &[f32],
+        k: usize,
+        accept: impl Fn(&str) -> bool,
+    ) -> Vec<(&str, f32)> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        if k == 0 {
+            return Vec::new();
+        }
+        let top = self.nodes[entry].links.len() - 1;
+
+        let mut entries = vec![entry];
+        for layer in (1..=top).rev() {
+            entries = vec![self.search_layer(query, &entries, 1, layer, |_| true)[0].node];
+        }
+        self.search_layer(query, &entries, self.config.ef_search.max(k), 0, |node| {
+            !node.deleted && accept(&node.storage_key)
+        })
+        .into_iter()
+        .take(k)
+        .map(|s| (self.nodes[s.node].storage_key.as_str(), s.distance))
+        .collect()
+    }
+
+    // best-first walk of one layer; every reachable node is a waypoint but only
+    // accepted ones are kept, so a selective filter widens the walk instead of
+    // returning fewer results
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[usize],
+        ef: usize,
+        layer: usize,
+        accept: impl Fn(&Node) -> bool,
+    ) -> Vec<Scored> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+        for &node in entries {
+            let scored = self.scored(query, node);
+            candidates.push(Reverse(scored));
+            if accept(&self.nodes[node]) {
+                results.push(scored);
+            }
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            if results.len() >= ef
+                && results
+                    .peek()
+                    .is_some_and(|worst: &Scored| current.distance > worst.distance)
+            {
+                break;
+            }
+            for &neighbour in &self.nodes[current.node].links[layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let scored = self.scored(query, neighbour);
+                let worth_visiting = results.len() < ef
+                    || results
+                        .peek()
+                        .is_some_and(|worst| scored.distance < worst.distance);
+                if !worth_visiting {
+                    continue;
+                }
+                candidates.push(Reverse(scored));
+                if accept(&self.nodes[neighbour]) {
+                    results.push(scored);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    // keeps the closest links
+    fn prune(&mut self, node: usize, layer: usize, max_links: usize) {
+        let vector = self.nodes[node].vector.clone();
+        let mut links: Vec<Scored> = self.nodes[node].links[layer]
+            .iter()
+            .map(|&link| self.scored(&vector, link))
+            .collect();
+        links.sort();
+        links.truncate(max_links);
+        self.nodes[node].links[layer] = links.into_iter().map(|s| s.node).collect();
+    }
+
+    fn rebuild(&mut self) {
+        let live: Vec<(String, Vec<f32>)> = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .filter(|node| !node.deleted)
+            .map(|node| (node.storage_key, node.vector))
+            .collect();
+        self.clear();
+        for (storage_key, vector) in live {
+            self.insert(storage_key, vector);
+        }
+    }
+
+    fn scored(&self, query: &[f32], node: usize) -> Scored {
+        Scored {
+            distance: self.metric.distance(query, &self.nodes[node].vector),
+            node,
+        }
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.config.m * 2
+        } else {
+            self.config.m
+        }
+    }
+
+    // geometric levels with ratio 1/m; xorshift keeps this dependency free
+    fn random_level(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let uniform = ((self.rng >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let level = -uniform.ln() / (self.config.m as f64).ln();
+        (level as usize).min(MAX_LEVEL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(graph: &mut Hnsw, side: usize) {
+        for x in 0..side {
+            for y in 0..side {
+                graph.insert(format!("{}:{}", x, y), vec![x as f32, y as f32]);
+            }
+        }
+    }
+
+    #[test]
+    fn finds_exact_neighbours_on_a_grid() {
+        let mut graph = Hnsw::new(HnswConfig::new().m(4).ef_construction(32), Metric::L2);
+        grid(&mut graph, 20);
+        assert_eq!(graph.ids.len(), 400);
+
+        let found = graph.search(&[7.1, 3.2], 5, |_| true);
+        let keys: Vec<&str> = found.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys[0], "7:3");
+        assert_eq!(keys.len(), 5);
+        assert!(found.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(
+            keys.iter()
+                .all(|key| ["7:3", "7:4", "8:3", "6:3", "7:2"].contains(key))
+        );
+    }
+
+    #[test]
+    fn filters_and_tombstones() {
+        let mut graph = Hnsw::new(HnswConfig::new().m(4), Metric::L2);
+        grid(&mut graph, 10);
+
+        // only one row passes; the walk keeps going until it finds enough
+        let found = graph.search(&[0.0, 0.0], 3, |key| key.starts_with("9:"));
+        let keys: Vec<&str> = found.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, ["9:0", "9:1", "9:2"]);
+
+        assert!(graph.remove("0:0"));
+        assert!(!graph.remove("0:0"));
+        assert_eq!(graph.search(&[0.0, 0.0], 1, |_| true)[0].1, 1.0);
+
+        // removing most nodes triggers a rebuild without tombstones
+        for x in 0..10 {
+            for y in 0..8 {
+                graph.remove(&format!("{}:{}", x, y));
+            }
+        }
+        assert_eq!(graph.ids.len(), 20);
+        assert!(graph.deleted <= graph.ids.len());
+        assert_eq!(graph.search(&[0.0, 0.0], 1, |_| true)[0].0, "0:8");
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::condition::AttributePath;
+use crate::error::{TableError, TableResult};
+use crate::index::KeyIndex;
+use crate::types::{AttributeValue, Item, PrimaryKey};
+
+use super::hnsw::{Hnsw, HnswConfig};
+use super::metric::Metric;
+
+// little-endian f32s, the layout `B` vector attributes are read with
+pub fn pack_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+#[derive(Debug)]
+enum Vectors {
+    // brute force over every vector, by table storage key
+    Exact(BTreeMap<String, Vec<f32>>),
+    Approximate(Hnsw),
+}
+
+// vectors read from one attribute
+#[derive(Debug)]
+pub struct VectorIndex {
+    name: String,
+    attribute: AttributePath,
+    dimensions: usize,
+    metric: Metric,
+    keys: HashMap<String, PrimaryKey>,
+    vectors: Vectors,
+}
+
+impl VectorIndex {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    #[inline]
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn is_approximate(&self) -> bool {
+        matches!(self.vectors, Vectors::Approximate(_))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // items whose attribute isn't a vector of the right size aren't indexed
+    pub fn put(&mut self, table_key: &PrimaryKey, item: &Item) {
+        let storage_key = table_key.to_storage_key();
+        self.remove(&storage_key);
+
+        let Some(vector) = self.vector(item) else {
+            return;
+        };
+        self.keys.insert(storage_key.clone(), table_key.clone());
+        match &mut self.vectors {
+            Vectors::Exact(vectors) => {
+                vectors.insert(storage_key, vector);
+            }
+            Vectors::Approximate(graph) => graph.insert(storage_key, vector),
+        }
+    }
+
+    pub fn delete(&mut self, table_key: &PrimaryKey) {
+        self.remove(&table_key.to_storage_key());
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        match &mut self.vectors {
+            Vectors::Exact(vectors) => vectors.clear(),
+            Vectors::Approximate(graph) => graph.clear(),
+        }
+    }
+
+    // up to k keys that pass `accept` with their distances, closest first
+    pub fn nearest(
+        &self,
+        query: &[f32],
+        k: usize,
+        accept: impl Fn(&PrimaryKey) -> bool,
+    ) -> Vec<(PrimaryKey, f32)> {
+        let accept = |storage_key: &str| accept(&self.keys[storage_key]);
+        let found: Vec<(&str, f32)> = match &self.vectors {
+            Vectors::Exact(vectors) => {
+                let mut scored: Vec<(&str, f32)> = vectors
+                    .iter()
+                    .filter(|(storage_key, _)| accept(storage_key))
+                    .map(|(storage_key, vector)| {
+                        (storage_key.as_str(), self.metric.distance(query, vector))
+                    })
+                    .collect();
+                // stable, so ties stay in storage key order
+                scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+                scored.truncate(k);
+                scored
+            }
+            Vectors::Approximate(graph) => graph.search(query, k, accept),
+        };
+        found
+            .into_iter()
+            .map(|(storage_key, distance)| (self.keys[storage_key].clone(), distance))
+            .collect()
+    }
+
+    fn remove(&mut self, storage_key: &str) {
+        if self.keys.remove(storage_key).is_none() {
+            return;
+        }
+        match &mut self.vectors {
+            Vectors::Exact(vectors) => {
+                vectors.remove(storage_key);
+            }
+            Vectors::Approximate(graph) => {
+                graph.remove(storage_key);
+            }
+        }
+    }
+
+    // a list of numbers or packed little-endian f32s
+    fn vector(&self, item: &Item) -> Option<Vec<f32>> {
+        let vector: Vec<f32> = match self.attribute.resolve(item)? {
+            AttributeValue::L(list) => list
+                .iter()
+                .map(|value| match value {
+                    AttributeValue::N(n) => n.parse().ok(),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+            AttributeValue::B(bytes) if bytes.len() % 4 == 0 => bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect(),
+            _ => return None,
+        };
+        (vector.len() == self.dimensions && vector.iter().all(|v| v.is_finite())).then_some(vector)
+    }
+}
+
+impl KeyIndex for VectorIndex {
+    fn name(&self) -> &str {
+        VectorIndex::name(self)
+    }
+
+    fn put(&mut self, table_key: &PrimaryKey, item: &Item) {
+        VectorIndex::put(self, table_key, item)
+    }
+
+    fn delete(&mut self, table_key: &PrimaryKey) {
+        VectorIndex::delete(self, table_key)
+    }
+
+    fn clear(&mut self) {
+        VectorIndex::clear(self)
+    }
+}
+
+pub struct VectorIndexBuilder {
+    name: String,
+    attribute: AttributePath,
+    dimensions: Option<usize>,
+    metric: Metric,
+    hnsw: Option<HnswConfig>,
+}
+
+impl VectorIndexBuilder {
+    // reads `embedding` unless told otherwise; exact cosine search by default
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            attribute: AttributePath::new("embedding"),
+            dimensions: None,
+            metric: Metric::default(),
+            hnsw: None,
+        }
+    }
+
+    pub fn attribute(mut self, path: impl Into<AttributePath>) -> Self {
+        self.attribute = path.into();
+        self
+    }
+
+    // required, and at least 1
+    pub fn dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn exact(mut self) -> Self {
+        self.hnsw = None;
+        self
+    }
+
+    // approximate search over an hnsw graph
+    pub fn hnsw(mut self, config: HnswConfig) -> Self {
+        self.hnsw = Some(config);
+        self
+    }
+
+    pub fn build(self) -> TableResult<VectorIndex> {
+        let dimensions = match self.dimensions {
+            Some(0) => {
+                return Err(TableError::validation_error(format!(
+                    "vector index {}: vectors need at least one dimension",
+                    self.name
+                )));
+            }
+            Some(dimensions) => dimensions,
+            None => {
+                return Err(TableError::validation_error(format!(
+                    "vector index {}: dimensions are required",
+                    self.name
+                )));
+            }
+        };
+        if let Some(config) = self.hnsw
+            && config.m < 2
+        {
+            return Err(TableError::validation_error(format!(
+                "vector index {}: hnsw needs at least 2 links per node",
+                self.name
+            )));
+        }
+        let vectors = match self.hnsw {
+            Some(config) => Vectors::Approximate(Hnsw::new(config, self.metric)),
+            None => Vectors::Exact(BTreeMap::new()),
+        };
+        Ok(VectorIndex {
+            name: self.name,
+            attribute: self.attribute,
+            dimensions,
+            metric: self.metric,
+            keys: HashMap::new(),
+            vectors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(id: &str, embedding: &[f32]) -> (PrimaryKey, Item) {
+        let values = embedding
+            .iter()
+            .map(|v| AttributeValue::N(v.to_string()))
+            .collect();
+        let item = Item::new()
+            .with_s("id", id)
+            .with("embedding", AttributeValue::L(values));
+        (PrimaryKey::simple(id), item)
+    }
+
+    fn ids(found: &[(PrimaryKey, f32)]) -> Vec<&str> {
+        found
+            .iter()
+            .map(|(key, _)| key.pk.as_s().unwrap())
+            .collect()
+    }
+
+    fn index(builder: VectorIndexBuilder) -> VectorIndex {
+        let mut index = builder.dimensions(2).metric(Metric::L2).build().unwrap();
+        for (key, item) in [
+            doc("a", &[0.0, 0.0]),
+            doc("b", &[1.0, 0.0]),
+            doc("c", &[0.0, 3.0]),
+            doc("d", &[5.0, 5.0]),
+        ] {
+            index.put(&key, &item);
+        }
+        index
+    }
+
+    #[test]
+    fn exact_and_approximate_agree_on_small_sets() {
+        for builder in [
+            VectorIndexBuilder::new("v"),
+            VectorIndexBuilder::new("v").hnsw(HnswConfig::new()),
+        ] {
+            let index = index(builder);
+            let found = index.nearest(&[0.9, 0.1], 3, |_| true);
+            assert_eq!(ids(&found), ["b", "a", "c"]);
+            assert!((found[0].1 - 0.141_421).abs() < 1e-4);
+
+            let found = index.nearest(&[0.9, 0.1], 2, |key| key.pk.as_s() != Some("b"));
+            assert_eq!(ids(&found), ["a", "c"]);
+        }
+    }
+
+    #[test]
+    fn packed_binary_vectors() {
+        let mut index = VectorIndexBuilder::new("v").dimensions(3).build().unwrap();
+        let item = Item::new().with_b("embedding", pack_vector(&[0.5, -1.0, 2.0]));
+        index.put(&PrimaryKey::simple("x"), &item);
+        assert_eq!(index.len(), 1);
+        assert!(index.nearest(&[1.0, -2.0, 4.0], 1, |_| true)[0].1.abs() < 1e-6);
+    }
+
+    #[test]
+    fn rewrites_deletes_and_invalid_vectors() {
+        let mut index = index(VectorIndexBuilder::new("v").hnsw(HnswConfig::new()));
+        let (key, item) = doc("d", &[0.1, 0.0]);
+        index.put(&key, &item);
+        assert_eq!(ids(&index.nearest(&[0.1, 0.0], 1, |_| true)), ["d"]);
+
+        // wrong length, non-numeric elements, missing attribute
+        let (key, item) = doc("a", &[1.0, 2.0, 3.0]);
+        index.put(&key, &item);
+        index.put(
+            &PrimaryKey::simple("b"),
+            &Item::new().with("embedding", AttributeValue::L(vec!["x".into(), 1.into()])),
+        );
+        index.put(&PrimaryKey::simple("c"), &Item::new());
+        assert_eq!(index.len(), 1);
+
+        index.delete(&PrimaryKey::simple("d"));
+        assert!(index.is_empty());
+        assert!(index.nearest(&[0.0, 0.0], 3, |_| true).is_empty());
+    }
+
+    #[test]
+    fn invalid_settings_fail_the_build() {
+        for builder in [
+            VectorIndexBuilder::new("v"),
+            VectorIndexBuilder::new("v").dimensions(0),
+            VectorIndexBuilder::new("v")
+                .dimensions(2)
+                .hnsw(HnswConfig::new().m(1)),
+        ] {
+            assert!(builder.build().unwrap_err().is_validation_error());
+        }
+    }
+}
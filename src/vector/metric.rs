@@ -0,0 +1,53 @@
+// every metric is a distance: smaller means closer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    // 1 - cosine similarity; zero vectors are 1 away from everything
+    #[default]
+    Cosine,
+    // negated dot product
+    Dot,
+    // euclidean distance
+    L2,
+}
+
+impl Metric {
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Self::Cosine => {
+                let norms = dot(a, a).sqrt() * dot(b, b).sqrt();
+                if norms == 0.0 {
+                    1.0
+                } else {
+                    1.0 - dot(a, b) / norms
+                }
+            }
+            Self::Dot => -dot(a, b),
+            Self::L2 => a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt(),
+        }
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        let a = [1.0, 0.0];
+        let b = [0.0, 2.0];
+        assert_eq!(Metric::Cosine.distance(&a, &b), 1.0);
+        assert!(Metric::Cosine.distance(&a, &[3.0, 0.0]).abs() < 1e-6);
+        assert_eq!(Metric::Cosine.distance(&a, &[0.0, 0.0]), 1.0);
+        assert_eq!(Metric::Dot.distance(&[1.0, 2.0], &[3.0, 4.0]), -11.0);
+        assert_eq!(Metric::L2.distance(&[0.0, 0.0], &[3.0, 4.0]), 5.0);
+    }
+}
@@ -0,0 +1,9 @@
+mod hnsw;
+mod index;
+mod metric;
+mod query;
+
+pub use hnsw::HnswConfig;
+pub use index::{VectorIndex, VectorIndexBuilder, pack_vector};
+pub use metric::Metric;
+pub use query::VectorHit;
@@ -0,0 +1,9 @@
+use crate::types::{Item, PrimaryKey};
+
+#[derive(Debug, Clone)]
+pub struct VectorHit {
+    pub key: PrimaryKey,
+    pub item: Item,
+    // under the index's metric; smaller is closer
+    pub distance: f32,
+}
@@ -1,6 +1,6 @@
 use nosquealdb::{
-    AttributeValue, DeleteRequest, GsiBuilder, Item, KeyCondition, KeySchema, KeyType, LsiBuilder,
-    PrimaryKey, PutRequest, QueryRequest, Table, TableBuilder, TransactWriteItem,
+    AttributeValue, DeleteRequest, GsiBuilder, Item, KeyCondition, KeySchema, KeyType, KeyValue,
+    LsiBuilder, PrimaryKey, PutRequest, QueryRequest, Table, TableBuilder, TransactWriteItem,
     TransactWriteRequest, UpdateExpression, UpdateRequest, condition::attr,
 };
 use std::collections::BTreeMap;
@@ -375,7 +375,8 @@ mod update {
             "by-status",
             KeySchema::simple("status", KeyType::S),
         ))
-        .build();
+        .build()
+        .unwrap();
 
         // set initial state
         table
@@ -532,8 +533,9 @@ mod transactions {
 
     #[test]
     fn reject_duplicate_keys() {
-        let mut table =
-            TableBuilder::new("users", KeySchema::simple("user_id", KeyType::S)).build();
+        let mut table = TableBuilder::new("users", KeySchema::simple("user_id", KeyType::S))
+            .build()
+            .unwrap();
 
         let result = table.transact_write(
             TransactWriteRequest::new()
@@ -546,7 +548,9 @@ mod transactions {
 
     #[test]
     fn atomic_transfer() {
-        let mut table = TableBuilder::new("account", KeySchema::simple("id", KeyType::S)).build();
+        let mut table = TableBuilder::new("account", KeySchema::simple("id", KeyType::S))
+            .build()
+            .unwrap();
         let items = vec![
             TransactWriteItem::put(Item::new().with_s("id", "a").with_n("balance", 100)),
             TransactWriteItem::put(Item::new().with_s("id", "b").with_n("balance", 200)),
@@ -600,6 +604,109 @@ mod transactions {
 
 mod gsi {
     use super::*;
+    use nosquealdb::condition::AttributePath;
+
+    #[test]
+    fn multi_valued_follows_set_updates() {
+        let mut table = TableBuilder::new("posts", KeySchema::simple("id", KeyType::S))
+            .with_gsi(
+                GsiBuilder::new("by-tag", KeySchema::simple("tag", KeyType::S))
+                    .multi_valued("tag")
+                    .keys_only(),
+            )
+            .build()
+            .unwrap();
+        let tags =
+            |values: &[&str]| AttributeValue::Ss(values.iter().map(|v| v.to_string()).collect());
+        let tagged = |table: &Table, tag: &str| -> Vec<String> {
+            table
+                .query_gsi("by-tag", KeyCondition::pk(tag))
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.get("id").unwrap().as_s().unwrap().to_string())
+                .collect()
+        };
+
+        table
+            .put_item(Item::new().with_s("id", "p1").with("tag", tags(&["rust"])))
+            .unwrap();
+        table
+            .put_item(
+                Item::new()
+                    .with_s("id", "p2")
+                    .with("tag", tags(&["rust", "db"])),
+            )
+            .unwrap();
+        assert_eq!(tagged(&table, "rust"), ["p1", "p2"]);
+
+        table
+            .update_item(
+                &PrimaryKey::simple("p1"),
+                UpdateExpression::new().add("tag", tags(&["db", "web"])),
+            )
+            .unwrap();
+        table
+            .update_item(
+                &PrimaryKey::simple("p2"),
+                UpdateExpression::new().delete("tag", tags(&["rust"])),
+            )
+            .unwrap();
+
+        assert_eq!(tagged(&table, "rust"), ["p1"]);
+        assert_eq!(tagged(&table, "db"), ["p1", "p2"]);
+        assert_eq!(tagged(&table, "web"), ["p1"]);
+        assert_eq!(table.gsi("by-tag").unwrap().len(), 4);
+    }
+
+    #[test]
+    fn keyed_by_nested_attribute() {
+        let mut table = TableBuilder::new("orders", KeySchema::simple("id", KeyType::S))
+            .with_gsi(
+                GsiBuilder::new("by-customer", KeySchema::simple("customer_id", KeyType::S))
+                    .key_path("customer_id", AttributePath::new("customer").key("id")),
+            )
+            .build()
+            .unwrap();
+
+        let mut customer = BTreeMap::new();
+        customer.insert("id".to_string(), AttributeValue::S("c1".into()));
+        table
+            .put_item(
+                Item::new()
+                    .with_s("id", "o1")
+                    .with("customer", AttributeValue::M(customer)),
+            )
+            .unwrap();
+        assert_eq!(
+            table
+                .query_gsi("by-customer", KeyCondition::pk("c1"))
+                .unwrap()
+                .count,
+            1
+        );
+
+        table
+            .update_item(
+                &PrimaryKey::simple("o1"),
+                UpdateExpression::new().set(AttributePath::new("customer").key("id"), "c2"),
+            )
+            .unwrap();
+        assert_eq!(
+            table
+                .query_gsi("by-customer", KeyCondition::pk("c1"))
+                .unwrap()
+                .count,
+            0
+        );
+        assert_eq!(
+            table
+                .query_gsi("by-customer", KeyCondition::pk("c2"))
+                .unwrap()
+                .count,
+            1
+        );
+    }
 
     #[test]
     fn nonexistent_index_returns_error() {
@@ -624,7 +731,8 @@ mod gsi {
             "by-status",
             KeySchema::simple("status", KeyType::S),
         ))
-        .build();
+        .build()
+        .unwrap();
 
         table
             .put_item(
@@ -671,7 +779,8 @@ mod gsi {
                 "by-status",
                 KeySchema::simple("status", KeyType::S),
             ))
-            .build();
+            .build()
+            .unwrap();
 
         // insert item with status. should appear in GSI
         table
@@ -701,6 +810,417 @@ mod gsi {
             .unwrap();
         assert!(item.is_some());
     }
+
+    #[test]
+    fn query_reads_only_matching_range() {
+        let mut table = TableBuilder::new(
+            "scores",
+            KeySchema::composite("player", KeyType::S, "game", KeyType::S),
+        )
+        .with_gsi(GsiBuilder::new(
+            "by-score",
+            KeySchema::composite("game", KeyType::S, "score", KeyType::N),
+        ))
+        .build()
+        .unwrap();
+
+        for (i, score) in [5, 120, -7, 33, 1000, 8, 0, 250].iter().enumerate() {
+            for game in ["chess", "go"] {
+                table
+                    .put_item(
+                        Item::new()
+                            .with_s("player", format!("p{}", i))
+                            .with_s("game", game)
+                            .with_n("score", *score),
+                    )
+                    .unwrap();
+            }
+        }
+
+        let scores = |result: nosquealdb::QueryResult| -> Vec<String> {
+            result
+                .items
+                .iter()
+                .map(|item| item.get("score").unwrap().as_n().unwrap().to_string())
+                .collect()
+        };
+
+        let result = table
+            .query_gsi("by-score", KeyCondition::pk("chess"))
+            .unwrap();
+        assert_eq!(
+            scores(result),
+            vec!["-7", "0", "5", "8", "33", "120", "250", "1000"]
+        );
+
+        let result = table
+            .query_gsi(
+                "by-score",
+                QueryRequest::new(
+                    KeyCondition::pk("go")
+                        .sk_between(KeyValue::N("8".into()), KeyValue::N("250".into())),
+                )
+                .reverse()
+                .limit(3),
+            )
+            .unwrap();
+        assert_eq!(result.scanned_count, 3);
+        assert_eq!(scores(result), vec!["250", "120", "33"]);
+    }
+
+    #[test]
+    fn too_many_key_attributes_fail_the_build() {
+        let gsi = ["b", "c", "d", "e"].into_iter().fold(
+            GsiBuilder::new("wide", KeySchema::simple("a", KeyType::S)),
+            |gsi, name| gsi.partition_key_attribute(name, KeyType::S),
+        );
+        let err = TableBuilder::new("events", KeySchema::simple("id", KeyType::S))
+            .with_gsi(gsi)
+            .build()
+            .unwrap_err();
+        assert!(err.is_validation_error());
+    }
+
+    #[test]
+    fn multi_attribute_keys() {
+        let mut table = TableBuilder::new("events", KeySchema::simple("id", KeyType::S))
+            .with_gsi(
+                GsiBuilder::new("by-location", KeySchema::simple("tenant", KeyType::S))
+                    .partition_key_attribute("region", KeyType::S)
+                    .sort_key_attribute("day", KeyType::S)
+                    .sort_key_attribute("seq", KeyType::N),
+            )
+            .build()
+            .unwrap();
+
+        for (id, region, day, seq) in [
+            ("e1", "eu", "2026-03-01", 2),
+            ("e2", "eu", "2026-03-01", 1),
+            ("e3", "eu", "2026-03-02", 1),
+            ("e4", "us", "2026-03-01", 1),
+        ] {
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("id", id)
+                        .with_s("tenant", "acme")
+                        .with_s("region", region)
+                        .with_s("day", day)
+                        .with_n("seq", seq),
+                )
+                .unwrap();
+        }
+
+        let ids = |result: nosquealdb::QueryResult| -> Vec<String> {
+            result
+                .items
+                .iter()
+                .map(|item| item.get("id").unwrap().as_s().unwrap().to_string())
+                .collect()
+        };
+
+        let result = table
+            .query_gsi(
+                "by-location",
+                KeyCondition::pk("acme").and_pk("eu").sk_eq("2026-03-01"),
+            )
+            .unwrap();
+        assert_eq!(ids(result), ["e2", "e1"]);
+
+        let result = table
+            .query_gsi(
+                "by-location",
+                QueryRequest::new(KeyCondition::pk("acme").and_pk("eu")).reverse(),
+            )
+            .unwrap();
+        assert_eq!(ids(result), ["e3", "e1", "e2"]);
+
+        // the table itself has single-attribute keys
+        let err = table
+            .query(KeyCondition::pk("e1").and_pk("eu"))
+            .unwrap_err();
+        assert!(err.is_validation_error());
+    }
+
+    #[test]
+    fn partial_index_follows_filter() {
+        let mut table = TableBuilder::new("tickets", KeySchema::simple("id", KeyType::S))
+            .with_gsi(
+                GsiBuilder::new("open-by-owner", KeySchema::simple("owner", KeyType::S))
+                    .filter(attr("status").eq("open")),
+            )
+            .build()
+            .unwrap();
+
+        for (id, status) in [("t1", "open"), ("t2", "closed"), ("t3", "open")] {
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("id", id)
+                        .with_s("owner", "sam")
+                        .with_s("status", status),
+                )
+                .unwrap();
+        }
+        let open = |table: &Table| {
+            table
+                .query_gsi("open-by-owner", KeyCondition::pk("sam"))
+                .unwrap()
+                .count
+        };
+        assert_eq!(open(&table), 2);
+
+        table
+            .update_item(
+                &PrimaryKey::simple("t1"),
+                UpdateExpression::new().set("status", "closed"),
+            )
+            .unwrap();
+        assert_eq!(open(&table), 1);
+
+        table
+            .update_item(
+                &PrimaryKey::simple("t2"),
+                UpdateExpression::new().set("status", "open"),
+            )
+            .unwrap();
+        assert_eq!(open(&table), 2);
+
+        table.delete_item(&PrimaryKey::simple("t3")).unwrap();
+        assert_eq!(open(&table), 1);
+        assert!(
+            table
+                .describe()
+                .gsi("open-by-owner")
+                .unwrap()
+                .filter
+                .is_some()
+        );
+    }
+}
+
+mod index_management {
+    use super::*;
+    use nosquealdb::Projection;
+
+    #[test]
+    fn table_from_json_definition() {
+        let definition = r#"{
+            "TableName": "users",
+            "AttributeDefinitions": [
+                {"AttributeName": "user_id", "AttributeType": "S"},
+                {"AttributeName": "email", "AttributeType": "S"}
+            ],
+            "KeySchema": [{"AttributeName": "user_id", "KeyType": "HASH"}],
+            "GlobalSecondaryIndexes": [{
+                "IndexName": "by_email",
+                "KeySchema": [{"AttributeName": "email", "KeyType": "HASH"}],
+                "Projection": {"ProjectionType": "KEYS_ONLY"}
+            }]
+        }"#;
+
+        let mut table = TableBuilder::from_json(definition)
+            .unwrap()
+            .build()
+            .unwrap();
+        table
+            .put_item(
+                Item::new()
+                    .with_s("user_id", "u1")
+                    .with_s("email", "a@example.com")
+                    .with_s("name", "Alice"),
+            )
+            .unwrap();
+
+        let result = table
+            .query_gsi("by_email", KeyCondition::pk("a@example.com"))
+            .unwrap();
+        assert_eq!(result.items.len(), 1);
+        assert!(!result.items[0].contains("name"));
+    }
+
+    fn table_with_gsi() -> Table {
+        let mut table = TableBuilder::new(
+            "orders",
+            KeySchema::composite("pk", KeyType::S, "sk", KeyType::S),
+        )
+        .with_gsi(GsiBuilder::new("by-status", KeySchema::simple("status", KeyType::S)).keys_only())
+        .build()
+        .unwrap();
+
+        for i in 0..3 {
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("pk", "user1")
+                        .with_s("sk", format!("order{}", i))
+                        .with_s("status", "open")
+                        .with_n("amount", i)
+                        .with_s("notes", "fragile"),
+                )
+                .unwrap();
+        }
+        table
+    }
+
+    #[test]
+    fn drop_gsi() {
+        let mut table = table_with_gsi();
+
+        table.drop_gsi("by-status").unwrap();
+        assert!(table.gsi("by-status").is_none());
+        assert_eq!(table.gsi_names().count(), 0);
+
+        let result = table.query_gsi("by-status", KeyCondition::pk("open"));
+        assert!(result.unwrap_err().is_index_not_found());
+
+        // base table untouched
+        assert_eq!(table.len(), 3);
+
+        assert!(
+            table
+                .drop_gsi("by-status")
+                .unwrap_err()
+                .is_index_not_found()
+        );
+    }
+
+    #[test]
+    fn update_gsi_projection_reprojects_entries() {
+        let mut table = table_with_gsi();
+
+        let result = table
+            .query_gsi("by-status", KeyCondition::pk("open"))
+            .unwrap();
+        assert!(!result.items[0].contains("amount"));
+
+        table
+            .update_gsi_projection("by-status", Projection::include(["amount"]))
+            .unwrap();
+        assert_eq!(
+            table.gsi("by-status").unwrap().projection(),
+            &Projection::include(["amount"])
+        );
+
+        let result = table
+            .query_gsi("by-status", KeyCondition::pk("open"))
+            .unwrap();
+        assert_eq!(result.count, 3);
+        assert!(result.items.iter().all(|item| item.contains("amount")));
+        assert!(result.items.iter().all(|item| !item.contains("notes")));
+
+        // later writes use the new projection
+        table
+            .put_item(
+                Item::new()
+                    .with_s("pk", "user2")
+                    .with_s("sk", "order0")
+                    .with_s("status", "open")
+                    .with_n("amount", 7),
+            )
+            .unwrap();
+        let result = table
+            .query_gsi("by-status", KeyCondition::pk("open"))
+            .unwrap();
+        assert_eq!(result.count, 4);
+        assert!(result.items.iter().all(|item| item.contains("amount")));
+    }
+
+    #[test]
+    fn online_gsi_backfill() {
+        use nosquealdb::IndexStatus;
+
+        let mut table = Table::new(
+            "orders",
+            KeySchema::composite("pk", KeyType::S, "sk", KeyType::S),
+        );
+        for i in 0..10 {
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("pk", "user1")
+                        .with_s("sk", format!("order{}", i))
+                        .with_s("status", "open"),
+                )
+                .unwrap();
+        }
+
+        table
+            .add_gsi_online(GsiBuilder::new(
+                "by-status",
+                KeySchema::simple("status", KeyType::S),
+            ))
+            .unwrap();
+        assert_eq!(
+            table.gsi("by-status").unwrap().status(),
+            IndexStatus::Creating
+        );
+        assert!(table.is_backfilling());
+
+        let result = table.query_gsi("by-status", KeyCondition::pk("open"));
+        assert!(result.unwrap_err().is_index_not_active());
+
+        assert_eq!(table.backfill_step(4).unwrap(), 4);
+        assert_eq!(table.gsi("by-status").unwrap().len(), 4);
+
+        // writes interleaved with the backfill
+        table
+            .put_item(
+                Item::new()
+                    .with_s("pk", "user2")
+                    .with_s("sk", "order0")
+                    .with_s("status", "open"),
+            )
+            .unwrap();
+        for i in 0..10 {
+            let key = PrimaryKey::composite("user1", format!("order{}", i));
+            if i % 3 == 0 {
+                table.delete_item(&key).unwrap();
+            } else if i % 3 == 1 {
+                table
+                    .update_item(&key, UpdateExpression::new().set("status", "closed"))
+                    .unwrap();
+            }
+        }
+
+        while table.backfill_step(3).unwrap() > 0 {}
+        assert!(!table.is_backfilling());
+        assert_eq!(
+            table.describe().gsi("by-status").unwrap().status,
+            IndexStatus::Active
+        );
+
+        // i in {2, 5, 8} stay open, plus user2
+        let open = table
+            .query_gsi("by-status", KeyCondition::pk("open"))
+            .unwrap();
+        assert_eq!(open.count, 4);
+        let closed = table
+            .query_gsi("by-status", KeyCondition::pk("closed"))
+            .unwrap();
+        assert_eq!(closed.count, 3);
+        assert_eq!(table.gsi("by-status").unwrap().len(), table.len());
+    }
+
+    #[test]
+    fn online_gsi_on_empty_table_is_active() {
+        let mut table = Table::new("orders", KeySchema::simple("pk", KeyType::S));
+        table
+            .add_gsi_online(GsiBuilder::new(
+                "by-status",
+                KeySchema::simple("status", KeyType::S),
+            ))
+            .unwrap();
+        assert!(table.gsi("by-status").unwrap().is_active());
+        assert_eq!(table.backfill_step(10).unwrap(), 0);
+    }
+
+    #[test]
+    fn update_unknown_gsi_fails() {
+        let mut table = table_with_gsi();
+        let result = table.update_gsi_projection("missing", Projection::All);
+        assert!(result.unwrap_err().is_index_not_found());
+    }
 }
 
 mod lsi {
@@ -724,7 +1244,8 @@ mod lsi {
         let schema = KeySchema::composite("pk", KeyType::S, "sk", KeyType::S);
         let mut table = TableBuilder::new("test", schema)
             .with_lsi(LsiBuilder::new("by-date", "date", KeyType::S))
-            .build();
+            .build()
+            .unwrap();
 
         table
             .put_item(
@@ -868,7 +1389,8 @@ mod conditional_write {
             "by-status",
             KeySchema::simple("status", KeyType::S),
         ))
-        .build();
+        .build()
+        .unwrap();
 
         table
             .put(
@@ -919,7 +1441,8 @@ mod projection {
 
         let mut table = TableBuilder::new("test", table_schema)
             .with_gsi(GsiBuilder::new("by-gsi", gsi_schema).keys_only())
-            .build();
+            .build()
+            .unwrap();
 
         table
             .put_item(
@@ -957,7 +1480,8 @@ mod projection {
 
         let mut table = TableBuilder::new("test", table_schema)
             .with_gsi(GsiBuilder::new("by-category", gsi_schema).include(["name", "price"]))
-            .build();
+            .build()
+            .unwrap();
 
         table
             .put_item(
@@ -995,6 +1519,7 @@ mod binary {
     use super::*;
 
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn it_works() {
         let mut table = Table::new(
             "test",
@@ -1081,7 +1606,8 @@ mod batch {
             "by-status",
             KeySchema::simple("status", KeyType::S),
         ))
-        .build();
+        .build()
+        .unwrap();
 
         table
             .batch_write(
@@ -1162,10 +1688,1977 @@ mod scan {
     }
 }
 
+mod select {
+    use super::*;
+    use nosquealdb::{ScanRequest, Select};
+
+    fn orders_table() -> Table {
+        let mut table = TableBuilder::new(
+            "orders",
+            KeySchema::composite("user", KeyType::S, "order", KeyType::S),
+        )
+        .with_gsi(GsiBuilder::new("by-status", KeySchema::simple("status", KeyType::S)).keys_only())
+        .build()
+        .unwrap();
+
+        for i in 0..6 {
+            let status = if i % 2 == 0 { "open" } else { "closed" };
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("user", "u1")
+                        .with_s("order", format!("o{}", i))
+                        .with_s("status", status)
+                        .with_n("amount", i * 10),
+                )
+                .unwrap();
+        }
+        table
+            .put_item(
+                Item::new()
+                    .with_s("user", "u2")
+                    .with_s("order", "o0")
+                    .with_s("status", "open"),
+            )
+            .unwrap();
+        table
+    }
+
+    #[test]
+    fn count_query() {
+        let mut table = orders_table();
+
+        let result = table
+            .query(QueryRequest::new(KeyCondition::pk("u1")).select(Select::Count))
+            .unwrap();
+        assert_eq!(result.count, 6);
+        assert!(result.items.is_empty());
+
+        let result = table
+            .query(
+                QueryRequest::new(KeyCondition::pk("u1"))
+                    .filter(attr("status").eq("open"))
+                    .select(Select::Count),
+            )
+            .unwrap();
+        assert_eq!(result.count, 3);
+        assert_eq!(result.scanned_count, 7);
+        assert!(result.items.is_empty());
+    }
+
+    #[test]
+    fn count_with_limit() {
+        let mut table = orders_table();
+
+        let result = table
+            .query(
+                QueryRequest::new(KeyCondition::pk("u1"))
+                    .limit(4)
+                    .select(Select::Count),
+            )
+            .unwrap();
+        assert_eq!(result.count, 4);
+        assert!(result.items.is_empty());
+    }
+
+    #[test]
+    fn count_gsi() {
+        let table = orders_table();
+
+        let result = table
+            .query_gsi(
+                "by-status",
+                QueryRequest::new(KeyCondition::pk("open")).select(Select::Count),
+            )
+            .unwrap();
+        assert_eq!(result.count, 4);
+        assert!(result.items.is_empty());
+    }
+
+    #[test]
+    fn specific_attributes() {
+        let mut table = orders_table();
+
+        let result = table
+            .query(
+                QueryRequest::new(KeyCondition::pk("u1").sk_eq("o2"))
+                    .select(Select::specific(["order", "amount"])),
+            )
+            .unwrap();
+        assert_eq!(result.count, 1);
+        let item = &result.items[0];
+        assert_eq!(item.len(), 2);
+        assert_eq!(item.get("amount"), Some(&AttributeValue::N("20".into())));
+    }
+
+    #[test]
+    fn all_projected_attributes() {
+        let mut table = orders_table();
+
+        let result = table
+            .query_gsi(
+                "by-status",
+                QueryRequest::new(KeyCondition::pk("closed"))
+                    .select(Select::AllProjectedAttributes),
+            )
+            .unwrap();
+        assert_eq!(result.count, 3);
+        assert!(!result.items[0].contains("amount"));
+
+        // base table has nothing to project
+        let result = table.query(
+            QueryRequest::new(KeyCondition::pk("u1")).select(Select::AllProjectedAttributes),
+        );
+        assert!(result.unwrap_err().is_validation_error());
+    }
+
+    #[test]
+    fn scan_count() {
+        let table = orders_table();
+
+        let result = table
+            .scan_with_counts(
+                ScanRequest::new()
+                    .filter(attr("status").eq("closed"))
+                    .select(Select::Count),
+            )
+            .unwrap();
+        assert_eq!(result.count, 3);
+        assert_eq!(result.scanned_count, 7);
+        assert!(result.items.is_empty());
+
+        let items = table
+            .scan(ScanRequest::new().select(Select::specific(["user"])))
+            .unwrap();
+        assert_eq!(items.len(), 7);
+        assert!(items.iter().all(|item| item.len() == 1));
+    }
+}
+
+mod json_lines {
+    use super::*;
+
+    #[test]
+    fn export_import_roundtrip() {
+        let mut source = Table::new(
+            "src",
+            KeySchema::composite("pk", KeyType::S, "sk", KeyType::N),
+        );
+        for i in 0..5 {
+            source
+                .put_item(
+                    Item::new()
+                        .with_s("pk", format!("user{}", i % 2))
+                        .with_n("sk", i)
+                        .with_b("data", vec![i as u8, 0xff])
+                        .with_l("tags", vec![AttributeValue::S("x\ny".into())]),
+                )
+                .unwrap();
+        }
+
+        let mut buffer = Vec::new();
+        assert_eq!(source.export_json_lines(&mut buffer).unwrap(), 5);
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert_eq!(text.lines().count(), 5);
+        assert!(text.lines().all(|line| line.starts_with("{\"Item\":{")));
+
+        let mut target = Table::new(
+            "dst",
+            KeySchema::composite("pk", KeyType::S, "sk", KeyType::N),
+        );
+        let result = target.import_json_lines(buffer.as_slice()).unwrap();
+        assert!(result.is_success());
+        assert_eq!(result.imported, 5);
+
+        let mut expected = source.scan_all().unwrap();
+        let mut actual = target.scan_all().unwrap();
+        expected.sort_by_key(|item| item.get("sk").unwrap().as_n().unwrap().to_string());
+        actual.sort_by_key(|item| item.get("sk").unwrap().as_n().unwrap().to_string());
+        assert_eq!(actual, expected);
+
+        // export order is deterministic
+        let mut again = Vec::new();
+        target.export_json_lines(&mut again).unwrap();
+        assert_eq!(again, buffer);
+    }
+
+    #[test]
+    fn export_orders_numeric_keys_by_value() {
+        let mut table = Table::new("test", KeySchema::simple("id", KeyType::N));
+        for id in [10, 9, 100, -1] {
+            table.put_item(Item::new().with_n("id", id)).unwrap();
+        }
+
+        let mut buffer = Vec::new();
+        table.export_json_lines(&mut buffer).unwrap();
+        let ids: Vec<_> = String::from_utf8(buffer)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let line = line.strip_prefix("{\"Item\":").unwrap();
+                let item = nosquealdb::item_from_dynamodb_json(&line[..line.len() - 1]).unwrap();
+                item.get("id").unwrap().as_n().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(ids, ["-1", "9", "10", "100"]);
+    }
+
+    #[test]
+    fn import_skips_a_byte_order_mark() {
+        let input = "\u{FEFF}{\"Item\": {\"pk\": {\"S\": \"a\"}}}\n{\"pk\": {\"S\": \"b\"}}\n";
+        let mut table = Table::new("test", KeySchema::simple("pk", KeyType::S));
+        let result = table.import_json_lines(input.as_bytes()).unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(result.imported, 2);
+    }
+
+    #[test]
+    fn import_reports_bad_lines() {
+        let input = concat!(
+            "{\"pk\": {\"S\": \"a\"}}\n",
+            "\n",
+            "{\"pk\": {\"S\": \"b\"\n",
+            "{\"other\": {\"S\": \"c\"}}\n",
+            "{\"Item\": {\"pk\": {\"S\": \"d\"}, \"n\": {\"N\": \"1.5\"}}}\n",
+        );
+
+        let mut table = Table::new("test", KeySchema::simple("pk", KeyType::S));
+        let result = table.import_json_lines(input.as_bytes()).unwrap();
+
+        assert_eq!(result.imported, 2);
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.errors[0].line, 3);
+        assert!(result.errors[0].message.contains("invalid json"));
+        assert_eq!(result.errors[1].line, 4);
+        assert!(result.errors[1].message.contains("invalid key"));
+        assert_eq!(table.len(), 2);
+    }
+}
+
+mod csv {
+    use super::*;
+    use nosquealdb::{ColumnType, CsvMapping, KeyValue, condition::AttributePath};
+
+    fn products() -> Table {
+        Table::new(
+            "products",
+            KeySchema::composite("category", KeyType::S, "sku", KeyType::N),
+        )
+    }
+
+    fn mapping() -> CsvMapping {
+        CsvMapping::new()
+            .key("category", ColumnType::S)
+            .key_as("SKU", "sku", ColumnType::N)
+            .column("price", ColumnType::N)
+            .column("in_stock", ColumnType::Bool)
+            .column("tags", ColumnType::ss())
+            .column("details", ColumnType::Json)
+    }
+
+    #[test]
+    fn import_with_type_mapping() {
+        let input = "\
+category,SKU,price,in_stock,tags,details,ignored
+tools,1,9.99,true,red;blue,\"{\"\"weight\"\": 2}\",x
+tools,2,,no,,,
+,3,1,true,,,
+toys,abc,1,true,,,
+toys,4,1,true,,[1,
+toys,5,1
+toys,6,5,false,\"big, shiny\",\"[\"\"a\"\"]\",
+";
+        let mut table = products();
+        let result = table.import_csv(input.as_bytes(), &mapping()).unwrap();
+
+        assert_eq!(result.imported, 3);
+        let lines: Vec<_> = result.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![4, 5, 6, 7]);
+        assert!(result.errors[0].message.contains("invalid key"));
+        assert!(result.errors[1].message.contains("column SKU"));
+        assert!(result.errors[2].message.contains("column details"));
+        assert!(result.errors[3].message.contains("expected 7 fields"));
+
+        let first = table
+            .get_item(&PrimaryKey::composite("tools", KeyValue::N("1".into())))
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.get("price"), Some(&AttributeValue::N("9.99".into())));
+        assert_eq!(first.get("in_stock"), Some(&AttributeValue::Bool(true)));
+        assert_eq!(
+            first.get("tags"),
+            Some(&AttributeValue::Ss(
+                ["blue".to_string(), "red".to_string()].into()
+            ))
+        );
+        let details = first.get("details").unwrap().as_m().unwrap();
+        assert_eq!(details.get("weight"), Some(&AttributeValue::N("2".into())));
+        assert!(!first.contains("ignored"));
+
+        let second = table
+            .get_item(&PrimaryKey::composite("tools", KeyValue::N("2".into())))
+            .unwrap()
+            .unwrap();
+        assert!(!second.contains("price"));
+        assert!(!second.contains("tags"));
+    }
+
+    #[test]
+    fn set_cells_without_elements_are_row_errors() {
+        let input =
+            "category,SKU,price,in_stock,tags,details\ntools,1,1,true, ; ,\ntools,2,1,true,a,\n";
+        let mut table = products();
+        let result = table.import_csv(input.as_bytes(), &mapping()).unwrap();
+
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].line, 2);
+        assert_eq!(result.errors[0].message, "column tags: empty set: \" ; \"");
+    }
+
+    #[test]
+    fn spreadsheet_exports_with_bom_and_blank_lines() {
+        let input = "\u{FEFF}category,SKU,price\r\ntools,1,9.99\r\n\r\ntools,2,5\r\n\r\n";
+        let mapping = CsvMapping::new()
+            .key("category", ColumnType::S)
+            .key_as("SKU", "sku", ColumnType::N)
+            .column("price", ColumnType::N);
+        let mut table = products();
+        let result = table.import_csv(input.as_bytes(), &mapping).unwrap();
+
+        assert_eq!(result.imported, 2);
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn unique_conflicts_are_row_errors() {
+        let mut table = products();
+        table
+            .add_gsi(GsiBuilder::new("by-code", KeySchema::simple("code", KeyType::S)).unique())
+            .unwrap();
+        let mapping = CsvMapping::new()
+            .key("category", ColumnType::S)
+            .key_as("SKU", "sku", ColumnType::N)
+            .column("code", ColumnType::S);
+        let input = "category,SKU,code\ntools,1,A\ntools,2,B\ntools,3,A\n";
+        let result = table.import_csv(input.as_bytes(), &mapping).unwrap();
+
+        assert_eq!(result.imported, 2);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].line, 4);
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn mapping_must_cover_key_schema() {
+        let mut table = products();
+        let mapping = CsvMapping::new().key("category", ColumnType::S);
+        let err = table
+            .import_csv("category\ntools\n".as_bytes(), &mapping)
+            .unwrap_err();
+        assert!(err.is_validation_error());
+
+        let err = table
+            .import_csv("category,price\ntools,1\n".as_bytes(), &self::mapping())
+            .unwrap_err();
+        assert!(err.is_validation_error());
+    }
+
+    #[test]
+    fn import_writes_in_batches() {
+        let mut input = String::from("category,SKU,price,in_stock,tags,details\n");
+        for i in 0..60 {
+            input.push_str(&format!("bulk,{},1,true,,\n", i));
+        }
+
+        let mut table = products();
+        let result = table.import_csv(input.as_bytes(), &mapping()).unwrap();
+        assert!(result.is_success());
+        assert_eq!(result.imported, 60);
+        assert_eq!(table.len(), 60);
+    }
+
+    #[test]
+    fn export_flattens_paths() {
+        let mut table = products();
+        let input = "\
+category,SKU,price,in_stock,tags,details
+tools,2,5,true,a;b,\"{\"\"dims\"\": {\"\"w\"\": 3}, \"\"note\"\": \"\"x,y\"\"}\"
+tools,1,9.99,false,,
+";
+        table.import_csv(input.as_bytes(), &mapping()).unwrap();
+
+        let mut out = Vec::new();
+        let count = table
+            .export_csv(
+                &mut out,
+                [
+                    AttributePath::new("category"),
+                    AttributePath::new("sku"),
+                    AttributePath::new("tags"),
+                    AttributePath::new("details").key("dims").key("w"),
+                    AttributePath::new("details").key("note"),
+                ],
+            )
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\
+category,sku,tags,details.dims.w,details.note
+tools,1,,,
+tools,2,a;b,3,\"x,y\"
+"
+        );
+    }
+}
+
+mod conversions {
+    use super::*;
+    use nosquealdb::{KeyValue, impl_item};
+    use std::collections::BTreeSet;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Order {
+        customer: String,
+        order_id: u32,
+        total: f64,
+        items: Vec<String>,
+        labels: BTreeSet<String>,
+        note: Option<String>,
+    }
+    impl_item!(Order {
+        customer => "pk",
+        order_id => "sk",
+        total,
+        items,
+        labels,
+        note,
+    });
+
+    #[test]
+    fn structs_through_table() {
+        let mut table = Table::new(
+            "orders",
+            KeySchema::composite("pk", KeyType::S, "sk", KeyType::N),
+        );
+        let order = Order {
+            customer: "c1".into(),
+            order_id: 7,
+            total: 19.5,
+            items: vec!["book".into(), "pen".into()],
+            labels: ["gift".to_string()].into(),
+            note: None,
+        };
+        table.put_item(order.clone().try_into().unwrap()).unwrap();
+
+        let item = table
+            .get_item(&PrimaryKey::composite("c1", KeyValue::N("7".into())))
+            .unwrap()
+            .unwrap();
+        assert_eq!(item.get_as::<f64>("total"), Ok(19.5));
+        assert_eq!(Order::try_from(item), Ok(order));
+    }
+}
+
+mod entities {
+    use super::*;
+    use nosquealdb::entity::GsiKeyTemplate;
+    use nosquealdb::{Entity, Repository, entity_enum, impl_item};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct User {
+        id: String,
+        email: String,
+        name: String,
+    }
+    impl_item!(User { id, email, name });
+
+    impl Entity for User {
+        const TYPE: &'static str = "User";
+        const PK: &'static str = "USER#{id}";
+        const SK: Option<&'static str> = Some("PROFILE");
+        const GSI_KEYS: &'static [GsiKeyTemplate] =
+            &[GsiKeyTemplate::new("gsi1pk", "EMAIL#{email}").sort_key("gsi1sk", "USER")];
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Order {
+        user_id: String,
+        ts: u64,
+        total: f64,
+    }
+    impl_item!(Order { user_id, ts, total });
+
+    impl Entity for Order {
+        const TYPE: &'static str = "Order";
+        const PK: &'static str = "USER#{user_id}";
+        const SK: Option<&'static str> = Some("ORDER#{ts}");
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum UserData {
+        User(User),
+        Order(Order),
+    }
+    entity_enum!(UserData {
+        User(User),
+        Order(Order)
+    });
+
+    fn table() -> Table {
+        TableBuilder::new(
+            "app",
+            KeySchema::composite("pk", KeyType::S, "sk", KeyType::S),
+        )
+        .with_gsi(GsiBuilder::new(
+            "gsi1",
+            KeySchema::composite("gsi1pk", KeyType::S, "gsi1sk", KeyType::S),
+        ))
+        .build()
+        .unwrap()
+    }
+
+    fn seed(table: &mut Table) -> (User, Vec<Order>) {
+        let user = User {
+            id: "42".into(),
+            email: "ada@example.com".into(),
+            name: "Ada".into(),
+        };
+        Repository::new(table).put(&user).unwrap();
+
+        let orders: Vec<_> = [100, 200]
+            .into_iter()
+            .map(|ts| Order {
+                user_id: "42".into(),
+                ts,
+                total: ts as f64 / 10.0,
+            })
+            .collect();
+        let mut repo = Repository::<Order>::new(table);
+        for order in &orders {
+            repo.put(order).unwrap();
+        }
+        (user, orders)
+    }
+
+    #[test]
+    fn composed_keys_and_discriminator() {
+        let mut table = table();
+        let (user, _) = seed(&mut table);
+
+        let raw = table
+            .get_item(&PrimaryKey::composite("USER#42", "PROFILE"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            raw.get("entity_type"),
+            Some(&AttributeValue::S("User".into()))
+        );
+        assert_eq!(
+            raw.get("gsi1pk"),
+            Some(&AttributeValue::S("EMAIL#ada@example.com".into()))
+        );
+
+        let repo = Repository::<User>::new(&mut table);
+        assert_eq!(repo.get(&[("id", "42")]).unwrap(), Some(user));
+        assert_eq!(repo.get(&[("id", "7")]).unwrap(), None);
+        assert!(repo.get(&[]).unwrap_err().is_validation_error());
+
+        let by_email = table
+            .query_gsi("gsi1", KeyCondition::pk("EMAIL#ada@example.com"))
+            .unwrap();
+        assert_eq!(by_email.items.len(), 1);
+    }
+
+    #[test]
+    fn query_children_decodes_variants() {
+        let mut table = table();
+        let (user, orders) = seed(&mut table);
+
+        let mut repo = Repository::<User>::new(&mut table);
+        let only_orders: Vec<Order> = repo.query_children(&[("id", "42")]).unwrap();
+        assert_eq!(only_orders, orders);
+
+        let everything: Vec<UserData> = repo.query_children(&[("id", "42")]).unwrap();
+        assert_eq!(
+            everything,
+            vec![
+                UserData::Order(orders[0].clone()),
+                UserData::Order(orders[1].clone()),
+                UserData::User(user),
+            ]
+        );
+    }
+
+    #[test]
+    fn get_checks_discriminator_and_delete_returns_entity() {
+        let mut table = table();
+        seed(&mut table);
+
+        let mut repo = Repository::<Order>::new(&mut table);
+        let deleted = repo.delete(&[("user_id", "42"), ("ts", "100")]).unwrap();
+        assert_eq!(deleted.map(|o| o.ts), Some(100));
+        assert_eq!(repo.get(&[("user_id", "42"), ("ts", "100")]).unwrap(), None);
+
+        // same key shape, wrong entity type
+        table
+            .put_item(
+                Item::new()
+                    .with_s("pk", "USER#1")
+                    .with_s("sk", "ORDER#5")
+                    .with_s("entity_type", "Invoice"),
+            )
+            .unwrap();
+        let repo = Repository::<Order>::new(&mut table);
+        let err = repo.get(&[("user_id", "1"), ("ts", "5")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("expected entity type Order, found Invoice")
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Invite {
+        id: String,
+        email: Option<String>,
+        code: Option<String>,
+    }
+    impl_item!(Invite { id, email, code });
+
+    impl Entity for Invite {
+        const TYPE: &'static str = "Invite";
+        const PK: &'static str = "INVITE#{id}";
+        const SK: Option<&'static str> = Some("INVITE");
+        const GSI_KEYS: &'static [GsiKeyTemplate] =
+            &[GsiKeyTemplate::new("gsi1pk", "EMAIL#{email}").sort_key("gsi1sk", "CODE#{code}")];
+    }
+
+    #[test]
+    fn gsi_keys_are_sparse() {
+        let mut table = table();
+        let invite = |id: &str, email: Option<&str>, code: Option<&str>| Invite {
+            id: id.into(),
+            email: email.map(Into::into),
+            code: code.map(Into::into),
+        };
+        let repo = Repository::<Invite>::new(&mut table);
+
+        // a missing field in either key leaves the whole index out
+        for entity in [
+            invite("1", None, Some("x")),
+            invite("2", Some("a@example.com"), None),
+        ] {
+            let item = repo.to_item(&entity).unwrap();
+            assert!(!item.contains("gsi1pk"), "{:?}", entity);
+            assert!(!item.contains("gsi1sk"), "{:?}", entity);
+        }
+
+        let item = repo
+            .to_item(&invite("3", Some("a@example.com"), Some("x")))
+            .unwrap();
+        assert_eq!(
+            item.get("gsi1sk"),
+            Some(&AttributeValue::S("CODE#x".into()))
+        );
+    }
+}
+
+mod iterators {
+    use super::*;
+    use nosquealdb::ScanRequest;
+
+    fn orders_table() -> Table {
+        let mut table = Table::new(
+            "orders",
+            KeySchema::composite("customer", KeyType::S, "order", KeyType::N),
+        );
+        for customer in ["alice", "bob"] {
+            for order in 0..250 {
+                table
+                    .put_item(
+                        Item::new()
+                            .with_s("customer", customer)
+                            .with_n("order", order)
+                            .with_bool("shipped", order % 4 == 0),
+                    )
+                    .unwrap();
+            }
+        }
+        table
+    }
+
+    #[test]
+    fn query_iter_matches_query() {
+        let mut table = orders_table();
+
+        let requests = [
+            QueryRequest::new(KeyCondition::pk("alice")),
+            QueryRequest::new(KeyCondition::pk("alice")).reverse(),
+            QueryRequest::new(KeyCondition::pk("bob").sk_gt(KeyValue::N("17".into()))).limit(130),
+            QueryRequest::new(KeyCondition::pk("bob"))
+                .reverse()
+                .filter(attr("shipped").eq(true))
+                .limit(101),
+        ];
+
+        for request in requests {
+            let expected = table.query(request.clone()).unwrap().items;
+            let streamed: Vec<Item> = table
+                .query_iter(request)
+                .unwrap()
+                .page_size(32)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(streamed, expected);
+        }
+    }
+
+    #[test]
+    fn query_iter_is_lazy() {
+        let table = orders_table();
+        let first: Vec<Item> = table
+            .query_iter(KeyCondition::pk("alice"))
+            .unwrap()
+            .take(2)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[1].get("order"), Some(&AttributeValue::N("1".into())));
+    }
+
+    #[test]
+    fn scan_iter_matches_scan() {
+        let table = orders_table();
+        let request = ScanRequest::new()
+            .filter(attr("shipped").eq(true))
+            .limit(40);
+
+        let streamed: Vec<Item> = table
+            .scan_iter(request.clone())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(streamed, table.scan(request).unwrap());
+        assert_eq!(table.scan_iter(ScanRequest::new()).unwrap().count(), 500);
+    }
+}
+
+mod unique_indexes {
+    use super::*;
+    use nosquealdb::{BatchWriteRequest, TableError};
+
+    fn user(id: &str, email: &str) -> Item {
+        Item::new().with_s("id", id).with_s("email", email)
+    }
+
+    fn users_table() -> Table {
+        let mut table = TableBuilder::new("users", KeySchema::simple("id", KeyType::S))
+            .with_gsi(GsiBuilder::new("by-email", KeySchema::simple("email", KeyType::S)).unique())
+            .build()
+            .unwrap();
+        table.put_item(user("u1", "ann@example.com")).unwrap();
+        table.put_item(user("u2", "bob@example.com")).unwrap();
+        table
+    }
+
+    fn owner(table: &Table, email: &str) -> Option<String> {
+        let result = table
+            .query_gsi("by-email", KeyCondition::pk(email))
+            .unwrap();
+        assert!(result.count <= 1);
+        result
+            .items
+            .first()
+            .map(|item| item.get("id").unwrap().as_s().unwrap().to_string())
+    }
+
+    #[test]
+    fn put_and_update_reject_duplicates() {
+        let mut table = users_table();
+
+        let err = table.put_item(user("u3", "ann@example.com")).unwrap_err();
+        assert!(err.is_unique_violation());
+        assert_eq!(
+            err,
+            TableError::unique_violation(
+                "by-email",
+                vec![PrimaryKey::simple("u1"), PrimaryKey::simple("u3")]
+            )
+        );
+        assert!(table.get_item(&PrimaryKey::simple("u3")).unwrap().is_none());
+
+        // rewriting the owner is fine
+        table
+            .put_item(user("u1", "ann@example.com").with_n("age", 30))
+            .unwrap();
+
+        let err = table
+            .update_item(
+                &PrimaryKey::simple("u2"),
+                UpdateExpression::new().set("email", "ann@example.com"),
+            )
+            .unwrap_err();
+        assert!(err.is_unique_violation());
+        assert_eq!(owner(&table, "bob@example.com").as_deref(), Some("u2"));
+
+        // freed values can be claimed
+        table.delete_item(&PrimaryKey::simple("u1")).unwrap();
+        table
+            .update_item(
+                &PrimaryKey::simple("u2"),
+                UpdateExpression::new().set("email", "ann@example.com"),
+            )
+            .unwrap();
+        assert_eq!(owner(&table, "ann@example.com").as_deref(), Some("u2"));
+    }
+
+    #[test]
+    fn batch_write_is_checked_as_a_whole() {
+        let mut table = users_table();
+
+        let request = BatchWriteRequest::new()
+            .put(user("u3", "cat@example.com"))
+            .put(user("u4", "cat@example.com"));
+        assert!(
+            table
+                .batch_write(request)
+                .unwrap_err()
+                .is_unique_violation()
+        );
+        assert!(table.get_item(&PrimaryKey::simple("u3")).unwrap().is_none());
+
+        // the old owner is deleted in the same batch
+        let request = BatchWriteRequest::new()
+            .put(user("u3", "ann@example.com"))
+            .delete(PrimaryKey::simple("u1"));
+        let result = table.batch_write(request).unwrap();
+        assert_eq!(result.processed_count, 2);
+        assert_eq!(owner(&table, "ann@example.com").as_deref(), Some("u3"));
+    }
+
+    #[test]
+    fn transactions_see_their_own_writes() {
+        let mut table = users_table();
+
+        // swapping values never leaves a duplicate behind
+        table
+            .transact_write(
+                TransactWriteRequest::new()
+                    .update(
+                        PrimaryKey::simple("u1"),
+                        UpdateExpression::new().set("email", "bob@example.com"),
+                    )
+                    .update(
+                        PrimaryKey::simple("u2"),
+                        UpdateExpression::new().set("email", "ann@example.com"),
+                    ),
+            )
+            .unwrap();
+        assert_eq!(owner(&table, "bob@example.com").as_deref(), Some("u1"));
+
+        let err = table
+            .transact_write(
+                TransactWriteRequest::new()
+                    .put(user("u3", "dan@example.com"))
+                    .put(user("u4", "bob@example.com")),
+            )
+            .unwrap_err();
+        assert!(err.is_unique_violation());
+        assert_eq!(err.cancellation_reasons().unwrap()[0].index(), 1);
+        assert!(table.get_item(&PrimaryKey::simple("u3")).unwrap().is_none());
+    }
+
+    #[test]
+    fn creating_index_over_duplicates_fails() {
+        let mut table = Table::new("users", KeySchema::simple("id", KeyType::S));
+        for (id, email) in [("u1", "a@x"), ("u2", "b@x"), ("u3", "a@x")] {
+            table.put_item(user(id, email)).unwrap();
+        }
+
+        let builder = GsiBuilder::new("by-email", KeySchema::simple("email", KeyType::S)).unique();
+        let err = table.add_gsi_online(builder).unwrap_err();
+        assert_eq!(
+            err,
+            TableError::unique_violation(
+                "by-email",
+                vec![PrimaryKey::simple("u1"), PrimaryKey::simple("u3")]
+            )
+        );
+        assert!(table.gsi("by-email").is_none());
+
+        table.delete_item(&PrimaryKey::simple("u3")).unwrap();
+        table
+            .add_gsi(GsiBuilder::new("by-email", KeySchema::simple("email", KeyType::S)).unique())
+            .unwrap();
+        assert!(table.describe().gsi("by-email").unwrap().unique);
+    }
+}
+
+mod eventual_consistency {
+    use super::*;
+    use nosquealdb::{EventualConsistency, GetRequest, ManualClock};
+    use std::time::Duration;
+
+    const LAG: Duration = Duration::from_millis(100);
+
+    fn order(id: &str, status: &str) -> Item {
+        Item::new()
+            .with_s("customer", "c1")
+            .with_s("id", id)
+            .with_s("status", status)
+    }
+
+    fn orders_table(clock: &ManualClock) -> Table {
+        TableBuilder::new(
+            "orders",
+            KeySchema::composite("customer", KeyType::S, "id", KeyType::S),
+        )
+        .with_gsi(GsiBuilder::new(
+            "by-status",
+            KeySchema::simple("status", KeyType::S),
+        ))
+        .with_eventual_consistency(EventualConsistency::new(LAG).with_clock(clock.clone()))
+        .build()
+        .unwrap()
+    }
+
+    fn with_status(table: &Table, status: &str) -> usize {
+        table
+            .query_gsi("by-status", KeyCondition::pk(status))
+            .unwrap()
+            .count
+    }
+
+    #[test]
+    fn gsi_catches_up_after_the_delay() {
+        let clock = ManualClock::new();
+        let mut table = orders_table(&clock);
+
+        table.put_item(order("o1", "open")).unwrap();
+        assert_eq!(with_status(&table, "open"), 0);
+
+        clock.advance(LAG);
+        assert_eq!(with_status(&table, "open"), 1);
+
+        table
+            .update_item(
+                &PrimaryKey::composite("c1", "o1"),
+                UpdateExpression::new().set("status", "shipped"),
+            )
+            .unwrap();
+        assert_eq!(with_status(&table, "open"), 1);
+        assert_eq!(with_status(&table, "shipped"), 0);
+
+        clock.advance(LAG);
+        assert_eq!(with_status(&table, "open"), 0);
+        assert_eq!(with_status(&table, "shipped"), 1);
+    }
+
+    #[test]
+    fn propagate_applies_everything_pending() {
+        let clock = ManualClock::new();
+        let mut table = orders_table(&clock);
+
+        table.put_item(order("o1", "open")).unwrap();
+        table.put_item(order("o2", "open")).unwrap();
+        table
+            .delete_item(&PrimaryKey::composite("c1", "o1"))
+            .unwrap();
+        assert_eq!(table.eventual_consistency().unwrap().pending_count(), 3);
+
+        assert_eq!(table.propagate(), 3);
+        assert_eq!(with_status(&table, "open"), 1);
+        assert_eq!(table.propagate(), 0);
+    }
+
+    #[test]
+    fn eventually_consistent_reads_can_be_stale() {
+        let clock = ManualClock::new();
+        let mut table = orders_table(&clock);
+        let key = PrimaryKey::composite("c1", "o1");
+
+        table.put_item(order("o1", "open")).unwrap();
+        clock.advance(LAG);
+        table.put_item(order("o1", "shipped")).unwrap();
+
+        let stale = table
+            .get(GetRequest::new(key.clone()).consistent_read(false))
+            .unwrap()
+            .unwrap();
+        assert_eq!(stale.get("status").unwrap().as_s(), Some("open"));
+        let fresh = table.get(GetRequest::new(key.clone())).unwrap().unwrap();
+        assert_eq!(fresh.get("status").unwrap().as_s(), Some("shipped"));
+
+        table.delete_item(&key).unwrap();
+        table.put_item(order("o2", "open")).unwrap();
+        let stale = table
+            .query(QueryRequest::new(KeyCondition::pk("c1")).consistent_read(false))
+            .unwrap();
+        let ids: Vec<_> = stale
+            .items
+            .iter()
+            .map(|item| item.get("id").unwrap().as_s().unwrap())
+            .collect();
+        assert_eq!(ids, ["o1"]);
+        assert_eq!(table.query(KeyCondition::pk("c1")).unwrap().count, 1);
+
+        clock.advance(LAG);
+        assert!(
+            table
+                .get(GetRequest::new(key).consistent_read(false))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn disabling_the_simulation_catches_up() {
+        let clock = ManualClock::new();
+        let mut table = orders_table(&clock);
+
+        table.put_item(order("o1", "open")).unwrap();
+        table.disable_eventual_consistency();
+        assert_eq!(with_status(&table, "open"), 1);
+
+        table.put_item(order("o2", "open")).unwrap();
+        assert_eq!(with_status(&table, "open"), 2);
+    }
+}
+
+mod full_text_search {
+    use super::*;
+    use nosquealdb::{SearchQuery, SearchRequest, SimpleTokenizer, TextIndexBuilder};
+
+    fn product(id: &str, name: &str, description: &str) -> Item {
+        Item::new()
+            .with_s("id", id)
+            .with_s("name", name)
+            .with_s("description", description)
+    }
+
+    fn catalog() -> Table {
+        let mut table = TableBuilder::new("products", KeySchema::simple("id", KeyType::S))
+            .with_text_index(
+                TextIndexBuilder::new("text")
+                    .attributes(["name", "description"])
+                    .tokenizer(SimpleTokenizer::new().with_stemming()),
+            )
+            .build()
+            .unwrap();
+        table
+            .put_item(product(
+                "p1",
+                "Trail Running Shoes",
+                "light shoes for trails",
+            ))
+            .unwrap();
+        table
+            .put_item(product("p2", "Road Running Shoe", "cushioned road shoe"))
+            .unwrap();
+        table
+            .put_item(product(
+                "p3",
+                "Hiking Boots",
+                "waterproof boots for hiking trails",
+            ))
+            .unwrap();
+        table
+    }
+
+    fn sorted(mut ids: Vec<String>) -> Vec<String> {
+        ids.sort();
+        ids
+    }
+
+    fn ids(table: &Table, request: impl Into<SearchRequest>) -> Vec<String> {
+        table
+            .search("text", request)
+            .unwrap()
+            .items()
+            .map(|item| item.get("id").unwrap().as_s().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn stays_in_sync_with_writes() {
+        let mut table = catalog();
+        assert_eq!(sorted(ids(&table, "shoes")), ["p1", "p2"]);
+        assert_eq!(ids(&table, "boots trail"), ["p3"]);
+
+        table
+            .update_item(
+                &PrimaryKey::simple("p2"),
+                UpdateExpression::new().set("description", "cushioned shoe for trails"),
+            )
+            .unwrap();
+        table.delete_item(&PrimaryKey::simple("p3")).unwrap();
+        assert_eq!(sorted(ids(&table, "trail")), ["p1", "p2"]);
+
+        table
+            .batch_write(vec![product("p4", "Trail Socks", "wool")])
+            .unwrap();
+        assert_eq!(ids(&table, "socks OR boots"), ["p4"]);
+        assert_eq!(table.text_index("text").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn phrases_and_ranking() {
+        let table = catalog();
+        assert_eq!(sorted(ids(&table, r#""running shoes""#)), ["p1", "p2"]);
+        assert_eq!(ids(&table, r#""shoes running""#), Vec::<String>::new());
+
+        let result = table.search("text", "hiking OR shoes").unwrap();
+        assert_eq!(result.total, 3);
+        // hiking appears twice in p3 and is rarer than shoe
+        assert_eq!(result.hits[0].key, PrimaryKey::simple("p3"));
+    }
+
+    #[test]
+    fn pages_through_ranked_hits() {
+        let table = catalog();
+        let query = SearchQuery::any([SearchQuery::term("shoe"), SearchQuery::term("boots")]);
+        let all = ids(&table, query.clone());
+        assert_eq!(all.len(), 3);
+
+        let mut paged = Vec::new();
+        let mut request = SearchRequest::new(query).limit(2);
+        loop {
+            let page = table.search("text", request.clone()).unwrap();
+            assert_eq!(page.total, 3);
+            paged.extend(
+                page.items()
+                    .map(|item| item.get("id").unwrap().as_s().unwrap().to_string()),
+            );
+            match page.last {
+                Some(cursor) => request = request.start_after(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(paged, all);
+    }
+
+    #[test]
+    fn unknown_index_fails() {
+        let table = catalog();
+        let err = table.search("missing", "shoes").unwrap_err();
+        assert!(err.is_index_not_found());
+    }
+}
+
+mod geo_indexes {
+    use super::*;
+    use nosquealdb::{BoundingBox, GeoIndexBuilder, GeoPoint, condition::AttributePath};
+
+    fn cafe(id: &str, lat: f64, lng: f64) -> Item {
+        let mut location = BTreeMap::new();
+        location.insert("lat".to_string(), AttributeValue::N(lat.to_string()));
+        location.insert("lng".to_string(), AttributeValue::N(lng.to_string()));
+        Item::new().with_s("id", id).with_m("location", location)
+    }
+
+    fn city() -> Table {
+        let mut table = TableBuilder::new("cafes", KeySchema::simple("id", KeyType::S))
+            .with_geo_index(GeoIndexBuilder::new("nearby").coordinates(
+                AttributePath::new("location").key("lat"),
+                AttributePath::new("location").key("lng"),
+            ))
+            .build()
+            .unwrap();
+        table.put_item(cafe("c1", 40.7580, -73.9855)).unwrap();
+        table.put_item(cafe("c2", 40.7527, -73.9772)).unwrap();
+        table.put_item(cafe("c3", 40.7061, -74.0087)).unwrap();
+        table.put_item(cafe("c4", 40.6782, -73.9442)).unwrap();
+        table
+    }
+
+    fn ids(hits: &[nosquealdb::GeoHit]) -> Vec<&str> {
+        hits.iter()
+            .map(|hit| hit.item.get("id").unwrap().as_s().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn radius_returns_nearest_first() {
+        let table = city();
+        let times_square = GeoPoint::new(40.7580, -73.9855);
+
+        let hits = table.query_radius("nearby", times_square, 1_500.0).unwrap();
+        assert_eq!(ids(&hits), ["c1", "c2"]);
+        assert!(hits[0].distance < 1.0);
+        assert!(hits[1].distance > 800.0 && hits[1].distance < 1_000.0);
+
+        let hits = table
+            .query_radius("nearby", times_square, 10_000.0)
+            .unwrap();
+        assert_eq!(ids(&hits), ["c1", "c2", "c3", "c4"]);
+    }
+
+    #[test]
+    fn bounding_box_query() {
+        let table = city();
+        let midtown = BoundingBox::new(GeoPoint::new(40.74, -74.00), GeoPoint::new(40.77, -73.97));
+        let hits = table.query_bbox("nearby", midtown).unwrap();
+        let mut found = ids(&hits);
+        found.sort();
+        assert_eq!(found, ["c1", "c2"]);
+    }
+
+    #[test]
+    fn stays_in_sync_with_writes() {
+        let mut table = city();
+        let downtown = GeoPoint::new(40.7061, -74.0087);
+
+        // c1 moves downtown
+        table.put_item(cafe("c1", 40.7065, -74.0090)).unwrap();
+        let hits = table.query_radius("nearby", downtown, 500.0).unwrap();
+        assert_eq!(ids(&hits), ["c3", "c1"]);
+
+        table.delete_item(&PrimaryKey::simple("c3")).unwrap();
+        let hits = table.query_radius("nearby", downtown, 500.0).unwrap();
+        assert_eq!(ids(&hits), ["c1"]);
+
+        // an item without coordinates drops out of the index
+        table
+            .put_item(Item::new().with_s("id", "c1").with_s("name", "closed"))
+            .unwrap();
+        assert!(
+            table
+                .query_radius("nearby", downtown, 500.0)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(table.geo_index("nearby").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn added_index_covers_existing_items() {
+        let mut table = Table::new("cafes", KeySchema::simple("id", KeyType::S));
+        table
+            .put_item(
+                Item::new()
+                    .with_s("id", "a")
+                    .with_n("lat", 0.0)
+                    .with_n("lng", 179.999),
+            )
+            .unwrap();
+        table
+            .put_item(
+                Item::new()
+                    .with_s("id", "b")
+                    .with_n("lat", 0.0)
+                    .with_n("lng", -179.999),
+            )
+            .unwrap();
+        table.add_geo_index(GeoIndexBuilder::new("geo")).unwrap();
+
+        // the circle wraps across the antimeridian
+        let hits = table
+            .query_radius("geo", GeoPoint::new(0.0, 180.0), 1_000.0)
+            .unwrap();
+        assert_eq!(hits.len(), 2);
+
+        let err = table
+            .query_radius("missing", GeoPoint::new(0.0, 0.0), 1.0)
+            .unwrap_err();
+        assert!(err.is_index_not_found());
+        let err = table
+            .query_radius("geo", GeoPoint::new(95.0, 0.0), 1.0)
+            .unwrap_err();
+        assert!(err.is_validation_error());
+
+        table.drop_geo_index("geo").unwrap();
+        assert_eq!(table.geo_index_names().count(), 0);
+    }
+
+    #[test]
+    fn radius_reaches_the_widest_longitudes() {
+        let mut table = TableBuilder::new("cabins", KeySchema::simple("id", KeyType::S))
+            .with_geo_index(GeoIndexBuilder::new("geo"))
+            .build()
+            .unwrap();
+        // about 222168 m out, west of where the circle is widest
+        table
+            .put_item(
+                Item::new()
+                    .with_s("id", "a")
+                    .with_n("lat", 70.13)
+                    .with_n("lng", -67.95),
+            )
+            .unwrap();
+
+        let hits = table
+            .query_radius("geo", GeoPoint::new(70.0, -62.1), 222_390.0)
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+}
+
+mod vector_search {
+    use super::*;
+    use nosquealdb::{HnswConfig, Metric, VectorIndexBuilder, pack_vector};
+
+    fn doc(id: &str, category: &str, embedding: &[f32]) -> Item {
+        let values = embedding
+            .iter()
+            .map(|v| AttributeValue::N(v.to_string()))
+            .collect();
+        Item::new()
+            .with_s("id", id)
+            .with_s("category", category)
+            .with("embedding", AttributeValue::L(values))
+    }
+
+    fn ids(hits: &[nosquealdb::VectorHit]) -> Vec<&str> {
+        hits.iter()
+            .map(|hit| hit.item.get("id").unwrap().as_s().unwrap())
+            .collect()
+    }
+
+    // deterministic pseudo-random vectors
+    fn vectors(count: usize, dimensions: usize) -> Vec<Vec<f32>> {
+        let mut state = 42u64;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+        };
+        (0..count)
+            .map(|_| (0..dimensions).map(|_| next()).collect())
+            .collect()
+    }
+
+    fn table(builder: VectorIndexBuilder) -> Table {
+        let mut table = TableBuilder::new("docs", KeySchema::simple("id", KeyType::S))
+            .with_vector_index(builder)
+            .build()
+            .unwrap();
+        table.put_item(doc("d1", "news", &[1.0, 0.0, 0.0])).unwrap();
+        table.put_item(doc("d2", "blog", &[0.9, 0.1, 0.0])).unwrap();
+        table.put_item(doc("d3", "news", &[0.0, 1.0, 0.0])).unwrap();
+        table
+            .put_item(
+                Item::new()
+                    .with_s("id", "d4")
+                    .with_s("category", "blog")
+                    .with_b("embedding", pack_vector(&[0.0, 0.0, 1.0])),
+            )
+            .unwrap();
+        table
+    }
+
+    #[test]
+    fn invalid_indexes_are_errors() {
+        let err = TableBuilder::new("docs", KeySchema::simple("id", KeyType::S))
+            .with_vector_index(VectorIndexBuilder::new("embeddings"))
+            .build()
+            .unwrap_err();
+        assert!(err.is_validation_error());
+
+        let mut table = table(VectorIndexBuilder::new("embeddings").dimensions(3));
+        let err = table
+            .add_vector_index(VectorIndexBuilder::new("small").dimensions(0))
+            .unwrap_err();
+        assert!(err.is_validation_error());
+        assert_eq!(
+            table.vector_index_names().collect::<Vec<_>>(),
+            ["embeddings"]
+        );
+    }
+
+    #[test]
+    fn stays_in_sync_with_writes() {
+        for builder in [
+            VectorIndexBuilder::new("embeddings").dimensions(3),
+            VectorIndexBuilder::new("embeddings")
+                .dimensions(3)
+                .hnsw(HnswConfig::new()),
+        ] {
+            let mut table = table(builder);
+            let hits = table.knn("embeddings", &[1.0, 0.0, 0.0], 2).unwrap();
+            assert_eq!(ids(&hits), ["d1", "d2"]);
+            assert!(hits[0].distance.abs() < 1e-6);
+
+            table
+                .update_item(
+                    &PrimaryKey::simple("d4"),
+                    UpdateExpression::new().set(
+                        "embedding",
+                        AttributeValue::L(vec![1.into(), 0.into(), 0.into()]),
+                    ),
+                )
+                .unwrap();
+            table.delete_item(&PrimaryKey::simple("d1")).unwrap();
+
+            let hits = table.knn("embeddings", &[1.0, 0.0, 0.0], 2).unwrap();
+            assert_eq!(ids(&hits), ["d4", "d2"]);
+            assert_eq!(table.vector_index("embeddings").unwrap().len(), 3);
+        }
+    }
+
+    #[test]
+    fn condition_prefilter() {
+        let table = table(
+            VectorIndexBuilder::new("embeddings")
+                .dimensions(3)
+                .metric(Metric::L2)
+                .hnsw(HnswConfig::new()),
+        );
+        let hits = table
+            .knn_where(
+                "embeddings",
+                &[1.0, 0.0, 0.0],
+                5,
+                &attr("category").eq("news"),
+            )
+            .unwrap();
+        assert_eq!(ids(&hits), ["d1", "d3"]);
+    }
+
+    #[test]
+    fn approximate_matches_exact() {
+        let mut table = TableBuilder::new("docs", KeySchema::simple("id", KeyType::S))
+            .with_vector_index(VectorIndexBuilder::new("exact").dimensions(8))
+            .with_vector_index(
+                VectorIndexBuilder::new("hnsw")
+                    .dimensions(8)
+                    .hnsw(HnswConfig::new().m(8)),
+            )
+            .build()
+            .unwrap();
+        for (i, vector) in vectors(500, 8).iter().enumerate() {
+            table
+                .put_item(doc(&format!("d{:03}", i), "any", vector))
+                .unwrap();
+        }
+
+        let mut found = 0;
+        for query in vectors(520, 8).iter().skip(500) {
+            let exact = table.knn("exact", query, 10).unwrap();
+            let approximate = table.knn("hnsw", query, 10).unwrap();
+            found += ids(&approximate)
+                .iter()
+                .filter(|id| ids(&exact).contains(id))
+                .count();
+        }
+        // recall@10 over 20 queries
+        assert!(found >= 180, "recall {}/200", found);
+    }
+
+    #[test]
+    fn rejects_bad_queries() {
+        let mut table = table(VectorIndexBuilder::new("embeddings").dimensions(3));
+        assert!(
+            table
+                .knn("missing", &[1.0, 0.0, 0.0], 1)
+                .unwrap_err()
+                .is_index_not_found()
+        );
+        assert!(
+            table
+                .knn("embeddings", &[1.0, 0.0], 1)
+                .unwrap_err()
+                .is_validation_error()
+        );
+        assert!(
+            table
+                .knn("embeddings", &[f32::NAN, 0.0, 0.0], 1)
+                .unwrap_err()
+                .is_validation_error()
+        );
+        assert!(
+            table
+                .knn("embeddings", &[1.0, 0.0, 0.0], 0)
+                .unwrap()
+                .is_empty()
+        );
+
+        table.drop_vector_index("embeddings").unwrap();
+        assert_eq!(table.vector_index_names().count(), 0);
+    }
+}
+
+mod aggregations {
+    use super::*;
+    use nosquealdb::{AggregateValue, Aggregation, ScanRequest};
+
+    fn orders() -> Table {
+        let mut table = TableBuilder::new(
+            "orders",
+            KeySchema::composite("customer", KeyType::S, "order_id", KeyType::N),
+        )
+        .with_gsi(
+            GsiBuilder::new("by_status", KeySchema::simple("status", KeyType::S))
+                .include(["amount", "region"]),
+        )
+        .build()
+        .unwrap();
+        for (customer, id, status, region, amount) in [
+            ("alice", 1, "shipped", "eu", "19.99"),
+            ("alice", 2, "pending", "eu", "5.01"),
+            ("alice", 3, "shipped", "us", "100"),
+            ("bob", 1, "shipped", "us", "0.1"),
+            ("bob", 2, "cancelled", "us", "0.2"),
+        ] {
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("customer", customer)
+                        .with_n("order_id", id)
+                        .with_s("status", status)
+                        .with_s("region", region)
+                        .with_n("amount", amount),
+                )
+                .unwrap();
+        }
+        table
+    }
+
+    #[test]
+    fn over_a_partition() {
+        let table = orders();
+        let aggregation = Aggregation::new()
+            .count()
+            .sum("amount")
+            .avg("amount")
+            .max("amount");
+        let result = table
+            .aggregate_query(KeyCondition::pk("alice"), &aggregation)
+            .unwrap();
+
+        assert_eq!(result.count, 3);
+        assert_eq!(result.get("count"), Some(&AggregateValue::Count(3)));
+        assert_eq!(result.get("sum(amount)").unwrap().as_number(), Some("125"));
+        assert_eq!(
+            result.get("avg(amount)").unwrap().as_number(),
+            Some("41.6666666666666667")
+        );
+        assert_eq!(result.get("max(amount)").unwrap().as_number(), Some("100"));
+
+        // the request's filter and the aggregation's filter both apply
+        let request = QueryRequest::new(KeyCondition::pk("alice")).filter(attr("region").eq("eu"));
+        let result = table
+            .aggregate_query(
+                request,
+                &Aggregation::new()
+                    .sum("amount")
+                    .filter(attr("status").eq("shipped")),
+            )
+            .unwrap();
+        assert_eq!(result.count, 1);
+        assert_eq!(
+            result.get("sum(amount)").unwrap().as_number(),
+            Some("19.99")
+        );
+    }
+
+    #[test]
+    fn grouped_scan_is_exact() {
+        let table = orders();
+        let aggregation = Aggregation::new().sum("amount").count().group_by("region");
+        let result = table
+            .aggregate_scan(ScanRequest::new(), &aggregation)
+            .unwrap();
+
+        assert_eq!(result.groups.len(), 2);
+        let eu = result
+            .group(&[Some(AttributeValue::S("eu".into()))])
+            .unwrap();
+        assert_eq!(eu.get("sum(amount)").unwrap().as_number(), Some("25"));
+        let us = result
+            .group(&[Some(AttributeValue::S("us".into()))])
+            .unwrap();
+        // 100 + 0.1 + 0.2 with no floating point drift
+        assert_eq!(us.get("sum(amount)").unwrap().as_number(), Some("100.3"));
+        assert_eq!(us.get("count"), Some(&AggregateValue::Count(3)));
+    }
+
+    #[test]
+    fn over_a_gsi() {
+        let table = orders();
+        let result = table
+            .aggregate_gsi(
+                "by_status",
+                KeyCondition::pk("shipped"),
+                &Aggregation::new().sum("amount").min("region"),
+            )
+            .unwrap();
+        assert_eq!(result.count, 3);
+        assert_eq!(
+            result.get("sum(amount)").unwrap().as_number(),
+            Some("120.09")
+        );
+        assert_eq!(
+            result.get("min(region)").unwrap().as_value(),
+            Some(&AttributeValue::S("eu".into()))
+        );
+
+        let err = table
+            .aggregate_gsi(
+                "missing",
+                KeyCondition::pk("x"),
+                &Aggregation::new().count(),
+            )
+            .unwrap_err();
+        assert!(err.is_index_not_found());
+        let err = table
+            .aggregate_scan(ScanRequest::new(), &Aggregation::new())
+            .unwrap_err();
+        assert!(err.is_validation_error());
+    }
+}
+
+mod partiql {
+    use super::*;
+    use nosquealdb::Plan;
+
+    fn orders() -> Table {
+        let mut table = TableBuilder::new(
+            "orders",
+            KeySchema::composite("customer", KeyType::S, "order_id", KeyType::N),
+        )
+        .with_gsi(GsiBuilder::new(
+            "by_status",
+            KeySchema::simple("status", KeyType::S),
+        ))
+        .build()
+        .unwrap();
+        for (customer, id, status, amount) in [
+            ("alice", 1, "shipped", 20),
+            ("alice", 2, "pending", 5),
+            ("alice", 3, "shipped", 100),
+            ("bob", 1, "pending", 1),
+        ] {
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("customer", customer)
+                        .with_n("order_id", id)
+                        .with_s("status", status)
+                        .with_n("amount", amount),
+                )
+                .unwrap();
+        }
+        table
+    }
+
+    fn ids(items: &[Item]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|item| {
+                (
+                    item.get("customer").unwrap().as_s().unwrap().to_string(),
+                    item.get("order_id").unwrap().as_n().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn selects_use_keys_when_they_can() {
+        let mut table = orders();
+        let sql = "SELECT * FROM orders WHERE customer = ? AND order_id >= 2 AND amount > 10";
+        let params = [AttributeValue::S("alice".into())];
+        assert!(matches!(
+            table.plan_statement(sql, &params).unwrap(),
+            Plan::Query {
+                index: None,
+                filter: Some(_),
+                ..
+            }
+        ));
+        let items = table.execute_statement(sql, &params).unwrap();
+        assert_eq!(ids(&items), [("alice".into(), "3".into())]);
+
+        let sql = "SELECT customer, order_id FROM orders.\"by_status\" WHERE status = 'pending'";
+        assert!(matches!(
+            table.plan_statement(sql, &[]).unwrap(),
+            Plan::Query {
+                index: Some(_),
+                filter: None,
+                ..
+            }
+        ));
+        let mut items = table.execute_statement(sql, &[]).unwrap();
+        items.sort_by_key(|item| item.get("customer").unwrap().as_s().unwrap().to_string());
+        assert_eq!(
+            ids(&items),
+            [("alice".into(), "2".into()), ("bob".into(), "1".into())]
+        );
+        assert_eq!(items[0].get("amount"), None);
+
+        let sql = "SELECT * FROM orders WHERE amount IN (1, 5) OR status = 'none'";
+        assert!(matches!(
+            table.plan_statement(sql, &[]).unwrap(),
+            Plan::Scan { .. }
+        ));
+        assert_eq!(table.execute_statement(sql, &[]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn writes() {
+        let mut table = orders();
+        let insert = "INSERT INTO orders VALUE {'customer': 'carol', 'order_id': 1, 'status': ?}";
+        table
+            .execute_statement(insert, &[AttributeValue::S("pending".into())])
+            .unwrap();
+        assert!(
+            table
+                .execute_statement(insert, &[AttributeValue::S("pending".into())])
+                .unwrap_err()
+                .item_already_exists()
+        );
+
+        table
+            .execute_statement(
+                "UPDATE orders SET status = 'shipped' REMOVE amount \
+                 WHERE customer = 'alice' AND order_id = 2 AND status = 'pending'",
+                &[],
+            )
+            .unwrap();
+        let key = PrimaryKey::composite("alice", KeyValue::N("2".into()));
+        let item = table.get_item(&key).unwrap().unwrap();
+        assert_eq!(
+            item.get("status"),
+            Some(&AttributeValue::S("shipped".into()))
+        );
+        assert_eq!(item.get("amount"), None);
+
+        // updates never create items
+        let missing = "UPDATE orders SET status = 'x' WHERE customer = 'dave' AND order_id = 1";
+        assert!(table.execute_statement(missing, &[]).is_err());
+        assert!(
+            table
+                .get_item(&PrimaryKey::composite("dave", KeyValue::N("1".into())))
+                .unwrap()
+                .is_none()
+        );
+
+        table
+            .execute_statement(
+                "DELETE FROM orders WHERE customer = 'alice' AND order_id = 2",
+                &[],
+            )
+            .unwrap();
+        assert!(table.get_item(&key).unwrap().is_none());
+    }
+
+    #[test]
+    fn errors() {
+        let mut table = orders();
+        let err = table
+            .execute_statement("SELECT * FROM orders WHERE customer = ?", &[])
+            .unwrap_err();
+        assert!(err.is_validation_error());
+        assert!(
+            err.to_string()
+                .contains("statement uses parameter 1, got 0")
+        );
+
+        let err = table
+            .execute_statement("SELECT * FROM orders WHERE", &[])
+            .unwrap_err();
+        assert!(err.to_string().contains("position 26"));
+
+        assert!(
+            table
+                .execute_statement("SELECT * FROM users", &[])
+                .unwrap_err()
+                .is_validation_error()
+        );
+        assert!(
+            table
+                .execute_statement("SELECT * FROM orders.missing", &[])
+                .unwrap_err()
+                .is_index_not_found()
+        );
+        assert!(
+            table
+                .execute_statement("DELETE FROM orders WHERE customer = 'alice'", &[])
+                .unwrap_err()
+                .is_validation_error()
+        );
+    }
+}
+
+mod condition_expressions {
+    use super::*;
+    use nosquealdb::condition::{AttributePath, Condition};
+    use nosquealdb::{ScanRequest, Select};
+    use std::collections::HashMap;
+
+    #[test]
+    fn parsed_conditions_drive_writes_and_scans() {
+        let mut table = Table::new("users", KeySchema::simple("pk", KeyType::S));
+        for (pk, status, tags) in [
+            ("u1", "active", vec!["a", "b", "c", "d"]),
+            ("u2", "banned", vec!["a"]),
+            ("u3", "pending", vec!["a", "b"]),
+        ] {
+            table
+                .put_item(Item::new().with_s("pk", pk).with_s("status", status).with(
+                    "tags",
+                    AttributeValue::Ss(tags.into_iter().map(String::from).collect()),
+                ))
+                .unwrap();
+        }
+
+        let names = HashMap::from([
+            ("#pk".to_string(), "pk".to_string()),
+            ("#s".to_string(), "status".to_string()),
+        ]);
+        let values = HashMap::from([
+            (":a".to_string(), AttributeValue::S("active".into())),
+            (":b".to_string(), AttributeValue::S("banned".into())),
+            (":n".to_string(), AttributeValue::N("1".into())),
+        ]);
+        let condition = Condition::parse(
+            "attribute_exists(#pk) AND #s IN (:a, :b) OR size(tags) > :n",
+            &names,
+            &values,
+        )
+        .unwrap();
+        let mut found: Vec<String> = table
+            .scan(ScanRequest::new().filter(condition))
+            .unwrap()
+            .iter()
+            .map(|item| item.get("pk").unwrap().as_s().unwrap().to_string())
+            .collect();
+        found.sort();
+        assert_eq!(found, ["u1", "u2", "u3"]);
+
+        let guard = Condition::parse("NOT (#s = :b)", &names, &values).unwrap();
+        let request = PutRequest::new(Item::new().with_s("pk", "u2").with_s("status", "active"))
+            .condition(guard);
+        assert!(table.put(request).unwrap_err().is_condition_failed());
+
+        let err = Condition::parse("#s = :missing", &names, &values).unwrap_err();
+        assert_eq!(err.position(), Some(5));
+        let err: nosquealdb::TableError = err.into();
+        assert!(err.is_validation_error());
+    }
+
+    #[test]
+    fn parsed_update_expressions() {
+        let mut table = Table::new("users", KeySchema::simple("pk", KeyType::S));
+        table
+            .put_item(
+                Item::new()
+                    .with_s("pk", "u1")
+                    .with_n("visits", 4)
+                    .with_s("temp", "x")
+                    .with(
+                        "tags",
+                        AttributeValue::Ss(["a", "b"].into_iter().map(String::from).collect()),
+                    ),
+            )
+            .unwrap();
+
+        let names = HashMap::from([("#name".to_string(), "name".to_string())]);
+        let values = HashMap::from([
+            (":name".to_string(), AttributeValue::S("Ada".into())),
+            (":zero".to_string(), AttributeValue::N("0".into())),
+            (":one".to_string(), AttributeValue::N("1".into())),
+            (
+                ":b".to_string(),
+                AttributeValue::Ss(["b".to_string()].into_iter().collect()),
+            ),
+        ]);
+        let update = UpdateExpression::parse(
+            "SET #name = :name, score = if_not_exists(score, :zero) REMOVE temp \
+             ADD visits :one DELETE tags :b",
+            &names,
+            &values,
+        )
+        .unwrap();
+        let key = PrimaryKey::simple("u1");
+        table.update_item(&key, update).unwrap();
+
+        let item = table.get_item(&key).unwrap().unwrap();
+        assert_eq!(item.get("name"), Some(&AttributeValue::S("Ada".into())));
+        assert_eq!(item.get("score"), Some(&AttributeValue::N("0".into())));
+        assert_eq!(item.get("visits"), Some(&AttributeValue::N("5".into())));
+        assert_eq!(item.get("temp"), None);
+        assert_eq!(
+            item.get("tags"),
+            Some(&AttributeValue::Ss(["a".to_string()].into_iter().collect()))
+        );
+
+        let err = UpdateExpression::parse("SET a = :one ADD a :one", &names, &values).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid expression at position 17: path a overlaps path a"
+        );
+    }
+
+    #[test]
+    fn parsed_key_conditions_and_projections() {
+        let schema = KeySchema::composite("pk", KeyType::S, "sk", KeyType::S);
+        let mut table = Table::new("orders", schema.clone());
+        let mut address = BTreeMap::new();
+        address.insert("city".to_string(), AttributeValue::S("Oslo".into()));
+        address.insert("zip".to_string(), AttributeValue::S("0150".into()));
+        for sk in ["order#1", "order#2", "profile"] {
+            table
+                .put_item(
+                    Item::new()
+                        .with_s("pk", "user#1")
+                        .with_s("sk", sk)
+                        .with("address", AttributeValue::M(address.clone())),
+                )
+                .unwrap();
+        }
+
+        let names = HashMap::from([
+            ("#pk".to_string(), "pk".to_string()),
+            ("#sk".to_string(), "sk".to_string()),
+        ]);
+        let values = HashMap::from([
+            (":p".to_string(), AttributeValue::S("user#1".into())),
+            (":prefix".to_string(), AttributeValue::S("order#".into())),
+        ]);
+        let key_condition = KeyCondition::parse(
+            "#pk = :p AND begins_with(#sk, :prefix)",
+            &names,
+            &values,
+            &schema,
+        )
+        .unwrap();
+        let projection =
+            AttributePath::parse_projection("sk, address.city", &HashMap::new()).unwrap();
+        let result = table
+            .query(QueryRequest::new(key_condition).select(Select::SpecificAttributes(projection)))
+            .unwrap();
+
+        assert_eq!(result.items.len(), 2);
+        let mut city = BTreeMap::new();
+        city.insert("city".to_string(), AttributeValue::S("Oslo".into()));
+        assert_eq!(
+            result.items[0],
+            Item::new()
+                .with_s("sk", "order#1")
+                .with("address", AttributeValue::M(city))
+        );
+
+        // plain strings now name nested paths too
+        let found = table
+            .scan(ScanRequest::new().filter(attr("address.zip").eq("0150")))
+            .unwrap();
+        assert_eq!(found.len(), 3);
+
+        let err = KeyCondition::parse("#sk = :p", &names, &values, &schema).unwrap_err();
+        assert!(nosquealdb::TableError::from(err).is_validation_error());
+    }
+}
+
 mod edge_cases {
     use super::*;
 
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn empty_string_key() {
         let mut table = Table::new("test", KeySchema::simple("pk", KeyType::S));
 